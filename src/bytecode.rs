@@ -11,7 +11,9 @@
 //   2. @OnlyIn(Dist.DEDICATED_SERVER) / @Environment(EnvType.SERVER)     → Server
 //   3. References to client-exclusive classes (RenderSystem, Screen, …)  → Client
 //   4. References to dedicated-server-exclusive classes                   → Server
-//   5. No evidence found                                                  → None
+//   5. Only with the layout hint: assets/ but no data/ (or the reverse)  → Client
+//      (Server), flagged as a heuristic — resource packs ship assets too
+//   6. No evidence found                                                  → None
 // ─────────────────────────────────────────────────────────────────────────────
 
 use std::io::Read;
@@ -105,6 +107,8 @@ pub enum Confidence {
     Annotation,
     /// Reference to a class that cannot exist outside that dist
     ClassReference,
+    /// Only the jar's top-level folders: a guess, not evidence
+    Layout,
     /// No signal found
     None,
 }
@@ -132,9 +136,10 @@ impl BytecodeEvidence {
 
 // ── Core scanning ─────────────────────────────────────────────────────────────
 
-/// Scan every .class file inside a jar for side-detection signals.
+/// Scan every .class file inside a jar for side-detection signals.  With
+/// `layout`, a jar with no class evidence falls back to [`layout_side`].
 /// Returns `None` if the jar cannot be opened as a zip archive.
-pub fn analyse_jar(path: &str) -> Option<BytecodeEvidence> {
+pub fn analyse_jar(path: impl AsRef<std::path::Path>, layout: bool) -> Option<BytecodeEvidence> {
     let file = std::fs::File::open(path).ok()?;
     let mut archive = zip::ZipArchive::new(file).ok()?;
 
@@ -143,12 +148,16 @@ pub fn analyse_jar(path: &str) -> Option<BytecodeEvidence> {
     let mut client_annotations:  Vec<String> = Vec::new();
     let mut server_annotations:  Vec<String> = Vec::new();
     let mut classes_scanned = 0usize;
+    let mut top_dirs = std::collections::BTreeSet::new();
 
     for i in 0..archive.len() {
         let mut entry = match archive.by_index(i) {
             Ok(e) => e,
             Err(_) => continue,
         };
+        if let Some((top, _)) = entry.name().split_once('/') {
+            if !top_dirs.contains(top) { top_dirs.insert(top.to_string()); }
+        }
 
         // Only scan .class files
         if !entry.name().ends_with(".class") {
@@ -211,6 +220,8 @@ pub fn analyse_jar(path: &str) -> Option<BytecodeEvidence> {
                 server_signals[0]
             )),
         )
+    } else if let Some((side, why)) = layout.then(|| layout_side(&top_dirs)).flatten() {
+        (side, Confidence::Layout, Some(format!("{why} (layout heuristic)")))
     } else {
         (DetectedSide::Unknown, Confidence::None, None)
     };
@@ -223,6 +234,16 @@ pub fn analyse_jar(path: &str) -> Option<BytecodeEvidence> {
     })
 }
 
+/// Client assets with no data packs suggest a client-only mod; data with no
+/// assets a server-only one.  Weak: plenty of universal mods are built either way.
+pub fn layout_side(top_dirs: &std::collections::BTreeSet<String>) -> Option<(DetectedSide, &'static str)> {
+    match (top_dirs.contains("assets"), top_dirs.contains("data")) {
+        (true, false) => Some((DetectedSide::Client, "assets/ but no data/")),
+        (false, true) => Some((DetectedSide::Server, "data/ but no assets/")),
+        _ => None,
+    }
+}
+
 // ── Low-level constant-pool extraction ───────────────────────────────────────
 //
 // JVM .class constant pool format (JVMS §4.4):
@@ -431,4 +452,12 @@ mod tests {
         assert!(!ss.is_empty(), "should detect DedicatedServer as server signal");
         assert!(cs.is_empty());
     }
+
+    #[test]
+    fn layout_only_guesses_from_one_sided_folders() {
+        let dirs = |names: &[&str]| names.iter().map(|n| n.to_string()).collect();
+        assert_eq!(layout_side(&dirs(&["META-INF", "assets"])).map(|(s, _)| s), Some(DetectedSide::Client));
+        assert_eq!(layout_side(&dirs(&["data"])).map(|(s, _)| s), Some(DetectedSide::Server));
+        assert_eq!(layout_side(&dirs(&["assets", "data"])), None);
+    }
 }
@@ -0,0 +1,149 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// checksum.rs — Checksums (and keyed signatures) for published modules
+//
+// `lodestone checksum <module.json>` writes `<module.json>.sha256` next to
+// the module:
+//
+//   sha256 9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08
+//   hmac-sha256 …              # only with --key
+//
+// The digest is taken over the module's canonical form — its JSON reparsed
+// and written compactly with keys sorted — so re-indenting or gzipping the
+// file doesn't change it, while any edit to a tag or version does.  The
+// optional HMAC line is a signature for those holding the author's key.
+// `--verify-module` recomputes both when a module is loaded.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::read_module_file;
+
+/// HMAC block size for SHA-256.
+const BLOCK: usize = 64;
+
+pub fn sidecar_path(module: &str) -> PathBuf {
+    PathBuf::from(format!("{module}.sha256"))
+}
+
+/// The module at `path` reduced to compact, key-sorted JSON.
+pub fn canonical(path: &str) -> Result<Vec<u8>, String> {
+    let raw = read_module_file(path).map_err(|e| e.to_string())?;
+    let value: serde_json::Value = serde_json::from_str(&raw).map_err(|e| e.to_string())?;
+    serde_json::to_vec(&value).map_err(|e| e.to_string())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+pub fn sha256(data: &[u8]) -> String {
+    hex(&Sha256::digest(data))
+}
+
+/// HMAC-SHA256 of `data` under `key` (RFC 2104).
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> String {
+    let mut block = [0u8; BLOCK];
+    if key.len() > BLOCK {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let pad = |byte: u8| block.map(|b| b ^ byte);
+    let inner = Sha256::new().chain_update(pad(0x36)).chain_update(data).finalize();
+    hex(&Sha256::new().chain_update(pad(0x5c)).chain_update(inner).finalize())
+}
+
+/// What a sidecar records.
+#[derive(Debug, PartialEq)]
+pub struct Sidecar {
+    pub sha256:    String,
+    pub signature: Option<String>,
+}
+
+impl Sidecar {
+    fn of(canonical: &[u8], key: Option<&[u8]>) -> Self {
+        Self { sha256: sha256(canonical), signature: key.map(|k| hmac_sha256(k, canonical)) }
+    }
+
+    fn parse(raw: &str) -> Self {
+        let field = |name: &str| raw.lines()
+            .find_map(|l| l.trim().strip_prefix(name)?.strip_prefix(' ').map(|v| v.trim().to_string()));
+        Self { sha256: field("sha256").unwrap_or_default(), signature: field("hmac-sha256") }
+    }
+
+    fn render(&self) -> String {
+        let mut out = format!("sha256 {}\n", self.sha256);
+        if let Some(s) = &self.signature {
+            out.push_str(&format!("hmac-sha256 {s}\n"));
+        }
+        out
+    }
+}
+
+/// Write the sidecar for the module at `path`, signed when `key` is given.
+pub fn write(path: &str, key: Option<&[u8]>) -> Result<(PathBuf, Sidecar), String> {
+    let sidecar = Sidecar::of(&canonical(path)?, key);
+    let out = sidecar_path(path);
+    std::fs::write(&out, sidecar.render()).map_err(|e| format!("{}: {e}", out.display()))?;
+    Ok((out, sidecar))
+}
+
+/// Check the module at `path` against its sidecar.  A signature can only be
+/// checked with the key; without one just the checksum is.  `Ok` says what
+/// was verified.
+pub fn verify(path: &str, key: Option<&[u8]>) -> Result<&'static str, String> {
+    let side = sidecar_path(path);
+    if !Path::new(&side).is_file() {
+        return Err(format!("no checksum file ({} is missing)", side.display()));
+    }
+    let raw = std::fs::read_to_string(&side).map_err(|e| format!("{}: {e}", side.display()))?;
+    let recorded = Sidecar::parse(&raw);
+    let now = Sidecar::of(&canonical(path)?, key);
+    if recorded.sha256 != now.sha256 {
+        return Err("checksum mismatch: the module was changed after it was published".into());
+    }
+    match (&recorded.signature, &now.signature) {
+        (Some(want), Some(got)) if want != got => Err("signature mismatch: wrong key, or the checksum file was regenerated".into()),
+        (Some(_), Some(_)) => Ok("checksum and signature match"),
+        (Some(_), None)    => Ok("checksum matches; signature not checked (no key)"),
+        (None, _)          => Ok("checksum matches"),
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Unit tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hmac_matches_rfc_4231() {
+        assert_eq!(
+            hmac_sha256(b"Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+        );
+    }
+
+    #[test]
+    fn reformatting_passes_and_edits_fail() {
+        let d = std::env::temp_dir().join(format!("lodestone-checksum-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&d);
+        std::fs::create_dir_all(&d).unwrap();
+        let p = d.join("module.json");
+        let path = p.to_str().unwrap();
+        std::fs::write(&p, r#"{"header": {"module_name": "t"}, "mods": {"sodium": {"mod_tag": "Client"}}}"#).unwrap();
+        write(path, Some(b"secret")).unwrap();
+
+        std::fs::write(&p, "{\n  \"mods\": {\"sodium\": {\"mod_tag\": \"Client\"}},\n  \"header\": {\"module_name\": \"t\"}\n}").unwrap();
+        assert_eq!(verify(path, Some(b"secret")), Ok("checksum and signature match"));
+        assert!(verify(path, Some(b"guess")).unwrap_err().starts_with("signature mismatch"));
+        assert_eq!(verify(path, None), Ok("checksum matches; signature not checked (no key)"));
+
+        std::fs::write(&p, r#"{"header": {"module_name": "t"}, "mods": {"sodium": {"mod_tag": "Both"}}}"#).unwrap();
+        assert!(verify(path, None).unwrap_err().starts_with("checksum mismatch"));
+    }
+}
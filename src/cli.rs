@@ -0,0 +1,2325 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// cli.rs — Command-line entry points
+//
+// Running `lodestone` with no arguments opens the GUI.  Any arguments are
+// treated as a subcommand instead, so one-off jobs can be done from a shell
+// without clicking through the app:
+//
+//   lodestone scan [<dir>] --module <module.json> [--ndjson] [--strict-match] [--curseforge]
+//   lodestone stats [<dir>]
+//   lodestone misplaced <instance>
+//   lodestone incompatible [<dir>]
+//   lodestone duplicates [<dir>] [--jobs <n>] [--delete [--module <module.json>] [--no-backup] [--yes] [--force]]
+//   lodestone mapping [<dir>] [--hash] [--jobs <n>]
+//   lodestone icons [<dir>] --output <folder>
+//   lodestone graph [<dir>] [--module <module.json>] [--output <file.dot>]
+//   lodestone inspect <jar> [--module <module.json> [--tag <side>]]
+//   lodestone new-module <module.json> [--name <name>] [--author <author>] [--force]
+//   lodestone shell <module.json>
+//   lodestone get <module.json> <query>
+//   lodestone module bump <module.json> <major|minor|patch>
+//   lodestone edit <module.json> <mod id> [--tag <side>] [--note <text> | --clear-note]
+//   lodestone op <zip|move|copy|delete|quarantine|export> <dir> --module <module.json>
+//   lodestone modrinth-index [<dir>] --module <module.json> [--name <pack>] [--version-id <v>]
+//   lodestone slugs <module.json> [--refresh] [--dry-run]
+//   lodestone migrate <module.json>
+//   lodestone pack-tag <module.json> <pack.mrpack | modrinth modpack> [--name <pack>] [--dry-run]
+//   lodestone retag <module.json> (--loader <loader> | --match <pattern>) --tag <side>
+//   lodestone dedupe <module.json> [--dry-run]
+//   lodestone add-unknowns <module.json> <dir> [--default-tag <side>] [--dry-run] [--yes]
+//   lodestone sort [<dir>] --module <module.json> [--client <dir>] [--server <dir>]
+//   lodestone run <plan.toml>
+//   lodestone compare <dir a> <dir b>
+//   lodestone missing [<dir>] --module <module.json> [--json | --bom] [--output <file>]
+//   lodestone join <client dir> --module <module.json> [--server <dir>]
+//   lodestone stale <module.json> <dir> [--prune [--yes]]
+//   lodestone diff <old.json> <new.json> [--markdown]
+//   lodestone conflicts <module.json> <module.json>...
+//   lodestone history [<dir>]
+//   lodestone undo <dir> <run id>
+//   lodestone restore <backup.zip> <dir> [--force]
+//   lodestone restore-quarantine <dir> [<jar>...] [--force]
+//   lodestone empty-quarantine <dir> [--force]
+//   lodestone self-test
+//   lodestone checksum <module.json> [--key <file>] [--verify]
+//
+// Most take more options than shown here; `lodestone help` prints them all.
+// Commands that write a module accept `--compact` to skip pretty-printing;
+// commands that take a mods directory offer an interactive folder picker
+// when it is left out.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::path::{Path, PathBuf};
+
+use crate::pattern::IdPattern;
+use crate::report::{self, NdjsonWriter};
+use crate::stats;
+use crate::version::{Bump, Semver};
+use crate::{
+    add_bom, bytecode, check_output, checksum, compare, diff_modules, dominant_loader, expr,
+    history, incompat, instance_mods_dir, io_context, jar_files, lastscan, library, list_jars,
+    list_jars_with, mapping, modrinth, op_targets, pack_dirs, packformat, parse_jar, placement,
+    plan, protected_targets, quarantine, read_icon, remote, run_listed, run_recorded,
+    scan_directory, scan_directory_timed, scan_warnings, selftest, session, sort_by_side,
+    tag_conflicts, template, verify_zip, ConfirmPhrase, Impact, JsonStyle, ModLoader, Module,
+    ModuleEntry, ModuleVersion, OpFilter, OpOptions, Operation, ScanOptions, Side, Sides,
+    VersionSource, Warning, WarningKind, MODULE_FORMAT, TOOL_VERSION, UTF8_BOM,
+};
+
+const USAGE: &str = "\
+usage: lodestone                      open the GUI
+       lodestone scan [<dir>] --module <module.json> [--ndjson] [--strict-match] [--curseforge]
+                      [--version-tolerance exact|minor|major] [--list-all] [--summary-json]
+                      [--layout-hint] [--verbose | --compact] [--mapping] [--per-subdir | --instances]
+                      [--timing] [--explain <mod id>] [--emit-links] [--minecraft <version>] [--jobs <n>]
+                      [--changed-only]
+       lodestone stats [<dir>]
+       lodestone misplaced <instance>
+       lodestone incompatible [<dir>]
+       lodestone duplicates [<dir>] [--jobs <n>] [--delete [--module <module.json>] [--no-backup] [--yes] [--force]]
+       lodestone mapping [<dir>] [--hash] [--jobs <n>]
+       lodestone icons [<dir>] --output <folder>
+       lodestone graph [<dir>] [--module <module.json>] [--output <file.dot>]
+       lodestone inspect <jar> [--module <module.json> [--tag <side>]]
+       lodestone new-module <module.json> [--name <name>] [--author <author>] [--force]
+       lodestone shell <module.json>
+       lodestone get <module.json> <query>
+       lodestone module bump <module.json> <major|minor|patch>
+       lodestone edit <module.json> <mod id> [--tag <side>] [--note <text> | --clear-note]
+                      [--modrinth <slug>] [--curseforge <slug>]
+                      [--version-range <range> | --clear-version-range] [--protect | --unprotect]
+       lodestone op <zip|move|copy|delete|quarantine|export> <dir> --module <module.json>
+                    (--tag <side> | --only-matched | --invalid | --filter <expr>)
+                    [--loader <loader>] [--output <path>] [--verify-zip] [--bom] [--no-backup] [--yes] [--force]
+                    [--limit <n>] [--rename-on-move] [--changed-only] [--in-pack <pack> | --not-in-pack <pack>]
+       lodestone op <zip|move|copy|delete|quarantine|export|report> <dir> (--jars <a.jar,b.jar> | --jars-file <file>)
+                    [--module <module.json>]
+                    [--output <path>] [--verify-zip] [--bom] [--no-backup] [--yes] [--force] [--limit <n>]
+                    [--rename-on-move] [--format txt|markdown]
+       lodestone op report <dir> --module <module.json> --output <file.txt> [--loader <loader>] [--bom]
+                    [--format txt|markdown]
+       lodestone modrinth-index [<dir>] --module <module.json> [--name <pack>] [--version-id <v>]
+                    [--minecraft <v>] [--loader-version <v>] [--output <file>]
+       lodestone slugs <module.json> [--refresh] [--dry-run]
+       lodestone migrate <module.json>
+       lodestone pack-tag <module.json> <pack.mrpack | modrinth modpack> [--name <pack>] [--dry-run]
+       lodestone retag <module.json> (--loader <loader> | --match <pattern>) --tag <side>
+                       [--dry-run] [--yes]
+       lodestone retag <module.json> --libraries [--tag <side>] [--dry-run] [--yes]
+       lodestone retag <module.json> --tag-from-declaration <dir> [--overwrite] [--dry-run] [--yes]
+       lodestone dedupe <module.json> [--dry-run]
+       lodestone add-unknowns <module.json> <dir> [--default-tag <side>] [--dry-run] [--yes]
+       lodestone sort [<dir>] --module <module.json> [--client <dir>] [--server <dir>]
+                                                   [--both <dir>] [--unknown <dir>] [--yes]
+       lodestone run <plan.toml>
+       lodestone compare <dir a> <dir b>
+       lodestone missing [<dir>] --module <module.json> [--json | --bom] [--output <file>]
+       lodestone join <client dir> --module <module.json> [--server <dir>]
+       lodestone stale <module.json> <dir> [--prune [--yes]]
+       lodestone diff <old.json> <new.json> [--markdown]
+       lodestone conflicts <module.json> <module.json>...
+       lodestone history [<dir>]
+       lodestone undo <dir> <run id>
+       lodestone restore <backup.zip> <dir> [--force]
+       lodestone restore-quarantine <dir> [<jar>...] [--force]
+       lodestone empty-quarantine <dir> [--force]
+       lodestone self-test
+       lodestone checksum <module.json> [--key <file>] [--verify]
+
+Commands that save a module accept --compact to write it without indentation;
+scan --compact instead prints one short line per jar.
+Scan collects what was noteworthy but not fatal (side disagreements, broken
+signatures, unreadable jars, a stale mapping) and prints it at the end under
+a \"Warnings (N)\" heading, apart from errors; --summary-json lists them too.
+Resource pack jars (a pack.mcmeta, no mod metadata) are checked against the
+game's pack format when the Minecraft version is known: --minecraft, or the
+launcher's minecraftinstance.json or mmc-pack.json.
+Scan, op, sort and run accept --fail-fast to stop at the first unreadable jar or
+failed file; by default they carry on and report every failure at the end.
+Dot-prefixed jars and folders are skipped unless the command is given
+--include-hidden.  Commands that scan a folder open (and hash) up to --jobs
+jars at once, one per CPU by default.
+Each scan records the jars it read in <dir>/.lodestone/last-scan.json; scan or op
+--changed-only then takes just the jars new or modified since (all of them the
+first time).
+Mapping saves what each jar is to <dir>/.lodestone/mapping.json; scan --mapping
+reads jars from it instead of opening them, reopening any whose --hash changed.
+Scan --instances takes a folder of game instances, scans each one's mods (or
+.minecraft/mods) folder, then lists the mods every instance shares and those
+only one has.
+Move, delete and sort record a run under <dir>/.lodestone/history; delete keeps
+a backup there unless --no-backup is given.  Delete states the size of what it
+takes when asking, and how much it freed when done.
+Quarantine moves jars into <dir>/.lodestone_quarantine instead, listed in its
+manifest.json; restore-quarantine puts them back and empty-quarantine deletes them.
+Join lists what a client folder needs to join a server: the Client and Both
+mods of the server's module (or, with --server, of its mods folder) to install
+or update, and the Server-only jars to remove.
+Add-unknowns adds every mod in <dir> the module doesn't list, tagged with
+--default-tag (unknown unless given), so they can all go in at once and be
+sorted out later with retag or edit.
+Duplicates lists jars with byte-identical content under different names, and
+with --delete removes every copy but the shortest-named one, backed up and
+recorded like any delete.
+Misplaced checks a game instance for mods in resourcepacks/ or shaderpacks/
+and packs in mods/, and says where each belongs.
+Edit --protect marks a mod protected: move, delete and quarantine skip it even
+when its tag matches, and say so (\"protected, skipped\").  With --jars, or
+duplicates --delete, give --module so the listed jars are checked too.
+Move overwrites a jar of the same name at --output; with --rename-on-move it
+gives the incoming jar the first free name-N.jar instead and says so.
+Op report lists jars under a heading per side; --format markdown (the default
+for a .md output) writes a table of jar, mod id, loader, version, module tag
+and match instead.
+Pack-tag marks the module entries for a Modrinth modpack's mods (a .mrpack, or
+a modpack's slug or id, whose latest version is downloaded) with the pack's
+name; op --in-pack or --not-in-pack then takes only those jars, or all others.
+Op --output may use {tag}, {module}, {date} (UTC, YYYY-MM-DD) and {count}, e.g.
+--output \"{module}-{tag}-{date}.zip\"; plans and the GUI expand them too.
+Shell edits a module with typed commands (add, tag, note, remove; help lists
+them) and saves after each; u undoes the last change, up to 50 back.
+Op --filter selects by an expression of tags and loaders joined with &&, || and
+!, e.g. --filter \"client || (both && forge)\"; unknown there means the tag.
+Op --limit <n> acts on the first n selected jars only and says how many were
+left, so a new module can be tried on a big folder a few jars at a time.
+Zip, copy, export and report never prompt.  Move and sort ask y/N unless --yes;
+delete wants DELETE typed unless --force.  Set LODESTONE_CONFIRM to change the
+phrase: `tag` asks for the tag being deleted, `random` for a word shown at the
+prompt, anything else is used as is.
+A module whose header sets min_tool_version above this build loads with a
+warning; set LODESTONE_STRICT_VERSION to refuse it instead (exit code 2).
+Leaving out [<dir>] in a terminal opens an interactive folder picker.
+--bom starts a text file with a UTF-8 byte-order mark, for Windows tools that
+otherwise misread non-ASCII names.
+Op --tag takes several sides separated by commas (client,both); leaving it out
+in a terminal opens a checklist of sides instead.
+Checksum writes <module.json>.sha256 for publishing, signed with --key.  Any
+command given --verify-module checks each module it loads against that file and
+warns if it was changed; set LODESTONE_MODULE_KEY to a key file to check the
+signature too.
+A module path may be an https:// URL: it is downloaded, cached and only fetched
+again when the server says it changed; a download that isn't a module keeps the
+cached copy.  --verify-module fetches the <url>.sha256 published beside it.
+
+Exit codes: 0 success, 1 usage error, 2 module could not be loaded or saved,
+3 directory missing or empty, 4 operation finished with failures.";
+
+// ── Exit codes ────────────────────────────────────────────────────────────────
+
+/// Why a command failed.  Each kind has a fixed exit code so scripts can tell
+/// a typo from a broken module from a half-finished move.
+#[derive(Debug)]
+enum CliError {
+    /// Exit 1: bad arguments, unknown command, or any failure not listed below
+    Usage(String),
+    /// Exit 2: the module could not be read, parsed or written back
+    Module(String),
+    /// Exit 3: the mods directory does not exist or holds no jars
+    Directory(String),
+    /// Exit 4: the operation ran but some files were not processed
+    Partial(String),
+}
+
+impl CliError {
+    fn code(&self) -> i32 {
+        match self {
+            CliError::Usage(_)     => 1,
+            CliError::Module(_)    => 2,
+            CliError::Directory(_) => 3,
+            CliError::Partial(_)   => 4,
+        }
+    }
+}
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::Usage(m) | CliError::Module(m) | CliError::Directory(m) | CliError::Partial(m) => f.write_str(m),
+        }
+    }
+}
+
+impl From<String> for CliError {
+    fn from(m: String) -> Self { CliError::Usage(m) }
+}
+
+impl From<&str> for CliError {
+    fn from(m: &str) -> Self { CliError::Usage(m.to_string()) }
+}
+
+fn load_module(path: &str) -> Result<Module, CliError> {
+    let module = Module::load(path).map_err(|e| CliError::Module(format!("{path}: {e}")))?;
+    if let Some(why) = module.too_new() {
+        eprintln!("warning: {path}: {why}");
+    }
+    if VERIFY_MODULES.load(Ordering::Relaxed) {
+        // A downloaded module is checked against the checksum file published with it
+        if remote::is_url(path) {
+            if let Err(why) = remote::fetch_sidecar(path) {
+                eprintln!("warning: {path}: no checksum file fetched: {why}");
+            }
+        }
+        let key = std::env::var_os("LODESTONE_MODULE_KEY").and_then(|k| std::fs::read(k).ok());
+        match checksum::verify(&module.path, key.as_deref()) {
+            Ok(what) => eprintln!("{path}: {what}"),
+            Err(why) => eprintln!("warning: {path}: {why}"),
+        }
+    }
+    Ok(module)
+}
+
+fn save_module(module: &Module, style: JsonStyle) -> Result<(), CliError> {
+    module.save(style).map_err(|e| CliError::Module(format!("{}: {e}", module.path)))
+}
+
+fn check_dir(dir: &str) -> Result<(), CliError> {
+    if !Path::new(dir).is_dir() {
+        return Err(CliError::Directory(format!("{dir} is not a directory")));
+    }
+    // An unreadable folder would otherwise just look empty
+    std::fs::read_dir(dir).map(drop)
+        .map_err(|e| CliError::Directory(io_context(e, &format!("reading {dir}")).to_string()))
+}
+
+// ── Argument parsing ──────────────────────────────────────────────────────────
+
+/// Options that take a value whatever the command, since every command
+/// that scans accepts them.
+const SHARED_VALUED: &[&str] = &["jobs"];
+
+/// Positional arguments plus `--name [value]` options.
+struct Args {
+    positional: Vec<String>,
+    options:    BTreeMap<String, Option<String>>,
+}
+
+impl Args {
+    /// `valued` lists the option names that consume the following argument;
+    /// every other `--name` is a boolean switch.
+    fn parse(raw: &[String], valued: &[&str]) -> Result<Self, String> {
+        let mut positional = Vec::new();
+        let mut options = BTreeMap::new();
+        let mut it = raw.iter();
+        while let Some(a) = it.next() {
+            match a.strip_prefix("--") {
+                Some(name) if valued.contains(&name) || SHARED_VALUED.contains(&name) => {
+                    let v = it.next().ok_or_else(|| format!("--{name} needs a value"))?;
+                    options.insert(name.to_string(), Some(v.clone()));
+                }
+                Some(name) => { options.insert(name.to_string(), None); }
+                None => positional.push(a.clone()),
+            }
+        }
+        Ok(Self { positional, options })
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        self.options.get(name).and_then(|v| v.as_deref())
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.options.contains_key(name)
+    }
+
+    /// `--compact` writes modules without indentation; shared by every command that saves.
+    fn json_style(&self) -> JsonStyle {
+        if self.flag("compact") { JsonStyle::Compact } else { JsonStyle::Pretty }
+    }
+
+    /// `--fail-fast` stops at the first unreadable jar or failed file;
+    /// shared by every command that scans or changes files.
+    fn fail_fast(&self) -> bool {
+        self.flag("fail-fast")
+    }
+
+    /// `--include-hidden` also scans (and so acts on) dot-prefixed jars.
+    fn include_hidden(&self) -> bool {
+        self.flag("include-hidden")
+    }
+
+    /// `--jobs <n>`: how many jars to work on at once; 0 (the default) for
+    /// one per CPU.
+    fn jobs(&self) -> Result<usize, CliError> {
+        self.value("jobs").map_or(Ok(0), |n| n.parse().map_err(|_| format!("--jobs wants a number, not '{n}'").into()))
+    }
+
+    /// What every command that scans a folder takes from the command line:
+    /// `--include-hidden` and `--jobs`.
+    fn scan_options(&self) -> Result<ScanOptions, CliError> {
+        Ok(ScanOptions { include_hidden: self.include_hidden(), jobs: self.jobs()?, ..Default::default() })
+    }
+}
+
+/// Jars the scan couldn't read.  With `--fail-fast` the first one is an
+/// error (the scan stopped there); otherwise each is reported as a warning.
+fn scan_failures(results: &[crate::ScanResult], fail_fast: bool) -> Result<(), CliError> {
+    let mut failed = results.iter().filter_map(|r| Some((&r.jar_name, r.parse_error.as_ref()?)));
+    if fail_fast {
+        return match failed.next() {
+            Some((jar, e)) => Err(CliError::Partial(format!("stopped at {jar}: {e}"))),
+            None           => Ok(()),
+        };
+    }
+    for (jar, e) in failed {
+        eprintln!("warning: could not read {jar}: {e}");
+    }
+    Ok(())
+}
+
+// ── Dispatch ──────────────────────────────────────────────────────────────────
+
+/// Set by `--verify-module`, which any command accepts.
+static VERIFY_MODULES: AtomicBool = AtomicBool::new(false);
+
+/// Run a subcommand and return the process exit code (see [`CliError`]).
+pub fn run(raw: &[String]) -> i32 {
+    let verify = raw.iter().any(|a| a == "--verify-module");
+    VERIFY_MODULES.store(verify, Ordering::Relaxed);
+    let raw: Vec<String> = raw.iter().filter(|a| *a != "--verify-module").cloned().collect();
+    let Some((cmd, rest)) = raw.split_first() else {
+        eprintln!("error: --verify-module needs a command\n{USAGE}");
+        return 1;
+    };
+    let result = match cmd.as_str() {
+        "scan"                     => cmd_scan(rest),
+        "graph"                    => cmd_graph(rest),
+        "misplaced"                => cmd_misplaced(rest),
+        "incompatible"             => cmd_incompatible(rest),
+        "duplicates"               => cmd_duplicates(rest),
+        "stats"                    => cmd_stats(rest),
+        "mapping"                  => cmd_mapping(rest),
+        "icons"                    => cmd_icons(rest),
+        "inspect"                  => cmd_inspect(rest),
+        "edit"                     => cmd_edit(rest),
+        "new-module"               => cmd_new_module(rest),
+        "shell"                    => cmd_shell(rest),
+        "get"                      => cmd_get(rest),
+        "module"                   => cmd_module(rest),
+        "retag"                    => cmd_retag(rest),
+        "pack-tag"                 => cmd_pack_tag(rest),
+        "dedupe"                   => cmd_dedupe(rest),
+        "add-unknowns"             => cmd_add_unknowns(rest),
+        "migrate"                  => cmd_migrate(rest),
+        "modrinth-index"           => cmd_modrinth_index(rest),
+        "slugs"                    => cmd_slugs(rest),
+        "sort"                     => cmd_sort(rest),
+        "op"                       => cmd_op(rest),
+        "run"                      => cmd_run(rest),
+        "compare"                  => cmd_compare(rest),
+        "missing"                  => cmd_missing(rest),
+        "join"                     => cmd_join(rest),
+        "stale"                    => cmd_stale(rest),
+        "diff"                     => cmd_diff(rest),
+        "conflicts"                => cmd_conflicts(rest),
+        "history"                  => cmd_history(rest),
+        "undo"                     => cmd_undo(rest),
+        "restore"                  => cmd_restore(rest),
+        "restore-quarantine"       => cmd_restore_quarantine(rest),
+        "empty-quarantine"         => cmd_empty_quarantine(rest),
+        "checksum"                 => cmd_checksum(rest),
+        "self-test"                => cmd_self_test(rest),
+        "help" | "--help" | "-h"   => { println!("{USAGE}"); Ok(()) }
+        _ => Err(format!("unknown command '{cmd}'\n{USAGE}").into()),
+    };
+    match result {
+        Ok(())  => 0,
+        Err(e)  => { eprintln!("error: {e}"); e.code() }
+    }
+}
+
+/// Ask before running something of the given impact.  Read-only work never
+/// prompts; moving jars out asks y/N unless `--yes`; deleting wants the
+/// [`ConfirmPhrase`] for `target` (the word for what is deleted, which
+/// only deletes need) typed unless `--force`.  `--force` also implies `--yes`.
+fn confirm_impact(impact: Impact, args: &Args, what: &str, target: Option<&str>) -> Result<(), CliError> {
+    let force = args.flag("force");
+    let confirmed = match impact {
+        Impact::ReadOnly => true,
+        Impact::Alters   => force || args.flag("yes")
+            || matches!(prompt_line(&format!("{what}? [y/N]")).as_deref(), Some("y" | "Y" | "yes")),
+        Impact::Destroys => force || {
+            println!("{what}?");
+            confirm_phrase(&ConfirmPhrase::from_env().phrase_for(target.unwrap_or("DELETE")))
+        }
+    };
+    if confirmed { Ok(()) } else { Err("not confirmed".into()) }
+}
+
+/// Ask for `expected` on stdin and return whether the user typed exactly that.
+fn confirm_phrase(expected: &str) -> bool {
+    prompt_line(&format!("Type {expected} to confirm:")).as_deref() == Some(expected)
+}
+
+/// Read a line from stdin, trimmed.  `None` on EOF or a read error.
+fn prompt_line(prompt: &str) -> Option<String> {
+    use std::io::Write;
+    print!("{prompt} ");
+    let _ = std::io::stdout().flush();
+    let mut line = String::new();
+    match std::io::stdin().read_line(&mut line) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(line.trim().to_string()),
+    }
+}
+
+/// Checklist of sides for `op` when no `--tag` was given: numbers toggle a
+/// side, an empty line accepts.  Returns `None` if the user quits or accepts
+/// with nothing ticked.
+fn pick_sides() -> Option<Sides> {
+    let mut picked = Sides::default();
+    loop {
+        println!();
+        for (i, s) in Sides::ALL.iter().enumerate() {
+            println!("  [{}] {}) {s}", if picked.contains(*s) { "x" } else { " " }, i + 1);
+        }
+        let choice = prompt_line("Numbers to toggle (e.g. 1,3), Enter to run, q to quit:")?;
+        match choice.as_str() {
+            "" => return (!picked.is_empty()).then_some(picked),
+            "q" | "Q" => return None,
+            _ => for n in choice.split(|c: char| c == ',' || c.is_whitespace()).filter(|n| !n.is_empty()) {
+                match n.parse::<usize>() {
+                    Ok(n) if (1..=Sides::ALL.len()).contains(&n) => {
+                        let side = Sides::ALL[n - 1];
+                        picked.toggle(side, !picked.contains(side));
+                    }
+                    _ => println!("Not a choice: {n}"),
+                }
+            },
+        }
+    }
+}
+
+/// Walk the filesystem from `start`: numbered subfolders, `0` to go up, `s` to
+/// pick the current folder.  Returns `None` if the user quits.
+fn pick_directory(start: PathBuf) -> Option<PathBuf> {
+    let mut cur = start.canonicalize().unwrap_or(start);
+    loop {
+        let mut subdirs: Vec<PathBuf> = std::fs::read_dir(&cur)
+            .map(|rd| rd.filter_map(Result::ok).map(|e| e.path()).filter(|p| p.is_dir()).collect())
+            .unwrap_or_default();
+        subdirs.sort();
+
+        let jars = list_jars(&cur.display().to_string()).len();
+        println!("\n{}  ({jars} jar{})", cur.display(), if jars == 1 { "" } else { "s" });
+        println!("  0) ..");
+        for (i, d) in subdirs.iter().enumerate() {
+            println!("  {}) {}", i + 1, d.file_name().map(|n| n.to_string_lossy()).unwrap_or_default());
+        }
+
+        let choice = prompt_line("Number to open, s to select this folder, q to quit:")?;
+        match choice.as_str() {
+            "s" | "S" => return Some(cur),
+            "q" | "Q" => return None,
+            _ => match choice.parse::<usize>() {
+                Ok(0) => { if let Some(p) = cur.parent() { cur = p.to_path_buf(); } }
+                Ok(n) if n <= subdirs.len() => cur = subdirs[n - 1].clone(),
+                _ => println!("Not a choice: {choice}"),
+            },
+        }
+    }
+}
+
+/// The command's single directory argument.  When it is left out in an
+/// interactive terminal, the user picks one with [`pick_directory`].
+fn dir_arg(args: &Args, cmd: &str) -> Result<String, CliError> {
+    let dir = match args.positional.as_slice() {
+        [dir] => dir.clone(),
+        [] if std::io::stdin().is_terminal() => pick_directory(PathBuf::from("."))
+            .map(|p| p.display().to_string())
+            .ok_or("no directory selected")?,
+        _ => return Err(format!("{cmd} takes exactly one directory\n{USAGE}").into()),
+    };
+    check_dir(&dir)?;
+    Ok(dir)
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Above this many jars the text scan prints only its summary unless asked
+/// for the full listing.  NDJSON output is never cut short.
+const LIST_LIMIT: usize = 200;
+
+/// Scan a mods folder against a module.  `--ndjson` streams one JSON object
+/// per jar to stdout as it is processed, with the summary going to stderr.
+/// `--strict-match` reports matched mods still tagged Unknown as needing classification;
+/// `--curseforge` identifies jars from the instance's `minecraftinstance.json`;
+/// `--version-tolerance` relaxes version comparison to major.minor or major;
+/// `--list-all` prints every jar even past [`LIST_LIMIT`];
+/// `--summary-json` prints the summary, with its no-match diagnosis, as JSON;
+/// `--layout-hint` guesses sides from assets/ and data/ folders;
+/// `--verbose` says under each jar which files its identification came from;
+/// `--compact` prints one short line per jar instead;
+/// `--mapping` identifies jars from the folder's saved mapping;
+/// `--emit-links` lists each mod's homepage and issue tracker instead.
+fn cmd_scan(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &["module", "version-tolerance", "explain", "minecraft"])?;
+    let dir = &dir_arg(&args, "scan")?;
+    let path = args.value("module").ok_or("scan needs --module")?;
+    let module = load_module(path)?;
+    let opts = ScanOptions {
+        strict:     args.flag("strict-match"),
+        curseforge: args.flag("curseforge"),
+        version_tolerance: args.value("version-tolerance").map(str::parse).transpose()?.unwrap_or_default(),
+        fail_fast:  args.fail_fast(),
+        layout_hint: args.flag("layout-hint"),
+        include_hidden: args.include_hidden(),
+        mapping:    args.flag("mapping"),
+        jobs:       args.jobs()?,
+        changed_only: args.flag("changed-only"),
+    };
+    if args.flag("emit-links") {
+        let (results, _) = scan_directory(dir, &module, &opts);
+        for line in report::links(&results) {
+            println!("{line}");
+        }
+        return scan_failures(&results, opts.fail_fast);
+    }
+    if let Some(id) = args.value("explain") {
+        let (results, _) = scan_directory(dir, &module, &opts);
+        for line in report::explain(id, &results, &module) {
+            println!("{line}");
+        }
+        return scan_failures(&results, opts.fail_fast);
+    }
+    let (per_subdir, instances) = (args.flag("per-subdir"), args.flag("instances"));
+    if !per_subdir && !instances {
+        return scan_one(dir, &module, &opts, &args).map(drop);
+    }
+
+    // Each immediate subfolder is its own pack, scanned and reported apart;
+    // with --instances it is a game instance and its mods folder is scanned
+    if per_subdir && instances {
+        return Err("--per-subdir and --instances are mutually exclusive".into());
+    }
+    if args.flag("ndjson") {
+        return Err("--per-subdir and --instances print a section per pack and can't be combined with --ndjson".into());
+    }
+    let packs: Vec<(String, String)> = pack_dirs(dir, opts.include_hidden).into_iter()
+        .filter_map(|p| {
+            let name = Path::new(&p).file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            if instances { instance_mods_dir(&p).map(|m| (name, m)) } else { Some((name, p)) }
+        })
+        .collect();
+    if packs.is_empty() {
+        let what = if instances { "instance folders with a mods folder" } else { "subfolders" };
+        return Err(CliError::Directory(format!("no {what} in {dir}")));
+    }
+    let (mut scanned, mut failed) = (Vec::new(), Vec::new());
+    for (i, (name, pack)) in packs.iter().enumerate() {
+        if i > 0 { println!(); }
+        println!("== {name} ==");
+        match scan_one(pack, &module, &opts, &args) {
+            Ok(results) => scanned.push((name.clone(), mod_ids(&results))),
+            Err(CliError::Directory(e)) => println!("{e}"),
+            Err(CliError::Partial(e)) => failed.push(format!("{name}: {e}")),
+            Err(e) => return Err(e),
+        }
+    }
+    if scanned.is_empty() && failed.is_empty() {
+        return Err(CliError::Directory(format!("no jars in any subfolder of {dir}")));
+    }
+    if instances && scanned.len() > 1 {
+        let fleet = compare::fleet(&scanned);
+        println!("\n== across {} instances ==", scanned.len());
+        println!("in every instance ({}): {}", fleet.everywhere.len(),
+            if fleet.everywhere.is_empty() { "—".to_string() } else { fleet.everywhere.join(", ") });
+        println!("in only one instance ({}):", fleet.unique.len());
+        for (id, instance) in &fleet.unique {
+            println!("  {id:<32} {instance}");
+        }
+    }
+    if failed.is_empty() { Ok(()) } else { Err(CliError::Partial(failed.join("; "))) }
+}
+
+/// The mod ids a scan identified, for comparing instances.
+fn mod_ids(results: &[crate::ScanResult]) -> BTreeSet<String> {
+    results.iter().filter_map(|r| r.jar_info.as_ref().map(|i| i.mod_id.clone())).collect()
+}
+
+/// The scan of one folder: every jar (or a count), the summary line and
+/// warnings, as `scan` prints them.  Returns the results for callers that
+/// look across folders.
+fn scan_one(dir: &str, module: &Module, opts: &ScanOptions, args: &Args) -> Result<Vec<crate::ScanResult>, CliError> {
+    let mut warnings = Vec::new();
+    if opts.mapping {
+        match mapping::load(dir) {
+            None => warnings.push(Warning::new(WarningKind::Mapping,
+                format!("no saved mapping in {dir}; run `lodestone mapping {dir}` to write one"))),
+            Some(m) => if let Some(why) = m.staleness(&list_jars_with(dir, opts.include_hidden)) {
+                warnings.push(Warning::new(WarningKind::Mapping, format!("mapping is stale ({why}); unmapped jars are opened as usual")));
+            },
+        }
+    }
+
+    let last = lastscan::load(dir);
+    if opts.changed_only && last.is_none() {
+        eprintln!("note: no earlier scan of {dir} is recorded; scanning every jar");
+    }
+
+    let (results, summary, timing) = if args.flag("ndjson") {
+        let out = NdjsonWriter::new(std::io::stdout());
+        scan_directory_timed(dir, module, opts, &|r| {
+            if let Err(e) = out.emit(r) {
+                eprintln!("error: writing {}: {e}", r.jar_name);
+            }
+        })
+    } else {
+        let scanned = scan_directory_timed(dir, module, opts, &|_| {});
+        let limit = (!args.flag("list-all")).then_some(LIST_LIMIT);
+        for line in jar_listing(&scanned.0, limit, args.flag("compact"), args.flag("verbose")) {
+            println!("{line}");
+        }
+        scanned
+    };
+
+    let mut record = last.unwrap_or_default();
+    record.update(dir, &results);
+    if let Err(e) = record.save(dir) {
+        eprintln!("warning: could not save {}: {e}", lastscan::record_path(dir).display());
+    }
+    if results.is_empty() {
+        if opts.changed_only && !list_jars_with(dir, opts.include_hidden).is_empty() {
+            println!("No jars changed since the last scan of {dir}.");
+            return Ok(results);
+        }
+        return Err(CliError::Directory(format!("no jars found in {dir}")));
+    }
+    warnings.extend(scan_warnings(&results, module));
+    if let Some(mc) = args.value("minecraft").map(String::from).or_else(|| packformat::instance_minecraft(dir)) {
+        warnings.extend(packformat::mismatches(dir, &results, &mc));
+    }
+    if opts.fail_fast {
+        // The first unreadable jar is the error scan_failures reports
+        warnings.retain(|w| w.kind != WarningKind::Unreadable);
+    }
+    let line = if args.flag("summary-json") {
+        serde_json::to_string(&report::SummaryRecord::new(&summary, &warnings)).map_err(|e| e.to_string())?
+    } else {
+        summary.describe()
+    };
+    let mut lines = vec![line];
+    if args.flag("timing") {
+        lines.extend(timing.lines());
+    }
+    for line in lines {
+        if args.flag("ndjson") {
+            eprintln!("{line}");
+        } else {
+            println!("{line}");
+        }
+    }
+    if let (false, Some(d)) = (args.flag("summary-json"), summary.diagnosis()) {
+        eprintln!("note: no full matches: {d}");
+    }
+    print_warnings(&warnings);
+    if opts.fail_fast {
+        scan_failures(&results, true)?;
+    }
+    Ok(results)
+}
+
+/// The text scan's lines for `results`, one per jar (two with `verbose`), or
+/// a single line saying how many there are when that is more than `limit`.
+fn jar_listing(results: &[crate::ScanResult], limit: Option<usize>, compact: bool, verbose: bool) -> Vec<String> {
+    if limit.is_some_and(|l| results.len() > l) {
+        return vec![format!("{} jars — too many to list; use --list-all to show each one.", results.len())];
+    }
+    let line: fn(&_) -> String = if compact { report::compact_line } else { report::text_line };
+    results.iter()
+        .flat_map(|r| std::iter::once(line(r)).chain(verbose.then(|| report::source_line(r)).flatten()))
+        .collect()
+}
+
+/// The warnings of a run, grouped by kind under one heading on stderr so
+/// they read apart from errors.
+fn print_warnings(warnings: &[Warning]) {
+    if warnings.is_empty() { return; }
+    let mut sorted: Vec<&Warning> = warnings.iter().collect();
+    sorted.sort_by_key(|w| w.kind);
+    eprintln!("Warnings ({}):", sorted.len());
+    for w in sorted {
+        eprintln!("  {}: {w}", w.kind);
+    }
+}
+
+/// Summarise a folder with no module: jar count, loaders, how many have
+/// readable metadata, total size and the largest jars.
+fn cmd_stats(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &[])?;
+    let dir = &dir_arg(&args, "stats")?;
+    let (results, _) = scan_directory(dir, &Module::empty(), &args.scan_options()?);
+    if results.is_empty() {
+        return Err(CliError::Directory(format!("no jars found in {dir}")));
+    }
+
+    let st = stats::folder_stats(dir, &results);
+    println!("{} jars, {} in total", st.total, stats::human_size(st.total_bytes));
+    println!("  with readable metadata: {}", st.with_metadata);
+    if st.invalid > 0 {
+        println!("  not valid jars:         {}", st.invalid);
+    }
+    println!("By loader:");
+    for (loader, n) in &st.by_loader {
+        println!("  {:<10} {n}", loader.to_string());
+    }
+    println!("Largest:");
+    for (jar, size) in &st.largest {
+        println!("  {:<40} {}", jar, stats::human_size(*size));
+    }
+    Ok(())
+}
+
+/// Point out mods dropped into `resourcepacks/` or `shaderpacks/` and packs
+/// dropped into `mods/`, with where each should go.  Nothing is moved.
+fn cmd_misplaced(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &[])?;
+    let [instance] = args.positional.as_slice() else {
+        return Err(format!("misplaced takes an instance folder\n{USAGE}").into());
+    };
+    if !Path::new(instance).is_dir() {
+        return Err(CliError::Directory(format!("{instance} is not a directory")));
+    }
+    let found = placement::check(instance);
+    for m in &found {
+        let name = m.file.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+        println!("{} is {} — move it to {}", m.file.display(), m.content.describe(), m.move_to.join(&*name).display());
+    }
+    match found.len() {
+        0 => println!("Every mod and pack in {instance} is where it belongs."),
+        n => println!("{n} file(s) in the wrong folder."),
+    }
+    Ok(())
+}
+
+/// List pairs of installed mods where one declares it breaks or conflicts
+/// with the other.  No module is needed: it comes from the jars' metadata.
+fn cmd_incompatible(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &[])?;
+    let dir = &dir_arg(&args, "incompatible")?;
+    let (results, _) = scan_directory(dir, &Module::empty(), &args.scan_options()?);
+    if results.is_empty() {
+        return Err(CliError::Directory(format!("no jars found in {dir}")));
+    }
+    let found = incompat::clashes(&results);
+    let installed = |i: &incompat::Installed| format!("{} {} ({})", i.mod_id, i.version.as_deref().unwrap_or("—"), i.jar);
+    for c in &found {
+        let range = match (&c.range, c.checked) {
+            (Some(r), true)  => format!(", versions {r}"),
+            (Some(r), false) => format!(", versions {r} (not checked)"),
+            (None, _)        => String::new(),
+        };
+        println!("{} breaks {}{range}", installed(&c.by), installed(&c.with));
+    }
+    match found.len() {
+        0 => println!("No installed mod declares it breaks another in {dir}."),
+        n => println!("{n} incompatible pair(s)."),
+    }
+    Ok(())
+}
+
+/// List jars whose bytes are identical under different names; `--delete`
+/// deletes all but the shortest-named copy in each group, as `op delete
+/// --jars` would, skipping any that `--module` protects.
+fn cmd_duplicates(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &["module"])?;
+    let dir = &dir_arg(&args, "duplicates")?;
+    let groups = crate::hash::identical(dir, &list_jars_with(dir, args.include_hidden()), args.jobs()?);
+    for g in &groups {
+        println!("{}  (kept)", g[0]);
+        for copy in &g[1..] {
+            println!("  {copy}");
+        }
+    }
+    let extra: Vec<String> = groups.iter().flat_map(|g| g[1..].iter().cloned()).collect();
+    if extra.is_empty() {
+        println!("No identical jars in {dir}.");
+        return Ok(());
+    }
+    println!("{} identical cop{} of {} jar(s).", extra.len(), if extra.len() == 1 { "y" } else { "ies" }, groups.len());
+    if !args.flag("delete") {
+        println!("Run again with --delete to delete the copies, keeping the shortest-named copy of each.");
+        return Ok(());
+    }
+    op_on_jars(Operation::Delete, dir, &extra, None, None, &args)
+}
+
+/// Save the folder's jar → mod id mapping for `scan --mapping`, with a hash
+/// per jar under `--hash`.  No module is needed.
+fn cmd_mapping(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &[])?;
+    let dir = &dir_arg(&args, "mapping")?;
+    let opts = args.scan_options()?;
+    let (results, _) = scan_directory(dir, &Module::empty(), &opts);
+    if results.is_empty() {
+        return Err(CliError::Directory(format!("no jars found in {dir}")));
+    }
+    let map = mapping::build(dir, &results, args.flag("hash"), opts.jobs);
+    let path = map.save(dir).map_err(|e| CliError::Partial(io_context(e, &format!("writing {dir}/.lodestone")).to_string()))?;
+    println!("Mapped {} of {} jar(s) to {}.", map.jars.len(), map.jar_count, path.display());
+    Ok(())
+}
+
+/// Whether `name` can be used as a file name as it is: one plain path
+/// component, so a mod id like `../../x` can't write outside the folder.
+fn is_plain_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".."
+        && !name.contains(['/', '\\', ':', '\0'])
+}
+
+/// Copy every mod's declared icon out of its jar into `--output`, named by
+/// mod id — e.g. for a pack's web page.  No module is needed.  Ids that
+/// aren't plain file names are skipped; a second mod with the same id gets
+/// the next free `<id>-N` name, with a warning.
+fn cmd_icons(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &["output"])?;
+    let dir = &dir_arg(&args, "icons")?;
+    let out = args.value("output").ok_or("icons needs --output")?;
+    std::fs::create_dir_all(out).map_err(|e| format!("{out}: {e}"))?;
+
+    let (mut written, mut warnings) = (0, Vec::new());
+    let mut taken = BTreeSet::new();
+    for file_name in jar_files(dir, args.include_hidden()) {
+        let (path, jar) = (Path::new(dir).join(&file_name), file_name.to_string_lossy());
+        let Ok(Some(info)) = parse_jar(&path) else { continue };
+        let Some(icon) = &info.icon else { continue };
+        let Some(bytes) = read_icon(&path, icon) else {
+            warnings.push(format!("{jar}: declares {icon} but doesn't contain it"));
+            continue;
+        };
+        if !is_plain_name(&info.mod_id) {
+            warnings.push(format!("{jar}: mod id '{}' is not a plain file name; icon skipped", info.mod_id));
+            continue;
+        }
+        let ext = Path::new(icon).extension().and_then(|e| e.to_str())
+            .filter(|e| e.chars().all(|c| c.is_ascii_alphanumeric()))
+            .unwrap_or("png");
+        let mut dest = Path::new(out).join(format!("{}.{ext}", info.mod_id));
+        if !taken.insert(dest.clone()) {
+            dest = crate::free_name(Path::new(out), dest.file_name().unwrap_or_default());
+            warnings.push(format!("{jar}: another jar's mod is also '{}'; icon written as {}", info.mod_id, dest.display()));
+            taken.insert(dest.clone());
+        }
+        std::fs::write(&dest, bytes).map_err(|e| CliError::Partial(format!("{}: {e}", dest.display())))?;
+        written += 1;
+    }
+    for w in &warnings {
+        eprintln!("warning: {w}");
+    }
+    println!("Wrote {written} icon(s) to {out}.");
+    Ok(())
+}
+
+/// Emit the folder's dependency graph as Graphviz DOT, to stdout or `--output`.
+/// The module is optional; without one, sides come from manifests and bytecode.
+fn cmd_graph(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &["module", "output"])?;
+    let dir = &dir_arg(&args, "graph")?;
+    let module = match args.value("module") {
+        Some(path) => load_module(path)?,
+        None       => Module::empty(),
+    };
+    let (results, _) = scan_directory(dir, &module, &args.scan_options()?);
+    if results.is_empty() {
+        return Err(CliError::Directory(format!("no jars found in {dir}")));
+    }
+
+    let dot = report::dependency_dot(&results);
+    match args.value("output") {
+        Some(out) => {
+            std::fs::write(out, dot).map_err(|e| format!("{out}: {e}"))?;
+            println!("Wrote dependency graph of {} jars to {out}.", results.len());
+        }
+        None => print!("{dot}"),
+    }
+    Ok(())
+}
+
+fn print_entry(mod_id: &str, e: &ModuleEntry) {
+    println!("{mod_id}: {} · {} · v{}", e.mod_tag, e.mod_type, e.mod_version);
+    if let Some(r) = &e.version_range {
+        println!("  accepts: {r}");
+    }
+    if e.protected {
+        println!("  protected: never moved, deleted or quarantined");
+    }
+    if let Some(n) = &e.note {
+        println!("  note: {n}");
+    }
+    for (site, slug) in [("modrinth", &e.modrinth), ("curseforge", &e.curseforge)] {
+        if let Some(slug) = slug {
+            println!("  {site}: {slug}");
+        }
+    }
+}
+
+/// Print what Lodestone detects for a single jar.  With `--module` the stored
+/// entry is shown too, and `--tag` adds or updates it.
+fn cmd_inspect(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &["tag", "module"])?;
+    let [jar] = args.positional.as_slice() else {
+        return Err(format!("inspect takes exactly one jar path\n{USAGE}").into());
+    };
+    let tag = args.value("tag").map(str::parse::<Side>).transpose()?;
+    let module_path = args.value("module");
+    if tag.is_some() && module_path.is_none() {
+        return Err("--tag needs --module".into());
+    }
+
+    let info = parse_jar(jar)
+        .map_err(|e| format!("{jar}: {e}"))?
+        .ok_or_else(|| format!("{jar}: no mod metadata found"))?;
+    let bc = bytecode::analyse_jar(jar, true).unwrap_or_else(bytecode::BytecodeEvidence::unknown);
+
+    println!("mod id:   {}", info.mod_id);
+    println!("loader:   {}", info.loader);
+    let source = match info.version_source {
+        VersionSource::Metadata         => "",
+        VersionSource::Manifest         => " (from MANIFEST.MF)",
+        VersionSource::GradleProperties => " (from gradle.properties — development build)",
+    };
+    println!("version:  {}{source}", info.version.as_deref().unwrap_or("—"));
+    println!("metadata: {}", info.metadata_file);
+    println!("declared: {}", info.declared_side.map(|s| s.to_string()).unwrap_or_else(|| "—".into()));
+    println!("bytecode: {:?} ({:?})", bc.side, bc.confidence);
+    if let Some(why) = library::reason(&info) {
+        println!("suggest:  Both ({why})");
+    }
+    if !info.bundled.is_empty() {
+        println!("bundles:  {} mod(s)", info.bundled.len());
+        for b in &info.bundled {
+            println!("          {} {}  ({})", b.mod_id, b.version.as_deref().unwrap_or("—"), b.path);
+        }
+    }
+
+    let Some(path) = module_path else { return Ok(()) };
+    let mut module = load_module(path)?;
+    let Some(tag) = tag else {
+        match module.mods.get(&info.mod_id) {
+            Some(e) => print_entry(&info.mod_id, e),
+            None    => println!("'{}' is not in '{}'.", info.mod_id, module.name),
+        }
+        return Ok(());
+    };
+
+    // Keep any note, project slugs, range and packs the author already wrote for this mod
+    let old = module.mods.get(&info.mod_id);
+    let replaced = module.mods.insert(info.mod_id.clone(), ModuleEntry {
+        mod_version: info.version.clone().unwrap_or_else(|| "*".into()),
+        mod_tag:     tag,
+        mod_type:    info.loader,
+        note:        old.and_then(|e| e.note.clone()),
+        modrinth:    old.and_then(|e| e.modrinth.clone()),
+        curseforge:  old.and_then(|e| e.curseforge.clone()),
+        version_range: old.and_then(|e| e.version_range.clone()),
+        protected:   old.is_some_and(|e| e.protected),
+        packs:       old.map(|e| e.packs.clone()).unwrap_or_default(),
+    });
+    save_module(&module, args.json_style())?;
+    let verb = if replaced.is_some() { "Updated" } else { "Added" };
+    println!("{verb} '{}' as {tag} in '{}'.", info.mod_id, module.name);
+    Ok(())
+}
+
+/// Change the tag, note, project slugs or accepted version range of an
+/// existing module entry.
+fn cmd_edit(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &["tag", "note", "modrinth", "curseforge", "version-range"])?;
+    let [path, mod_id] = args.positional.as_slice() else {
+        return Err(format!("edit takes a module path and a mod id\n{USAGE}").into());
+    };
+    if args.value("note").is_some() && args.flag("clear-note") {
+        return Err("--note and --clear-note are mutually exclusive".into());
+    }
+    if args.value("version-range").is_some() && args.flag("clear-version-range") {
+        return Err("--version-range and --clear-version-range are mutually exclusive".into());
+    }
+    if args.flag("protect") && args.flag("unprotect") {
+        return Err("--protect and --unprotect are mutually exclusive".into());
+    }
+    if let Some(r) = args.value("version-range") {
+        crate::version::check_range(r)?;
+    }
+    let tag = args.value("tag").map(str::parse::<Side>).transpose()?;
+
+    let mut module = load_module(path)?;
+    let entry = module.mods.get_mut(mod_id)
+        .ok_or_else(|| format!("'{mod_id}' is not in '{}'", module.name))?;
+    if let Some(t) = tag { entry.mod_tag = t; }
+    if let Some(n) = args.value("note") { entry.note = Some(n.to_string()); }
+    if args.flag("clear-note") { entry.note = None; }
+    if let Some(slug) = args.value("modrinth") { entry.modrinth = Some(slug.to_string()); }
+    if let Some(slug) = args.value("curseforge") { entry.curseforge = Some(slug.to_string()); }
+    if let Some(r) = args.value("version-range") { entry.version_range = Some(r.to_string()); }
+    if args.flag("clear-version-range") { entry.version_range = None; }
+    if args.flag("protect") { entry.protected = true; }
+    if args.flag("unprotect") { entry.protected = false; }
+    let entry = entry.clone();
+
+    save_module(&module, args.json_style())?;
+    print_entry(mod_id, &entry);
+    Ok(())
+}
+
+/// Start an empty module at a path.  An existing file there is kept unless
+/// `--force`, and the refusal says how many entries replacing it would lose.
+fn cmd_new_module(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &["name", "author"])?;
+    let [path] = args.positional.as_slice() else {
+        return Err(format!("new-module takes the path to create\n{USAGE}").into());
+    };
+    if remote::is_url(path) {
+        return Err(format!("{path}: a module can only be created on disk").into());
+    }
+    if Path::new(path).exists() && !args.flag("force") {
+        let what = match Module::load(path) {
+            Ok(m) => format!("holds '{}' with {} entr{}", m.name, m.mods.len(), if m.mods.len() == 1 { "y" } else { "ies" }),
+            Err(_) => "exists (not a readable module)".into(),
+        };
+        return Err(CliError::Module(format!("{path} already {what}; pass --force to replace it")));
+    }
+    let stem = Path::new(path).file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let name = args.value("name").map(String::from)
+        .unwrap_or_else(|| stem.split('.').next().unwrap_or_default().to_string());
+    let module = Module::new(path, &name, args.value("author").unwrap_or_default());
+    save_module(&module, args.json_style())?;
+    println!("Created '{name}' at {path}.");
+    Ok(())
+}
+
+/// Edit a module with typed commands (see `session.rs`), saving after each
+/// change or undo.  Ends on `q` or end of input.
+fn cmd_shell(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &[])?;
+    let [path] = args.positional.as_slice() else {
+        return Err(format!("shell takes a module path\n{USAGE}").into());
+    };
+    let mut session = session::Session::new(load_module(path)?, session::MAX_UNDO);
+    let n = session.module.mods.len();
+    println!("Editing '{}' ({n} entr{}); type help for commands.", session.module.name, if n == 1 { "y" } else { "ies" });
+    while let Some(line) = prompt_line(">") {
+        match session.apply(&line) {
+            Ok(session::Step::Save(what)) => {
+                save_module(&session.module, args.json_style())?;
+                println!("{what}");
+            }
+            Ok(session::Step::Show(text)) => if !text.is_empty() { println!("{text}") },
+            Ok(session::Step::Quit) => break,
+            Err(e) => println!("{e}"),
+        }
+    }
+    Ok(())
+}
+
+/// Print one value from a module: `header.module_version`, `mods.create.mod_tag`,
+/// or a JSON pointer such as `/mods/create`.  Strings print bare, anything
+/// else as JSON.  The module is read in the current format, whatever its file's.
+fn cmd_get(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &[])?;
+    let [path, query] = args.positional.as_slice() else {
+        return Err(format!("get takes a module path and a query\n{USAGE}").into());
+    };
+    let module = load_module(path)?;
+    let doc = serde_json::to_value(module.document()).map_err(|e| CliError::Module(e.to_string()))?;
+    let value = lookup(&doc, query).ok_or_else(|| format!("'{query}' is not in {path}"))?;
+    match value {
+        serde_json::Value::String(s) => println!("{s}"),
+        other => println!("{}", serde_json::to_string_pretty(other).map_err(|e| e.to_string())?),
+    }
+    Ok(())
+}
+
+/// `module bump <module.json> <part>`: raise the header's semver version.
+fn cmd_module(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &[])?;
+    let [sub, path, part] = args.positional.as_slice() else {
+        return Err(format!("module takes bump, a module path and major, minor or patch\n{USAGE}").into());
+    };
+    if sub != "bump" {
+        return Err(format!("unknown module command '{sub}'\n{USAGE}").into());
+    }
+    let part: Bump = part.parse()?;
+    let mut module = load_module(path)?;
+    // Older modules hold a bare number such as 1.0, which has no patch part
+    let current: Semver = match &module.version {
+        ModuleVersion::Text(v) => v.parse().map_err(|e| CliError::Module(format!("{path}: module version {e}")))?,
+        ModuleVersion::Number(n) => return Err(CliError::Module(format!(
+            "{path}: module version {n:?} is a number, not semver; set it to a string such as \"{n:?}.0\" first"
+        ))),
+    };
+    let next = current.bump(part);
+    module.version = ModuleVersion::Text(next.to_string());
+    save_module(&module, args.json_style())?;
+    println!("{}: {current} -> {next}", module.name);
+    Ok(())
+}
+
+/// A JSON pointer when the query starts with `/`, else dot-separated keys
+/// where a number indexes an array.
+fn lookup<'a>(doc: &'a serde_json::Value, query: &str) -> Option<&'a serde_json::Value> {
+    if query.starts_with('/') {
+        return doc.pointer(query);
+    }
+    query.split('.').filter(|k| !k.is_empty()).try_fold(doc, |v, key| match v {
+        serde_json::Value::Array(a) => a.get(key.parse::<usize>().ok()?),
+        _ => v.get(key),
+    })
+}
+
+/// Mark the module entries for every mod in a Modrinth modpack — a local
+/// `.mrpack` or a modpack project's latest version — with the pack's name
+/// (or `--name`), so `op --in-pack` / `--not-in-pack` can select by it.
+fn cmd_pack_tag(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &["name"])?;
+    let [path, source] = args.positional.as_slice() else {
+        return Err(format!("pack-tag takes a module path and a .mrpack file or Modrinth modpack\n{USAGE}").into());
+    };
+    let mut module = load_module(path)?;
+    let (title, projects) = if Path::new(source).is_file() {
+        modrinth::read_pack(Path::new(source))?
+    } else {
+        modrinth::fetch_pack(source)?
+    };
+    let name = args.value("name").map(String::from).unwrap_or(title);
+    if name.is_empty() {
+        return Err("the pack has no name; give one with --name".into());
+    }
+    let slugs = modrinth::project_slugs(&projects)?;
+    let (marked, unmatched) = module.mark_pack(&name, &slugs);
+    for id in &marked {
+        println!("{id}: in {name}");
+    }
+    if !unmatched.is_empty() {
+        eprintln!("{} of the pack's {} mods have no entry in the module: {}", unmatched.len(), slugs.len(), unmatched.join(", "));
+    }
+    if args.flag("dry-run") {
+        println!("Would mark {} entr{} as in '{name}' (dry run).", marked.len(), if marked.len() == 1 { "y" } else { "ies" });
+        return Ok(());
+    }
+    if !marked.is_empty() {
+        save_module(&module, args.json_style())?;
+    }
+    println!("Marked {} entr{} as in '{name}'.", marked.len(), if marked.len() == 1 { "y" } else { "ies" });
+    Ok(())
+}
+
+/// Merge module entries whose ids differ only in case into one lowercase
+/// entry, printing each merge and any tag or loader conflict it had to settle.
+fn cmd_dedupe(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &[])?;
+    let [path] = args.positional.as_slice() else {
+        return Err(format!("dedupe takes exactly one module path\n{USAGE}").into());
+    };
+    let mut module = load_module(path)?;
+    let merges = module.dedupe_case();
+    if merges.is_empty() {
+        println!("No ids in '{}' differ only in case.", module.name);
+        return Ok(());
+    }
+    for m in &merges {
+        println!("{} ← {}", m.key, m.merged.join(", "));
+        for c in &m.conflicts {
+            println!("  warning: {c}");
+        }
+    }
+    if args.flag("dry-run") {
+        println!("Dry run: {} merge(s), nothing written.", merges.len());
+        return Ok(());
+    }
+    save_module(&module, args.json_style())?;
+    println!("Merged {} set(s) of duplicate ids in {path}.", merges.len());
+    Ok(())
+}
+
+/// Apply one tag to every module entry of a loader family, to every entry
+/// whose id matches `--match <pattern>`, or to every known library
+/// (`--libraries`, `Both` unless `--tag` says otherwise).  The last two list
+/// the ids they would touch and ask first (`--yes` skips, `--dry-run` only lists).
+/// `--tag-from-declaration <dir>` is [`retag_from_declarations`].
+fn cmd_retag(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &["loader", "tag", "match", "tag-from-declaration"])?;
+    let [path] = args.positional.as_slice() else {
+        return Err(format!("retag takes exactly one module path\n{USAGE}").into());
+    };
+    if let Some(dir) = args.value("tag-from-declaration") {
+        if args.value("tag").is_some() || args.value("loader").is_some() || args.value("match").is_some() || args.flag("libraries") {
+            return Err("--tag-from-declaration takes the tags from the jars; drop --tag, --loader, --match and --libraries".into());
+        }
+        return retag_from_declarations(&args, path, dir);
+    }
+    let libraries = args.flag("libraries");
+    // Libraries are needed on both sides, so --libraries can leave out --tag
+    let tag: Side = match args.value("tag") {
+        Some(t) => t.parse()?,
+        None if libraries => Side::Both,
+        None => return Err("retag needs --tag".into()),
+    };
+    let mut module = load_module(path)?;
+
+    let (ids, what) = match (args.value("loader"), args.value("match"), libraries) {
+        (Some(l), None, false) => {
+            let loader: ModLoader = l.parse()?;
+            let n = module.retag_by_loader(loader, tag);
+            if n > 0 {
+                save_module(&module, args.json_style())?;
+            }
+            println!("Retagged {n} {loader} entr{} as {tag}.", if n == 1 { "y" } else { "ies" });
+            return Ok(());
+        }
+        (None, Some(p), false) => {
+            let pattern: IdPattern = p.parse()?;
+            (module.ids_matching(&pattern), format!("match {pattern}"))
+        }
+        (None, None, true) => (module.library_ids(), "are known libraries".to_string()),
+        _ => return Err("retag needs exactly one of --loader, --match or --libraries".into()),
+    };
+    if ids.is_empty() {
+        println!("No entries in '{}' {what}.", module.name);
+        return Ok(());
+    }
+    println!("{} entr{} {what}:", ids.len(), if ids.len() == 1 { "y" } else { "ies" });
+    for id in &ids {
+        println!("  {id:<32} {}", module.mods[id].mod_tag);
+    }
+    if args.flag("dry-run") {
+        return Ok(());
+    }
+    confirm_impact(Impact::Alters, &args, &format!("Tag them {tag}"), None)?;
+    let n = module.retag_ids(&ids, tag);
+    if n > 0 {
+        save_module(&module, args.json_style())?;
+    }
+    println!("Retagged {n} entr{} as {tag}.", if n == 1 { "y" } else { "ies" });
+    Ok(())
+}
+
+/// Add every mod in a folder the module doesn't list yet, all with one tag:
+/// `--default-tag`, or `Unknown` so scans keep flagging them for review.
+fn cmd_add_unknowns(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &["default-tag"])?;
+    let [path, dir] = args.positional.as_slice() else {
+        return Err(format!("add-unknowns takes a module path and a directory\n{USAGE}").into());
+    };
+    let tag: Side = args.value("default-tag").map(str::parse).transpose()?.unwrap_or(Side::Unknown);
+    check_dir(dir)?;
+    let mut module = load_module(path)?;
+    let fail_fast = args.fail_fast();
+    let (results, _) = scan_directory(dir, &module, &ScanOptions { fail_fast, ..args.scan_options()? });
+    scan_failures(&results, fail_fast)?;
+
+    let added = module.add_unknowns(&results, tag);
+    if added.is_empty() {
+        println!("Every identified mod in {dir} is already in '{}'.", module.name);
+        return Ok(());
+    }
+    for id in &added {
+        println!("  add     {id:<32} {tag}");
+    }
+    let counts = format!("{} mod(s) to add to '{}' as {tag}", added.len(), module.name);
+    if args.flag("dry-run") {
+        println!("{counts}.");
+        return Ok(());
+    }
+    confirm_impact(Impact::Alters, &args, &format!("Add {} mod(s)", added.len()), None)?;
+    save_module(&module, args.json_style())?;
+    println!("Added {} mod(s) to '{}' as {tag}.", added.len(), module.name);
+    Ok(())
+}
+
+/// Bootstrap a module from the mods themselves: every jar in `dir` whose
+/// metadata declares a side gets that tag, as a new entry or over an
+/// `Unknown` one.  Tags already set are kept unless `--overwrite`.
+fn retag_from_declarations(args: &Args, path: &str, dir: &str) -> Result<(), CliError> {
+    check_dir(dir)?;
+    let mut module = load_module(path)?;
+    let fail_fast = args.fail_fast();
+    let (results, _) = scan_directory(dir, &module, &ScanOptions { fail_fast, ..args.scan_options()? });
+    scan_failures(&results, fail_fast)?;
+
+    let tagged = module.tag_from_declarations(&results, args.flag("overwrite"));
+    for (id, side) in &tagged.added {
+        println!("  add     {id:<32} {side}");
+    }
+    for (id, from, to) in &tagged.updated {
+        println!("  retag   {id:<32} {from} → {to}");
+    }
+    for id in &tagged.review {
+        println!("  review  {id}");
+    }
+    let n = tagged.added.len() + tagged.updated.len();
+    let counts = format!(
+        "{n} mod(s) tagged from their declarations ({} added, {} updated), {} left for review",
+        tagged.added.len(), tagged.updated.len(), tagged.review.len(),
+    );
+    if n == 0 || args.flag("dry-run") {
+        println!("{counts}.");
+        return Ok(());
+    }
+    confirm_impact(Impact::Alters, args, &format!("Save {n} change(s) to '{}'", module.name), None)?;
+    save_module(&module, args.json_style())?;
+    println!("{counts}.");
+    Ok(())
+}
+
+/// Move every jar into the folder given for its side, in one pass.
+fn cmd_sort(raw: &[String]) -> Result<(), CliError> {
+    let sides = [Side::Client, Side::Server, Side::Both, Side::Unknown];
+    let names: Vec<String> = sides.iter().map(|s| s.to_string().to_lowercase()).collect();
+    let valued: Vec<&str> = names.iter().map(String::as_str).chain(["module"]).collect();
+    let args = Args::parse(raw, &valued)?;
+    let dir = &dir_arg(&args, "sort")?;
+    let path = args.value("module").ok_or("sort needs --module")?;
+    let dests: BTreeMap<Side, String> = sides.iter().zip(&names)
+        .filter_map(|(side, name)| args.value(name).map(|d| (*side, d.to_string())))
+        .collect();
+    if dests.is_empty() {
+        return Err("give at least one destination, e.g. --client ./client".into());
+    }
+    for d in dests.values() {
+        check_output(Operation::Move, dir, d)?;
+    }
+
+    let module = load_module(path)?;
+    let fail_fast = args.fail_fast();
+    let (results, _) = scan_directory(dir, &module, &ScanOptions { fail_fast, ..args.scan_options()? });
+    scan_failures(&results, fail_fast)?;
+    let names: Vec<&std::ffi::OsStr> = results.iter()
+        .filter(|r| dests.contains_key(&r.effective_side()))
+        .filter(|r| Path::new(dir).join(&r.file_name).is_file())
+        .map(|r| r.file_name.as_os_str())
+        .collect();
+    confirm_impact(Impact::Alters, &args, &format!("Sort {} jar(s) into their side folders", names.len()), None)?;
+    let mut run = history::Run::begin("Sort", Some(path), None, dir, &names);
+    let sorted = sort_by_side(dir, &results, &dests, fail_fast);
+    let side_of: BTreeMap<&str, Side> = results.iter().map(|r| (r.jar_name.as_str(), r.effective_side())).collect();
+    run.settle(dir, |name, file| side_of.get(name).and_then(|s| dests.get(s)).map(|d| Path::new(d).join(file)));
+    if !run.jars.is_empty() {
+        run.save(dir).map_err(|e| CliError::Partial(format!("could not record the run: {e}")))?;
+    }
+    let counts = sorted.map_err(|e| CliError::Partial(e.to_string()))?;
+    for (side, dest) in &dests {
+        println!("{side:<8} {:>4} → {dest}", counts.get(side).copied().unwrap_or(0));
+    }
+    Ok(())
+}
+
+/// Run one of the GUI's operations on every jar of a side.  Delete asks for
+/// the same typed confirmation as the GUI.
+fn cmd_op(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &["module", "tag", "loader", "output", "jars", "jars-file", "limit", "format", "in-pack", "not-in-pack", "filter"])?;
+    let [op, dir] = args.positional.as_slice() else {
+        return Err(format!("op takes an operation and a directory\n{USAGE}").into());
+    };
+    let op: Operation = op.parse()?;
+    check_dir(dir)?;
+    if args.flag("bom") && !matches!(op, Operation::Export | Operation::Report) {
+        return Err("--bom only applies to the text files export and report write".into());
+    }
+    let format: Option<report::ReportFormat> = args.value("format").map(str::parse).transpose()?;
+    if format.is_some() && op != Operation::Report {
+        return Err("--format only applies to op report".into());
+    }
+    let limit = args.value("limit")
+        .map(|n| n.parse::<usize>().ok().filter(|n| *n > 0).ok_or(format!("--limit takes a positive number, not '{n}'")))
+        .transpose()?;
+    if let Some(jars) = jar_list(&args)? {
+        return op_on_jars(op, dir, &jars, limit, format, &args);
+    }
+    let path = args.value("module").ok_or("op needs --module")?;
+    // A report covers every side, and --only-matched, --invalid and --filter
+    // pick jars by other means, so none of them needs --tag
+    let matched_only = args.flag("only-matched");
+    let invalid = args.flag("invalid");
+    let filter_expr = args.value("filter").map(str::parse::<expr::Expr>).transpose()?;
+    let selectors = [args.value("tag").is_some(), matched_only, invalid, filter_expr.is_some()].iter().filter(|b| **b).count();
+    if selectors > 1 {
+        return Err("--tag, --only-matched, --invalid and --filter are mutually exclusive".into());
+    }
+    let sides: Sides = match args.value("tag") {
+        Some(t) => t.parse()?,
+        None if op == Operation::Report || matched_only || invalid || filter_expr.is_some() => Sides::default(),
+        None if std::io::stdin().is_terminal() => pick_sides().ok_or("no tag selected")?,
+        None => return Err("op needs --tag, --only-matched, --invalid or --filter".into()),
+    };
+    let filter = OpFilter {
+        sides, loader: args.value("loader").map(str::parse).transpose()?, matched_only, invalid, limit,
+    };
+    let output = args.value("output").unwrap_or_default();
+    if op.needs_output() && output.is_empty() {
+        return Err(format!("{op} needs --output").into());
+    }
+    // A templated name is only known once the jars are, so it is checked then
+    let templated = template::is_template(output);
+    if !templated {
+        check_output(op, dir, output)?;
+    }
+
+    let module = load_module(path)?;
+    let fail_fast = args.fail_fast();
+    let changed_only = args.flag("changed-only");
+    let (mut results, _) = scan_directory(dir, &module, &ScanOptions { fail_fast, changed_only, ..args.scan_options()? });
+    scan_failures(&results, fail_fast)?;
+    // Narrow to the jars whose entry is (or isn't) marked with a pack-tag pack
+    let pack = match (args.value("in-pack"), args.value("not-in-pack")) {
+        (Some(_), Some(_)) => return Err("--in-pack and --not-in-pack are mutually exclusive".into()),
+        (Some(p), None) => Some((p, true)),
+        (None, Some(p)) => Some((p, false)),
+        (None, None) => None,
+    };
+    if let Some((pack, inside)) = pack {
+        results.retain(|r| r.module_entry.as_ref().is_some_and(|e| e.packs.iter().any(|p| p == pack)) == inside);
+    }
+    // An expression picks the jars itself; the sides they turn out to have
+    // then name the selection, as --tag's would
+    let filter = match &filter_expr {
+        Some(e) => {
+            results.retain(|r| expr::matches_expr(r, e));
+            OpFilter { sides: Sides::of(&results.iter().map(|r| r.effective_side()).collect::<Vec<_>>()), ..filter }
+        }
+        None => filter,
+    };
+    let present = |filter| op_targets(op, &results, filter).iter()
+        .filter(|r| std::path::Path::new(dir).join(&r.file_name).is_file())
+        .map(|r| r.jar_name.clone())
+        .collect::<Vec<String>>();
+    let expected = present(filter);
+    for r in protected_targets(op, &results, filter) {
+        println!("{}: protected, skipped", r.jar_name);
+    }
+    if expected.is_empty() {
+        println!("No matching jars in {dir}.");
+        return Ok(());
+    }
+    let left = present(OpFilter { limit: None, ..filter }).len() - expected.len();
+    let output = &template::expand(output, &template::Fields::today(&filter.target_word(), &module.name, expected.len()))?;
+    if templated {
+        check_output(op, dir, output)?;
+    }
+    if let Some(format) = format {
+        // Read-only, so there is no run to record: write it in the asked-for format
+        let body = report::render(&op_targets(op, &results, filter), format);
+        crate::write_atomic(Path::new(output), body.as_bytes()).map_err(|e| CliError::Partial(format!("{output}: {e}")))?;
+        op_done(op, &args, output, &expected, (expected.len(), None))?;
+        limit_note(limit, left);
+        return Ok(());
+    }
+    let backup = !args.flag("no-backup");
+    let how = if backup { "" } else { " permanently" };
+    let loader = filter.loader.map(|l| format!(" {l}")).unwrap_or_default();
+    let which = if invalid {
+        "invalid".to_string()
+    } else if matched_only {
+        "module-matched".to_string()
+    } else if let Some(f) = args.value("filter") {
+        format!("\"{f}\"")
+    } else {
+        sides.to_string()
+    };
+    let footprint = stats::Footprint::of(dir, &op_targets(op, &results, filter));
+    let space = space_clause(op, &footprint, backup);
+    confirm_impact(op.impact(), &args, &format!("{op} {} {which}{loader} jar(s){how}{space}", expected.len()), Some(&filter.target_word()))?;
+
+    let done = run_recorded(op, dir, &results, filter, path, OpOptions { output, backup, rename: args.flag("rename-on-move"), fail_fast })
+        .map_err(|e| CliError::Partial(e.to_string()))?;
+    rename_notes(dir, done.1.as_deref());
+    op_done(op, &args, output, &expected, done)?;
+    space_note(op, &footprint, backup);
+    limit_note(limit, left);
+    Ok(())
+}
+
+/// Every jar the recorded run gave a new name at its destination, which
+/// only `--rename-on-move` does.
+fn rename_notes(dir: &str, run_id: Option<&str>) {
+    let Some(run) = run_id.and_then(|id| history::list(dir).into_iter().find(|r| r.id == id)) else { return };
+    for (name, now) in run.renamed() {
+        println!("renamed {name} → {now} (the name was taken)");
+    }
+}
+
+/// How much a delete frees, for its prompt.  Backed-up jars only move
+/// within the folder's `.lodestone`, so nothing is freed until `--no-backup`.
+fn space_clause(op: Operation, footprint: &stats::Footprint, backup: bool) -> String {
+    let size = stats::human_size(footprint.total());
+    match (op, backup) {
+        (Operation::Delete, false) => format!(", freeing {size}"),
+        (Operation::Delete, true)  => format!(" ({size}, kept as a backup)"),
+        _ => String::new(),
+    }
+}
+
+/// After a delete, how much space it actually freed.
+fn space_note(op: Operation, footprint: &stats::Footprint, backup: bool) {
+    if op != Operation::Delete { return; }
+    let size = stats::human_size(footprint.gone());
+    if backup {
+        println!("{size} moved to the backup; nothing freed (--no-backup deletes for good).");
+    } else {
+        println!("Freed {size}.");
+    }
+}
+
+/// After an operation cut short by `--limit`, say how many jars it left.
+fn limit_note(limit: Option<usize>, left: usize) {
+    if let (Some(n), 1..) = (limit, left) {
+        println!("Stopped at the limit of {n}; {left} more matching jar(s) left untouched.");
+    }
+}
+
+/// Print how an operation went, first checking the archive if `--verify-zip`
+/// asked for it, or marking the text file UTF-8 if `--bom` did.
+fn op_done(op: Operation, args: &Args, output: &str, jars: &[String], (n, run_id): (usize, Option<String>)) -> Result<(), CliError> {
+    if args.flag("bom") {
+        add_bom(output).map_err(|e| CliError::Partial(format!("{output}: {e}")))?;
+    }
+    if op == Operation::Zip && args.flag("verify-zip") {
+        let names: Vec<&str> = jars.iter().map(String::as_str).collect();
+        verify_zip(output, &names).map_err(|e| CliError::Partial(format!("verification failed: {e}")))?;
+        println!("{n} file(s) zipped and verified.");
+    } else {
+        println!("{n} file(s) affected.");
+    }
+    if let Some(id) = run_id {
+        println!("Recorded as run {id}.");
+    }
+    Ok(())
+}
+
+/// `--jars a.jar,b.jar` or `--jars-file <file>` (one name per line, `#`
+/// comments allowed), when either is given.
+fn jar_list(args: &Args) -> Result<Option<Vec<String>>, CliError> {
+    let raw = match (args.value("jars"), args.value("jars-file")) {
+        (None, None)       => return Ok(None),
+        (Some(_), Some(_)) => return Err("--jars and --jars-file are mutually exclusive".into()),
+        (Some(list), None) => list.replace(',', "\n"),
+        (None, Some(file)) => std::fs::read_to_string(file).map_err(|e| format!("{file}: {e}"))?,
+    };
+    let jars: Vec<String> = raw.lines()
+        .map(|l| l.split('#').next().unwrap_or("").trim())
+        .filter(|l| !l.is_empty())
+        .map(String::from)
+        .collect();
+    if jars.is_empty() {
+        return Err("the jar list is empty".into());
+    }
+    Ok(Some(jars))
+}
+
+/// `op` on exactly the jars listed, with no module: the selection flags
+/// don't apply, and every name has to be in the folder.
+fn op_on_jars(
+    op: Operation, dir: &str, jars: &[String], limit: Option<usize>, format: Option<report::ReportFormat>, args: &Args,
+) -> Result<(), CliError> {
+    if ["tag", "loader", "filter"].iter().any(|o| args.value(o).is_some()) || args.flag("only-matched") || args.flag("invalid") {
+        return Err("--jars and --jars-file replace --tag, --loader, --filter, --only-matched and --invalid".into());
+    }
+    let output = args.value("output").unwrap_or_default();
+    if op.needs_output() && output.is_empty() {
+        return Err(format!("{op} needs --output").into());
+    }
+    check_output(op, dir, output)?;
+    let fail_fast = args.fail_fast();
+    // A module only says which of the named jars are protected
+    let module = args.value("module").map(load_module).transpose()?.unwrap_or_else(Module::empty);
+    // Named jars are acted on even when hidden
+    let (results, _) = scan_directory(dir, &module, &ScanOptions { fail_fast, include_hidden: true, ..args.scan_options()? });
+    let targets = crate::named_targets(op, &results, jars).map_err(|e| format!("{dir}: {e}"))?;
+    for name in jars.iter().filter(|j| !targets.iter().any(|t| t.jar_name == **j)) {
+        println!("{name}: protected, skipped");
+    }
+    let jars: Vec<String> = targets.iter().map(|t| t.jar_name.clone()).collect();
+    if jars.is_empty() {
+        println!("No listed jars left to act on in {dir}.");
+        return Ok(());
+    }
+    let (jars, left) = match limit {
+        Some(n) if n < jars.len() => (&jars[..n], jars.len() - n),
+        _ => (&jars[..], 0),
+    };
+    let backup = !args.flag("no-backup");
+    let how = if backup { "" } else { " permanently" };
+    let footprint = stats::Footprint::of(dir, &crate::named_targets(op, &results, jars).unwrap_or_default());
+    let space = space_clause(op, &footprint, backup);
+    confirm_impact(op.impact(), args, &format!("{op} {} listed jar(s){how}{space}", jars.len()), Some("listed"))?;
+
+    if let Some(format) = format {
+        // As for a filtered report: written in the asked-for format, no run recorded
+        let body = report::render(&crate::named_targets(op, &results, jars).unwrap_or_default(), format);
+        crate::write_atomic(Path::new(output), body.as_bytes()).map_err(|e| CliError::Partial(format!("{output}: {e}")))?;
+        op_done(op, args, output, jars, (jars.len(), None))?;
+        limit_note(limit, left);
+        return Ok(());
+    }
+    let done = run_listed(op, dir, &results, jars, OpOptions { output, backup, rename: args.flag("rename-on-move"), fail_fast })
+        .map_err(|e| CliError::Partial(e.to_string()))?;
+    rename_notes(dir, done.1.as_deref());
+    op_done(op, args, output, jars, done)?;
+    space_note(op, &footprint, backup);
+    limit_note(limit, left);
+    Ok(())
+}
+
+/// Execute a batch plan step by step (see `plan.rs` for the format).
+fn cmd_run(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &[])?;
+    let [path] = args.positional.as_slice() else {
+        return Err(format!("run takes exactly one plan file\n{USAGE}").into());
+    };
+    let plan = plan::load(path)?;
+    let base = Path::new(path).parent().map(Path::to_path_buf).unwrap_or_default();
+    let failures = plan::execute(&plan, &base, args.fail_fast(), &mut |line| println!("{line}"));
+    if failures.is_empty() {
+        println!("All {} step(s) completed.", plan.steps.len());
+        Ok(())
+    } else {
+        Err(CliError::Partial(format!("{} of {} step(s) failed", failures.len(), plan.steps.len())))
+    }
+}
+
+/// Diff two folders by mod id — e.g. a client pack against its server pack.
+/// No module is involved; ids and versions come from the jars themselves.
+fn cmd_compare(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &[])?;
+    let [a, b] = args.positional.as_slice() else {
+        return Err(format!("compare takes two directories\n{USAGE}").into());
+    };
+    check_dir(a)?;
+    check_dir(b)?;
+    let diff = compare::compare(&compare::mod_versions(a), &compare::mod_versions(b));
+    let v = |v: &Option<String>| v.clone().unwrap_or_else(|| "—".into());
+
+    for (dir, ids) in [(a, &diff.only_left), (b, &diff.only_right)] {
+        if ids.is_empty() { continue; }
+        println!("Only in {dir} ({}):", ids.len());
+        for id in ids {
+            println!("  {id}");
+        }
+    }
+    if !diff.versions.is_empty() {
+        println!("Version differences ({}):", diff.versions.len());
+        for (id, left, right) in &diff.versions {
+            println!("  {id:<24} {} → {}", v(left), v(right));
+        }
+    }
+    if diff.is_empty() {
+        println!("Both folders have the same {} mod(s).", diff.same);
+    } else {
+        println!("{} shared mod(s) match.", diff.same);
+    }
+    Ok(())
+}
+
+/// List the module's mods that have no jar in the folder, as text to paste
+/// to someone or (`--json`) for a downloader.  `--output` writes a file.
+fn cmd_missing(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &["module", "output"])?;
+    let dir = &dir_arg(&args, "missing")?;
+    let module = load_module(args.value("module").ok_or("missing needs --module")?)?;
+    let (results, _) = scan_directory(dir, &module, &args.scan_options()?);
+    let missing = module.missing_from(&results);
+
+    if args.flag("bom") && args.flag("json") {
+        return Err("--bom only applies to the text manifest, not --json".into());
+    }
+    let mut manifest = if args.flag("json") {
+        let records: Vec<_> = missing.iter().map(|(id, e)| report::MissingRecord::new(id, e)).collect();
+        let json = serde_json::json!({ "module": module.name, "missing": records });
+        serde_json::to_string_pretty(&json).map_err(|e| e.to_string())? + "\n"
+    } else {
+        report::missing_manifest(&module.name, &missing)
+    };
+    if args.flag("bom") {
+        manifest.insert_str(0, UTF8_BOM);
+    }
+    match args.value("output") {
+        Some(out) => {
+            std::fs::write(out, manifest).map_err(|e| format!("{out}: {e}"))?;
+            println!("Wrote {} missing mod(s) to {out}.", missing.len());
+        }
+        None => print!("{manifest}"),
+    }
+    Ok(())
+}
+
+/// What a client folder needs to join a server: the server's Client and
+/// Both mods it lacks or has at another version, and the Server-only jars it
+/// should drop.  The server side comes from the module, or from scanning
+/// `--server` with it; tags always come from the module.
+fn cmd_join(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &["module", "server"])?;
+    let [client] = args.positional.as_slice() else {
+        return Err(format!("join takes the client's mods directory\n{USAGE}").into());
+    };
+    check_dir(client)?;
+    let module = load_module(args.value("module").ok_or("join needs --module")?)?;
+    let wanted = match args.value("server") {
+        Some(server) => {
+            check_dir(server)?;
+            compare::wanted_from_folder(&scan_directory(server, &module, &args.scan_options()?).0)
+        }
+        None => compare::wanted_from_module(&module),
+    };
+    let (results, _) = scan_directory(client, &module, &args.scan_options()?);
+    let plan = compare::join_plan(&results, &wanted);
+    let v = |v: &Option<String>| v.clone().unwrap_or_else(|| "—".into());
+
+    if !plan.install.is_empty() {
+        println!("Install ({}):", plan.install.len());
+        for w in &plan.install {
+            println!("  {:<28} {:<14} {}", w.id, v(&w.version), w.side);
+        }
+    }
+    if !plan.update.is_empty() {
+        println!("Update ({}):", plan.update.len());
+        for (w, have) in &plan.update {
+            println!("  {:<28} {} → {}", w.id, v(have), v(&w.version));
+        }
+    }
+    if !plan.remove.is_empty() {
+        println!("Remove, server only ({}):", plan.remove.len());
+        for (jar, id) in &plan.remove {
+            println!("  {jar:<28} {id}");
+        }
+    }
+    if plan.install.is_empty() && plan.update.is_empty() && plan.remove.is_empty() {
+        println!("{client} is ready to join: all {} client mod(s) are in place.", plan.ok);
+    } else {
+        println!("{} client mod(s) already in place.", plan.ok);
+    }
+    Ok(())
+}
+
+/// For module authors: check every entry off against a reference folder.
+/// Entries with no jar there are stale or simply not installed; `--prune`
+/// removes them from the module after asking (`--yes` skips).
+fn cmd_stale(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &[])?;
+    let [path, dir] = args.positional.as_slice() else {
+        return Err(format!("stale takes a module path and a directory\n{USAGE}").into());
+    };
+    check_dir(dir)?;
+    let mut module = load_module(path)?;
+    let (results, _) = scan_directory(dir, &module, &args.scan_options()?);
+    let stale: Vec<String> = module.missing_from(&results).into_iter().map(|(id, _)| id.to_string()).collect();
+
+    for (id, e) in &module.mods {
+        let mark = if stale.contains(id) { ' ' } else { 'x' };
+        println!("[{mark}] {id:<32} {:<14} {}", e.mod_version, e.mod_type);
+    }
+    println!("{} of {} entr{} in '{}' have no jar in {dir}.",
+        stale.len(), module.mods.len(), if module.mods.len() == 1 { "y" } else { "ies" }, module.name);
+    if stale.is_empty() || !args.flag("prune") {
+        return Ok(());
+    }
+
+    confirm_impact(Impact::Alters, &args, &format!("Remove {} entr{} from '{}'",
+        stale.len(), if stale.len() == 1 { "y" } else { "ies" }, module.name), None)?;
+    let n = module.remove_entries(&stale);
+    save_module(&module, args.json_style())?;
+    println!("Pruned {n} entr{} from {path}.", if n == 1 { "y" } else { "ies" });
+    Ok(())
+}
+
+/// What changed from one release of a module to the next, as a changelog.
+fn cmd_diff(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &[])?;
+    let [old, new] = args.positional.as_slice() else {
+        return Err(format!("diff takes the old and the new module\n{USAGE}").into());
+    };
+    let (old, new) = (load_module(old)?, load_module(new)?);
+    let markdown = args.flag("markdown");
+    let title = format!("{} {} → {}", new.name, old.version, new.version);
+    println!("{}", if markdown { format!("## {title}\n") } else { title });
+    let diff = diff_modules(&old, &new);
+    if diff.is_empty() {
+        println!("No mods added, removed or changed.");
+    }
+    for line in diff.changelog(markdown) {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// Compare the tags several modules give the same mods and list every
+/// disagreement with the module each tag came from.
+fn cmd_conflicts(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &[])?;
+    if args.positional.len() < 2 {
+        return Err(format!("conflicts takes two or more module paths\n{USAGE}").into());
+    }
+    let modules = args.positional.iter().map(|p| load_module(p)).collect::<Result<Vec<_>, _>>()?;
+    let conflicts = tag_conflicts(&modules);
+    if conflicts.is_empty() {
+        println!("No tag conflicts across {} modules.", modules.len());
+        return Ok(());
+    }
+    println!("{:<32} {:<8} module", "id", "tag");
+    for c in &conflicts {
+        for (i, (path, tag)) in c.tags.iter().enumerate() {
+            let id = if i == 0 { c.id.as_str() } else { "" };
+            println!("{id:<32} {:<8} {path}", tag.to_string());
+        }
+    }
+    println!("{} mod(s) tagged differently across {} modules.", conflicts.len(), modules.len());
+    Ok(())
+}
+
+/// Run detection on the built-in fixture jars, one line per fixture, for
+/// bug reports and CI.  Any failure makes the exit code 4.
+fn cmd_self_test(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &[])?;
+    if !args.positional.is_empty() {
+        return Err(format!("self-test takes no arguments\n{USAGE}").into());
+    }
+    println!("Lodestone {TOOL_VERSION} on {}, {} user metadata pattern(s)", std::env::consts::OS, selftest::user_patterns());
+    let outcomes = selftest::run();
+    for o in &outcomes {
+        let mark = if o.passed() { "pass" } else { "FAIL" };
+        println!("{mark}  {:<11} {}", o.loader.to_string(), o.name);
+        for p in &o.problems {
+            println!("        {p}");
+        }
+    }
+    let failed = outcomes.iter().filter(|o| !o.passed()).count();
+    println!("{} of {} fixtures passed.", outcomes.len() - failed, outcomes.len());
+    if failed > 0 {
+        return Err(CliError::Partial(format!("{failed} detection fixture(s) failed")));
+    }
+    Ok(())
+}
+
+/// Write a module's checksum sidecar, or with `--verify` check it.
+fn cmd_checksum(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &["key"])?;
+    let [path] = &args.positional[..] else {
+        return Err(format!("checksum takes one module path\n{USAGE}").into());
+    };
+    load_module(path)?;
+    let key = args.value("key")
+        .map(|k| std::fs::read(k).map_err(|e| CliError::Usage(format!("{k}: {e}"))))
+        .transpose()?;
+    if args.flag("verify") {
+        let what = checksum::verify(path, key.as_deref()).map_err(|e| CliError::Module(format!("{path}: {e}")))?;
+        println!("{path}: {what}");
+        return Ok(());
+    }
+    let (out, sidecar) = checksum::write(path, key.as_deref()).map_err(CliError::Module)?;
+    println!("sha256 {}", sidecar.sha256);
+    if sidecar.signature.is_some() {
+        println!("signed with {}", args.value("key").unwrap_or_default());
+    }
+    println!("wrote {}", out.display());
+    Ok(())
+}
+
+/// List the recorded runs for a folder, oldest first.
+fn cmd_history(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &[])?;
+    let dir = &dir_arg(&args, "history")?;
+    let runs = history::list(dir);
+    if runs.is_empty() {
+        println!("No recorded runs in {dir}.");
+        return Ok(());
+    }
+    for r in &runs {
+        let state = if r.undone { "undone" } else if r.reversible() { "undoable" } else { "permanent" };
+        println!(
+            "{:<18} {:<7} {:<8} {:>4} jar(s)  {:<9} {}",
+            r.id, r.operation,
+            match (r.tag, r.tags.is_empty()) {
+                (Some(t), _)  => t.to_string(),
+                (None, false) => Sides::of(&r.tags).to_string(),
+                (None, true)  => "—".into(),
+            },
+            r.jars.len(), state,
+            r.module.as_deref().unwrap_or("—"),
+        );
+    }
+    Ok(())
+}
+
+/// Restore the jars a recorded move, sort or backed-up delete took away.
+fn cmd_undo(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &[])?;
+    let [dir, id] = args.positional.as_slice() else {
+        return Err(format!("undo takes a directory and a run id\n{USAGE}").into());
+    };
+    check_dir(dir)?;
+    let (restored, skipped) = history::undo(dir, id)?;
+    for s in &skipped {
+        eprintln!("skipped {s}");
+    }
+    println!("{restored} file(s) restored.");
+    if skipped.is_empty() {
+        Ok(())
+    } else {
+        Err(CliError::Partial(format!("{} file(s) could not be restored", skipped.len())))
+    }
+}
+
+/// Put the jars from a zip made by `op zip` back into a mods folder.
+/// Existing jars are kept unless `--force`.
+fn cmd_restore(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &[])?;
+    let [zip, dir] = args.positional.as_slice() else {
+        return Err(format!("restore takes a backup zip and a directory\n{USAGE}").into());
+    };
+    check_dir(dir)?;
+    let (restored, skipped) = history::restore_from_backup(zip, dir, args.flag("force"))?;
+    for s in &skipped {
+        eprintln!("skipped {s}: already in {dir} (--force overwrites)");
+    }
+    println!("{restored} jar(s) restored from {zip}.");
+    Ok(())
+}
+
+/// Move quarantined jars back into the folder: the ones named, or all.
+/// Jars whose name is taken again are kept in quarantine unless `--force`.
+fn cmd_restore_quarantine(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &[])?;
+    let Some((dir, jars)) = args.positional.split_first() else {
+        return Err(format!("restore-quarantine takes a directory and optionally jar names\n{USAGE}").into());
+    };
+    check_dir(dir)?;
+    if quarantine::load(dir).jars.is_empty() {
+        println!("Nothing is quarantined in {dir}.");
+        return Ok(());
+    }
+    let (restored, skipped) = quarantine::restore(dir, jars, args.flag("force"))?;
+    for s in &skipped {
+        eprintln!("kept {s} in quarantine: {dir} has a jar of that name (--force overwrites)");
+    }
+    println!("{} jar(s) restored from quarantine.", restored.len());
+    Ok(())
+}
+
+/// Delete everything in the folder's quarantine, after the usual delete
+/// confirmation.
+fn cmd_empty_quarantine(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &[])?;
+    let dir = &dir_arg(&args, "empty-quarantine")?;
+    let held = quarantine::load(dir).jars;
+    if held.is_empty() {
+        println!("Nothing is quarantined in {dir}.");
+        return Ok(());
+    }
+    for e in &held {
+        println!("  {}", e.name);
+    }
+    confirm_impact(Impact::Destroys, &args, &format!("Permanently delete {} quarantined jar(s)", held.len()), Some("quarantine"))?;
+    let n = quarantine::empty(dir)?;
+    println!("{n} quarantined jar(s) deleted.");
+    Ok(())
+}
+
+/// Rewrite a module that was stored in an older format.
+fn cmd_migrate(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &[])?;
+    let [path] = args.positional.as_slice() else {
+        return Err(format!("migrate takes exactly one module path\n{USAGE}").into());
+    };
+    let module = load_module(path)?;
+    match module.migrated_from {
+        Some(from) => {
+            save_module(&module, args.json_style())?;
+            println!("Upgraded '{}' from format {from} to {MODULE_FORMAT}.", module.name);
+        }
+        None => println!("'{}' is already format {MODULE_FORMAT}.", module.name),
+    }
+    Ok(())
+}
+
+/// Fill in each entry's Modrinth slug from the Modrinth API.  Entries that
+/// already have one are left alone; answers are cached next to the module,
+/// and `--refresh` asks again.  `--dry-run` prints what would be stored.
+fn cmd_slugs(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &[])?;
+    let [path] = args.positional.as_slice() else {
+        return Err(format!("slugs takes exactly one module path\n{USAGE}").into());
+    };
+    let mut module = load_module(path)?;
+    let mut cache = if args.flag("refresh") { modrinth::SlugCache::new() } else { modrinth::load_cache(path) };
+
+    let todo: Vec<String> = module.mods.iter().filter(|(_, e)| e.modrinth.is_none()).map(|(id, _)| id.clone()).collect();
+    let (mut found, mut fetched, mut failure) = (0, false, None);
+    for id in &todo {
+        let slug = match cache.get(id) {
+            Some(cached) => cached.clone(),
+            None => {
+                if fetched { std::thread::sleep(modrinth::REQUEST_GAP); }
+                fetched = true;
+                match modrinth::lookup_slug(id) {
+                    Ok(slug) => { cache.insert(id.clone(), slug.clone()); slug }
+                    // Keep what was found so far; a rerun picks up from the cache
+                    Err(e) => { failure = Some(format!("{id}: {e}")); break; }
+                }
+            }
+        };
+        match slug {
+            Some(slug) => {
+                println!("  {id:<32} {slug}");
+                module.mods.get_mut(id).expect("ids come from the module").modrinth = Some(slug);
+                found += 1;
+            }
+            None => println!("  {id:<32} —"),
+        }
+    }
+    if let Err(e) = modrinth::save_cache(path, &cache) {
+        eprintln!("warning: could not save {}: {e}", modrinth::cache_path(path).display());
+    }
+    if found > 0 && !args.flag("dry-run") {
+        save_module(&module, args.json_style())?;
+    }
+    println!("Found {found} of {} missing slug(s).", todo.len());
+    failure.map_or(Ok(()), |e| Err(CliError::Partial(e)))
+}
+
+/// Write a `modrinth.index.json` skeleton for the folder: hashes, sizes and
+/// client/server `env` from each jar's side.  Download URLs are left empty.
+fn cmd_modrinth_index(raw: &[String]) -> Result<(), CliError> {
+    let args = Args::parse(raw, &["module", "name", "version-id", "minecraft", "loader-version", "output"])?;
+    let dir = &dir_arg(&args, "modrinth-index")?;
+    let path = args.value("module").ok_or("modrinth-index needs --module")?;
+    let module = load_module(path)?;
+    let (results, _) = scan_directory(dir, &module, &args.scan_options()?);
+    if results.is_empty() {
+        return Err(CliError::Directory(format!("no jars found in {dir}")));
+    }
+
+    let mut deps = BTreeMap::new();
+    if let Some(mc) = args.value("minecraft") {
+        deps.insert("minecraft".to_string(), mc.to_string());
+    }
+    if let Some(v) = args.value("loader-version") {
+        let loader = dominant_loader(results.iter().filter_map(|r| r.jar_info.as_ref()).map(|i| i.loader))
+            .and_then(modrinth::loader_key)
+            .ok_or("--loader-version given but the folder's loader could not be detected")?;
+        deps.insert(loader.to_string(), v.to_string());
+    }
+
+    let name = args.value("name").map(String::from).unwrap_or_else(|| module.name.clone());
+    let version_id = args.value("version-id").unwrap_or("1.0.0").to_string();
+    let index = modrinth::build_index(dir, &results, name, version_id, deps).map_err(|e| e.to_string())?;
+    let out = args.value("output").unwrap_or("modrinth.index.json");
+    let json = serde_json::to_string_pretty(&index).map_err(|e| e.to_string())?;
+    std::fs::write(out, json).map_err(|e| format!("{out}: {e}"))?;
+    println!("Wrote {} file(s) to {out} — add download URLs before publishing.", index.files.len());
+    Ok(())
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Unit tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{tagged, temp_dir, write_jar};
+
+    fn run_args(args: &[&str]) -> i32 {
+        run(&args.iter().map(|a| a.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn exit_codes_follow_the_contract() {
+        let d = temp_dir("cli-exit");
+        let dir = d.to_str().unwrap();
+        let module = d.join("module.json");
+        std::fs::write(&module, r#"{"header": {"module_name": "t", "module_version": 1.0, "module_author": "t"}, "mods": {}}"#).unwrap();
+        let module = module.to_str().unwrap();
+
+        assert_eq!(run_args(&["help"]), 0);
+        assert_eq!(run_args(&["frobnicate"]), 1);
+        assert_eq!(run_args(&["scan", dir]), 1, "missing --module");
+        assert_eq!(run_args(&["scan", dir, "--module", "does-not-exist.json"]), 2);
+        assert_eq!(run_args(&["scan", "does-not-exist", "--module", module]), 3);
+        assert_eq!(run_args(&["scan", dir, "--module", module]), 3, "no jars");
+
+        // A recorded move whose jar has since vanished from its destination
+        std::fs::write(d.join("a.jar"), b"jar").unwrap();
+        let mut run = history::Run::begin("Move", Some(module), Some(Side::Client), dir, &["a.jar".as_ref()]);
+        let dest = d.join("moved.jar");
+        std::fs::rename(d.join("a.jar"), &dest).unwrap();
+        run.settle(dir, |_, _| Some(dest.clone()));
+        run.save(dir).unwrap();
+        std::fs::remove_file(&dest).unwrap();
+        assert_eq!(run_args(&["undo", dir, &run.id]), 4);
+    }
+
+    #[test]
+    fn queries_walk_keys_indices_and_pointers() {
+        let doc = serde_json::json!({"header": {"module_version": 1.5}, "mods": {"create": {"mod_tag": "Both"}}, "list": [1, 2]});
+        assert_eq!(lookup(&doc, "header.module_version"), Some(&serde_json::json!(1.5)));
+        assert_eq!(lookup(&doc, "mods.create.mod_tag"), Some(&serde_json::json!("Both")));
+        assert_eq!(lookup(&doc, "/mods/create/mod_tag"), Some(&serde_json::json!("Both")));
+        assert_eq!(lookup(&doc, "list.1"), Some(&serde_json::json!(2)));
+        assert_eq!(lookup(&doc, "mods.jei"), None);
+    }
+
+    #[test]
+    fn bump_rewrites_a_semver_module_version() {
+        let d = temp_dir("cli-bump");
+        let module = d.join("module.json");
+        std::fs::write(&module, r#"{"header": {"module_name": "t", "module_version": 1.0, "module_author": "t"}, "mods": {}}"#).unwrap();
+        let path = module.to_str().unwrap();
+        assert_eq!(run_args(&["module", "bump", path, "patch"]), 2, "a bare number isn't semver");
+
+        std::fs::write(&module, r#"{"header": {"module_name": "t", "module_version": "1.4.2", "module_author": "t"}, "mods": {}}"#).unwrap();
+        assert_eq!(run_args(&["module", "bump", path, "sideways"]), 1);
+        assert_eq!(run_args(&["module", "bump", path, "minor"]), 0);
+        assert_eq!(Module::load(path).unwrap().version, ModuleVersion::Text("1.5.0".into()));
+    }
+
+    #[test]
+    fn new_module_keeps_an_existing_file_without_force() {
+        let d = temp_dir("cli-new-module");
+        let module = d.join("pack.json");
+        let path = module.to_str().unwrap();
+        assert_eq!(run_args(&["new-module", path, "--author", "me"]), 0);
+        let created = Module::load(path).unwrap();
+        assert_eq!((created.name.as_str(), created.author.as_str(), created.mods.len()), ("pack", "me", 0));
+
+        std::fs::write(&module, r#"{"header": {"module_name": "t", "module_version": 1.0, "module_author": "t"},
+            "mods": {"create": {"mod_tag": "Both", "mod_type": "Forge", "mod_version": "0.5"}}}"#).unwrap();
+        assert_eq!(run_args(&["new-module", path]), 2);
+        assert_eq!(Module::load(path).unwrap().mods.len(), 1, "the populated module is untouched");
+        assert_eq!(run_args(&["new-module", path, "--force"]), 0);
+        assert!(Module::load(path).unwrap().mods.is_empty());
+    }
+
+    #[test]
+    fn duplicates_delete_keeps_one_copy() {
+        let d = temp_dir("cli-duplicates");
+        let dir = d.to_str().unwrap();
+        for name in ["sodium.jar", "sodium (1).jar", "sodium (2).jar"] {
+            std::fs::write(d.join(name), b"same bytes").unwrap();
+        }
+        std::fs::write(d.join("iris.jar"), b"different").unwrap();
+        assert_eq!(run_args(&["duplicates", dir]), 0);
+        assert!(d.join("sodium (1).jar").is_file(), "listing alone deletes nothing");
+        assert_eq!(run_args(&["duplicates", dir, "--delete", "--force"]), 0);
+        assert!(d.join("sodium.jar").is_file() && d.join("iris.jar").is_file());
+        assert!(!d.join("sodium (1).jar").exists() && !d.join("sodium (2).jar").exists());
+    }
+
+    #[test]
+    fn listed_jars_skip_what_the_module_protects() {
+        let d = temp_dir("cli-jars-protected");
+        let dir = d.to_str().unwrap();
+        for name in ["sodium.jar", "sodium (1).jar"] {
+            write_jar(&d.join(name), &[("fabric.mod.json", r#"{"id": "sodium", "version": "0.5.8"}"#)]);
+        }
+        let module = d.join("module.json");
+        let mut m = Module::new(module.to_str().unwrap(), "m", "");
+        m.mods.insert("sodium".into(), ModuleEntry { protected: true, ..tagged(Side::Client, ModLoader::Unknown) });
+        m.save(JsonStyle::default()).unwrap();
+        let module = module.to_str().unwrap();
+        assert_eq!(run_args(&["op", "delete", dir, "--jars", "sodium (1).jar", "--module", module, "--force"]), 0);
+        assert_eq!(run_args(&["duplicates", dir, "--delete", "--module", module, "--force"]), 0);
+        assert!(d.join("sodium.jar").is_file() && d.join("sodium (1).jar").is_file());
+    }
+
+    #[test]
+    fn listed_reports_honour_the_format() {
+        let d = temp_dir("cli-listed-format");
+        let dir = d.to_str().unwrap();
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", r#"{"id": "sodium", "version": "0.5.8"}"#)]);
+        let out = d.join("report.txt");
+        let out = out.to_str().unwrap();
+        assert_eq!(run_args(&["op", "report", dir, "--jars", "sodium.jar", "--format", "markdown", "--output", out]), 0);
+        let body = std::fs::read_to_string(out).unwrap();
+        assert!(body.starts_with("| Jar | Mod id |") && body.contains("| sodium.jar | sodium |"), "{body}");
+    }
+
+    #[test]
+    fn op_filter_copies_exactly_the_selected_jars() {
+        let d = temp_dir("cli-op-filter");
+        let dir = d.to_str().unwrap();
+        let fabric = |id: &str| format!(r#"{{"id": "{id}", "version": "1.0"}}"#);
+        let forge = |id: &str| format!("modLoader = \"javafml\"\n[[mods]]\nmodId = \"{id}\"\nversion = \"1.0\"\n");
+        write_jar(&d.join("minimap.jar"), &[("fabric.mod.json", &fabric("minimap"))]);
+        write_jar(&d.join("lib-forge.jar"), &[("META-INF/mods.toml", &forge("libforge"))]);
+        write_jar(&d.join("lib-fabric.jar"), &[("fabric.mod.json", &fabric("libfabric"))]);
+        write_jar(&d.join("backup.jar"), &[("META-INF/mods.toml", &forge("backup"))]);
+
+        let module = d.join("module.json");
+        let mut m = Module::new(module.to_str().unwrap(), "m", "");
+        for (id, tag, loader) in [
+            ("minimap", Side::Client, ModLoader::Fabric), ("libforge", Side::Both, ModLoader::Forge),
+            ("libfabric", Side::Both, ModLoader::Fabric), ("backup", Side::Server, ModLoader::Forge),
+        ] {
+            m.mods.insert(id.into(), tagged(tag, loader));
+        }
+        m.save(JsonStyle::default()).unwrap();
+        let out = d.join("out");
+        assert_eq!(run_args(&["op", "copy", dir, "--module", module.to_str().unwrap(),
+            "--filter", "client || (both && forge)", "--output", out.to_str().unwrap()]), 0);
+
+        let mut copied: Vec<String> = std::fs::read_dir(&out).unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        copied.sort();
+        assert_eq!(copied, ["lib-forge.jar", "minimap.jar"]);
+    }
+
+    #[test]
+    fn long_listings_collapse_to_a_count() {
+        let d = temp_dir("cli-list-limit");
+        for id in ["a", "b", "c"] {
+            write_jar(&d.join(format!("{id}.jar")), &[("fabric.mod.json", &format!(r#"{{"id": "{id}", "version": "1.0"}}"#))]);
+        }
+        let (results, _) = scan_directory(d.to_str().unwrap(), &Module::empty(), &ScanOptions::default());
+        assert_eq!(jar_listing(&results, Some(3), true, false).len(), 3);
+        assert_eq!(jar_listing(&results, Some(2), true, false),
+            ["3 jars — too many to list; use --list-all to show each one."]);
+        assert_eq!(jar_listing(&results, None, true, false).len(), 3, "--list-all lifts the limit");
+    }
+
+    #[test]
+    fn icons_stay_inside_the_output_folder() {
+        let d = temp_dir("cli-icons");
+        let mods = d.join("mods");
+        std::fs::create_dir_all(&mods).unwrap();
+        let jar = |id: &str| format!(r#"{{"id": "{id}", "version": "1.0", "icon": "icon.png"}}"#);
+        write_jar(&mods.join("evil.jar"), &[("fabric.mod.json", &jar("../../x")), ("icon.png", "evil")]);
+        write_jar(&mods.join("a.jar"), &[("fabric.mod.json", &jar("dup")), ("icon.png", "a")]);
+        write_jar(&mods.join("b.jar"), &[("fabric.mod.json", &jar("dup")), ("icon.png", "b")]);
+        let out = d.join("site").join("icons");
+        assert_eq!(run_args(&["icons", mods.to_str().unwrap(), "--output", out.to_str().unwrap()]), 0);
+
+        assert!(!d.join("x.png").exists() && !d.join("site").join("x.png").exists());
+        let mut names: Vec<String> = std::fs::read_dir(&out).unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["dup-1.png", "dup.png"]);
+        assert_eq!(std::fs::read_to_string(out.join("dup.png")).unwrap(), "a");
+        assert_eq!(std::fs::read_to_string(out.join("dup-1.png")).unwrap(), "b");
+    }
+
+    #[test]
+    fn folder_commands_honour_include_hidden_and_jobs() {
+        let d = temp_dir("cli-hidden-everywhere");
+        let mods = d.join("mods");
+        std::fs::create_dir_all(&mods).unwrap();
+        write_jar(&mods.join(".sodium.jar"), &[("fabric.mod.json", r#"{"id": "sodium", "version": "0.5.8"}"#)]);
+        let module = d.join("module.json");
+        let mut m = Module::new(module.to_str().unwrap(), "m", "");
+        m.mods.insert("sodium".into(), tagged(Side::Client, ModLoader::Fabric));
+        m.save(JsonStyle::default()).unwrap();
+        let (dir, module) = (mods.to_str().unwrap(), module.to_str().unwrap());
+        let out = d.join("missing.json");
+        let missing = |extra: &[&str]| {
+            let mut a = vec!["missing", dir, "--module", module, "--json", "--output", out.to_str().unwrap()];
+            a.extend(extra);
+            assert_eq!(run_args(&a), 0);
+            let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&out).unwrap()).unwrap();
+            json["missing"].as_array().unwrap().len()
+        };
+        assert_eq!(missing(&[]), 1, "the hidden jar is skipped by default");
+        assert_eq!(missing(&["--include-hidden", "--jobs", "2"]), 0);
+        assert_eq!(run_args(&["stats", dir, "--jobs", "x"]), 1);
+    }
+}
@@ -0,0 +1,190 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// compare.rs — Diff two mods folders by mod id
+//
+// A client pack and its server pack should share most mods at the same
+// versions.  Comparing needs no module: each folder is reduced to
+// `mod id → version` from the jars' own metadata and the two maps are diffed.
+// Across many instances (`scan --instances`) only presence is compared: which
+// mods every instance has and which only one does.
+//
+// Joining a server (`lodestone join`) is side-aware: of the server's mods,
+// the Client and Both ones belong on the client too, and a Server-only jar in
+// the client folder is dead weight.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use crate::{jar_files, parse_jar, Module, ScanResult, Side};
+
+/// Mod id → detected version for every jar in a folder that has metadata.
+pub type ModVersions = BTreeMap<String, Option<String>>;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct FolderDiff {
+    pub only_left:  Vec<String>,
+    pub only_right: Vec<String>,
+    /// Shared ids whose versions differ: (id, left version, right version)
+    pub versions:   Vec<(String, Option<String>, Option<String>)>,
+    /// Shared ids at the same version
+    pub same:       usize,
+}
+
+impl FolderDiff {
+    pub fn is_empty(&self) -> bool {
+        self.only_left.is_empty() && self.only_right.is_empty() && self.versions.is_empty()
+    }
+}
+
+pub fn mod_versions(dir: &str) -> ModVersions {
+    jar_files(dir, false).into_iter()
+        .filter_map(|jar| parse_jar(Path::new(dir).join(jar)).ok().flatten())
+        .map(|info| (info.mod_id, info.version))
+        .collect()
+}
+
+pub fn compare(left: &ModVersions, right: &ModVersions) -> FolderDiff {
+    let mut diff = FolderDiff::default();
+    for (id, lv) in left {
+        match right.get(id) {
+            None                 => diff.only_left.push(id.clone()),
+            Some(rv) if rv != lv => diff.versions.push((id.clone(), lv.clone(), rv.clone())),
+            Some(_)              => diff.same += 1,
+        }
+    }
+    diff.only_right = right.keys().filter(|id| !left.contains_key(*id)).cloned().collect();
+    diff
+}
+
+/// Mod ids across several instances.
+#[derive(Debug, Default, PartialEq)]
+pub struct Fleet {
+    /// Ids every instance has
+    pub everywhere: Vec<String>,
+    /// Ids exactly one instance has, with that instance
+    pub unique:     Vec<(String, String)>,
+}
+
+/// Compare the mod ids of named instances.
+pub fn fleet(instances: &[(String, BTreeSet<String>)]) -> Fleet {
+    let mut holders: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (name, ids) in instances {
+        for id in ids {
+            holders.entry(id).or_default().push(name);
+        }
+    }
+    let mut fleet = Fleet::default();
+    for (id, names) in holders {
+        if names.len() == instances.len() {
+            fleet.everywhere.push(id.to_string());
+        } else if let [only] = names[..] {
+            fleet.unique.push((id.to_string(), only.to_string()));
+        }
+    }
+    fleet
+}
+
+// ── Joining a server ──────────────────────────────────────────────────────────
+
+/// A mod the server's pack says the client needs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wanted {
+    pub id:      String,
+    pub version: Option<String>,
+    pub side:    Side,
+}
+
+/// The Client and Both mods in a scanned server folder.  A server folder
+/// rarely holds client-only mods, but some packs ship one folder for both.
+pub fn wanted_from_folder(server: &[ScanResult]) -> Vec<Wanted> {
+    server.iter()
+        .filter(|r| matches!(r.effective_side(), Side::Client | Side::Both))
+        .filter_map(|r| r.jar_info.as_ref().map(|i| Wanted { id: i.mod_id.clone(), version: i.version.clone(), side: r.effective_side() }))
+        .collect()
+}
+
+/// The Client and Both entries of a module that lists the server's pack.
+pub fn wanted_from_module(module: &Module) -> Vec<Wanted> {
+    module.mods.iter()
+        .filter(|(_, e)| matches!(e.mod_tag, Side::Client | Side::Both))
+        .map(|(id, e)| Wanted {
+            id:      id.clone(),
+            version: (e.mod_version != "*").then(|| e.mod_version.clone()),
+            side:    e.mod_tag,
+        })
+        .collect()
+}
+
+/// What to change in a client folder to join a server.
+#[derive(Debug, Default, PartialEq)]
+pub struct JoinPlan {
+    /// Wanted mods the client doesn't have
+    pub install: Vec<Wanted>,
+    /// Wanted mods the client has at another version: (wanted, client version)
+    pub update:  Vec<(Wanted, Option<String>)>,
+    /// Server-only jars in the client folder, as (jar, mod id)
+    pub remove:  Vec<(String, String)>,
+    /// Wanted mods already in place
+    pub ok:      usize,
+}
+
+/// Compare a scanned client folder with what the server wants.  Ids match
+/// in any case, and a mod bundled inside another jar counts as installed.
+pub fn join_plan(client: &[ScanResult], wanted: &[Wanted]) -> JoinPlan {
+    let mut have: BTreeMap<String, Option<String>> = BTreeMap::new();
+    for info in client.iter().filter_map(|r| r.jar_info.as_ref()) {
+        have.insert(info.mod_id.to_lowercase(), info.version.clone());
+        for b in &info.bundled {
+            have.entry(b.mod_id.to_lowercase()).or_insert_with(|| b.version.clone());
+        }
+    }
+    let mut plan = JoinPlan::default();
+    for w in wanted {
+        match have.get(&w.id.to_lowercase()) {
+            None => plan.install.push(w.clone()),
+            Some(v) if w.version.is_some() && v.is_some() && *v != w.version => plan.update.push((w.clone(), v.clone())),
+            Some(_) => plan.ok += 1,
+        }
+    }
+    plan.remove = client.iter()
+        .filter(|r| r.effective_side() == Side::Server)
+        .filter_map(|r| Some((r.jar_name.clone(), r.jar_info.as_ref()?.mod_id.clone())))
+        .collect();
+    plan
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Unit tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn versions(pairs: &[(&str, &str)]) -> ModVersions {
+        pairs.iter().map(|(id, v)| (id.to_string(), Some(v.to_string()))).collect()
+    }
+
+    #[test]
+    fn reports_missing_and_mismatched_mods() {
+        let client = versions(&[("create", "0.5.1"), ("jei", "15.2"), ("sodium", "0.5.8")]);
+        let server = versions(&[("create", "0.5.0"), ("jei", "15.2"), ("spark", "1.10")]);
+        let diff = compare(&client, &server);
+        assert_eq!(diff.only_left, vec!["sodium"]);
+        assert_eq!(diff.only_right, vec!["spark"]);
+        assert_eq!(diff.versions, vec![("create".into(), Some("0.5.1".into()), Some("0.5.0".into()))]);
+        assert_eq!(diff.same, 1);
+    }
+
+    #[test]
+    fn fleet_splits_shared_and_unique_mods() {
+        let set = |ids: &[&str]| ids.iter().map(|s| s.to_string()).collect::<BTreeSet<_>>();
+        let f = fleet(&[
+            ("a".into(), set(&["sodium", "create", "jei"])),
+            ("b".into(), set(&["sodium", "create"])),
+            ("c".into(), set(&["sodium", "jei", "ftb"])),
+        ]);
+        assert_eq!(f.everywhere, vec!["sodium"]);
+        assert_eq!(f.unique, vec![("ftb".to_string(), "c".to_string())]);
+    }
+}
@@ -0,0 +1,136 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// curseforge.rs — Jar identification from a CurseForge `minecraftinstance.json`
+//
+// The CurseForge launcher writes `minecraftinstance.json` into the instance
+// root (the parent of `mods/`).  It lists every addon it installed together
+// with the file name on disk, so a scan can identify those jars without
+// analysing them.  The manifest has no mod version, so that alone is still
+// read from the jar's own metadata.
+//
+// CurseForge knows projects by slug, not by the mod id inside the jar.  The
+// slug (last segment of the addon's website URL) is used as the id, which
+// matches for most mods (`jei`, `create`, `sodium`) but not all — so this is
+// opt-in via `ScanOptions::curseforge`.  Jars not listed fall back to normal
+// metadata parsing.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::{JarInfo, Links, ModLoader, VersionSource};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Instance {
+    #[serde(default)]
+    installed_addons: Vec<Addon>,
+    base_mod_loader:  Option<BaseLoader>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BaseLoader {
+    /// e.g. "forge-47.2.0", "neoforge-21.1.77", "fabric-0.15.11-1.20.1"
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Addon {
+    name: String,
+    #[serde(rename = "webSiteURL", default)]
+    web_site_url: Option<String>,
+    #[serde(rename = "installedFile")]
+    installed_file: Option<InstalledFile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InstalledFile {
+    #[serde(rename = "fileName")]
+    file_name: String,
+    #[serde(rename = "FileNameOnDisk", alias = "fileNameOnDisk", default)]
+    file_name_on_disk: Option<String>,
+}
+
+fn loader_from_name(name: &str) -> ModLoader {
+    let n = name.to_ascii_lowercase();
+    if n.starts_with("neoforge") { ModLoader::NeoForge }
+    else if n.starts_with("forge") { ModLoader::Forge }
+    else if n.starts_with("fabric") { ModLoader::Fabric }
+    else if n.starts_with("quilt") { ModLoader::Quilt }
+    else if n.starts_with("liteloader") { ModLoader::LiteLoader }
+    else if n.starts_with("rift") { ModLoader::Rift }
+    else { ModLoader::Unknown }
+}
+
+/// Jar file name → identification, from the `minecraftinstance.json` next to
+/// `mods_dir`.  `None` when there is no such file or it cannot be parsed.
+pub fn load_instance(mods_dir: &str) -> Option<BTreeMap<String, JarInfo>> {
+    let mods = Path::new(mods_dir).canonicalize().ok()?;
+    let root = mods.parent()?;
+    let raw = std::fs::read_to_string(root.join("minecraftinstance.json")).ok()?;
+    parse_instance(&raw)
+}
+
+fn parse_instance(raw: &str) -> Option<BTreeMap<String, JarInfo>> {
+    let inst: Instance = serde_json::from_str(raw).ok()?;
+    let loader = inst.base_mod_loader.as_ref()
+        .map(|l| loader_from_name(&l.name))
+        .unwrap_or(ModLoader::Unknown);
+
+    let map = inst.installed_addons.into_iter()
+        .filter_map(|a| {
+            let file = a.installed_file?;
+            let on_disk = file.file_name_on_disk.unwrap_or(file.file_name);
+            let slug = a.web_site_url.as_deref()
+                .and_then(|u| u.trim_end_matches('/').rsplit('/').next())
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+                .unwrap_or_else(|| a.name.to_lowercase().replace(' ', "-"));
+            Some((on_disk, JarInfo {
+                mod_id: slug,
+                loader,
+                version: None,
+                declared_side: None,
+                dependencies: Vec::new(),
+                breaks: Vec::new(),
+                version_source: VersionSource::Metadata,
+                icon: None,
+                metadata_file: "minecraftinstance.json".into(),
+                mixin_only: false,
+                bundled: Vec::new(),
+                links: Links::default(),
+            }))
+        })
+        .collect();
+    Some(map)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Unit tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_files_to_slugs() {
+        let raw = r#"{
+            "baseModLoader": { "name": "forge-47.2.0" },
+            "installedAddons": [
+                { "addonID": 238222, "name": "Just Enough Items (JEI)",
+                  "webSiteURL": "https://www.curseforge.com/minecraft/mc-mods/jei",
+                  "installedFile": { "id": 1, "fileName": "jei-1.20.1-forge-15.2.0.27.jar" } },
+                { "addonID": 328085, "name": "Create",
+                  "installedFile": { "id": 2, "fileName": "create.jar",
+                                     "FileNameOnDisk": "create-1.20.1-0.5.1.f.jar" } }
+            ]
+        }"#;
+        let map = parse_instance(raw).unwrap();
+        let jei = &map["jei-1.20.1-forge-15.2.0.27.jar"];
+        assert_eq!(jei.mod_id, "jei");
+        assert_eq!(jei.loader, ModLoader::Forge);
+        assert_eq!(map["create-1.20.1-0.5.1.f.jar"].mod_id, "create");
+    }
+}
@@ -0,0 +1,189 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// expr.rs — Tag and loader expressions for `op --filter`
+//
+// `--tag client,both` picks sides and `--loader forge` narrows them all to
+// one loader, which can't say "every client mod, plus the Forge mods tagged
+// both".  `--filter "client || (both && forge)"` can: the words are tags
+// (client, server, both, unknown) and loaders (forge, neoforge, fabric,
+// quilt, liteloader, rift), combined with `&&`, `||`, `!` and parentheses,
+// `!` binding tightest and `||` loosest.  `unknown` is the tag; a jar's
+// side and loader are read the way `--tag` and `--loader` read them.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use crate::{ModLoader, ScanResult, Side};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Side(Side),
+    Loader(ModLoader),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+fn tokens(s: &str) -> Result<Vec<Token>, String> {
+    let mut out = Vec::new();
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' => out.push(Token::Open),
+            ')' => out.push(Token::Close),
+            '!' => out.push(Token::Not),
+            '&' | '|' => {
+                if chars.next() != Some(c) {
+                    return Err(format!("'{c}' on its own in '{s}' (use {c}{c})"));
+                }
+                out.push(if c == '&' { Token::And } else { Token::Or });
+            }
+            c if c.is_alphanumeric() => {
+                let mut word = c.to_string();
+                while let Some(&c) = chars.peek().filter(|c| c.is_alphanumeric() || **c == '-' || **c == '_') {
+                    word.push(c);
+                    chars.next();
+                }
+                out.push(Token::Word(word));
+            }
+            c => return Err(format!("unexpected '{c}' in '{s}'")),
+        }
+    }
+    Ok(out)
+}
+
+/// Recursive descent over the tokens, one function per precedence level.
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    at:     usize,
+    source: &'a str,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.at)
+    }
+
+    fn eat(&mut self, t: &Token) -> bool {
+        let found = self.peek() == Some(t);
+        if found { self.at += 1; }
+        found
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut e = self.and()?;
+        while self.eat(&Token::Or) {
+            e = Expr::Or(Box::new(e), Box::new(self.and()?));
+        }
+        Ok(e)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut e = self.unary()?;
+        while self.eat(&Token::And) {
+            e = Expr::And(Box::new(e), Box::new(self.unary()?));
+        }
+        Ok(e)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        let source = self.source;
+        match self.tokens.get(self.at).cloned() {
+            Some(Token::Not) => {
+                self.at += 1;
+                Ok(Expr::Not(Box::new(self.unary()?)))
+            }
+            Some(Token::Open) => {
+                self.at += 1;
+                let e = self.or()?;
+                if !self.eat(&Token::Close) {
+                    return Err(format!("missing ')' in '{source}'"));
+                }
+                Ok(e)
+            }
+            Some(Token::Word(w)) => {
+                self.at += 1;
+                w.parse().map(Expr::Side)
+                    .or_else(|_| w.parse().map(Expr::Loader))
+                    .map_err(|_| format!("'{w}' in '{source}' is neither a tag nor a loader"))
+            }
+            Some(t) => Err(format!("unexpected {} in '{source}'", describe(&t))),
+            None => Err(format!("'{source}' ends where a tag or loader was expected")),
+        }
+    }
+}
+
+fn describe(t: &Token) -> &'static str {
+    match t {
+        Token::And   => "'&&'",
+        Token::Or    => "'||'",
+        Token::Close => "')'",
+        _            => "token",
+    }
+}
+
+impl std::str::FromStr for Expr {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut p = Parser { tokens: tokens(s)?, at: 0, source: s };
+        let e = p.or()?;
+        match p.peek() {
+            None => Ok(e),
+            Some(t) => Err(format!("unexpected {} in '{s}'", describe(t))),
+        }
+    }
+}
+
+impl Expr {
+    /// Whether a mod with this side and loader is selected.
+    pub fn holds(&self, side: Side, loader: ModLoader) -> bool {
+        match self {
+            Expr::Side(s)   => *s == side,
+            Expr::Loader(l) => *l == loader,
+            Expr::Not(e)    => !e.holds(side, loader),
+            Expr::And(a, b) => a.holds(side, loader) && b.holds(side, loader),
+            Expr::Or(a, b)  => a.holds(side, loader) || b.holds(side, loader),
+        }
+    }
+}
+
+/// Whether `expr` selects the scanned jar.
+pub fn matches_expr(r: &ScanResult, expr: &Expr) -> bool {
+    expr.holds(r.effective_side(), r.loader())
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Unit tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn precedence_and_parentheses_decide_what_is_selected() {
+        let e: Expr = "client || (both && forge)".parse().unwrap();
+        assert!(e.holds(Side::Client, ModLoader::Fabric));
+        assert!(e.holds(Side::Both, ModLoader::Forge));
+        assert!(!e.holds(Side::Both, ModLoader::Fabric));
+
+        // && binds tighter than ||, ! tighter than both
+        let e: Expr = "server || both && !fabric".parse().unwrap();
+        assert!(e.holds(Side::Server, ModLoader::Fabric));
+        assert!(!e.holds(Side::Both, ModLoader::Fabric));
+        assert!(e.holds(Side::Both, ModLoader::Quilt));
+        assert_eq!("unknown".parse::<Expr>().unwrap(), Expr::Side(Side::Unknown));
+
+        for bad in ["", "client |", "client | both", "(client", "client)", "clients", "client both"] {
+            assert!(bad.parse::<Expr>().is_err(), "{bad:?} should not parse");
+        }
+    }
+}
@@ -0,0 +1,100 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// hash.rs — Content hashes of jars on disk
+//
+// Hex digests in the forms other tools expect: Modrinth indexes use SHA-1
+// and SHA-512, everything Lodestone records itself uses SHA-256.
+// [`identical`] uses them to find byte-for-byte copies of one jar kept under
+// different names, which `lodestone duplicates` reports.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::Path;
+
+use sha2::Digest;
+
+/// Lower-case hex digest of the file at `path`, streamed in 64 KiB chunks.
+pub fn file_digest<D: Digest>(path: &Path) -> std::io::Result<String> {
+    let mut f = std::fs::File::open(path)?;
+    let mut hasher = D::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = f.read(&mut buf)?;
+        if n == 0 { break; }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher.finalize().iter().map(|b| format!("{b:02x}")).collect())
+}
+
+pub fn sha1(path: &Path) -> std::io::Result<String> {
+    file_digest::<sha1::Sha1>(path)
+}
+
+pub fn sha256(path: &Path) -> std::io::Result<String> {
+    file_digest::<sha2::Sha256>(path)
+}
+
+pub fn sha512(path: &Path) -> std::io::Result<String> {
+    file_digest::<sha2::Sha512>(path)
+}
+
+/// The jars among `jars` in `dir` with exactly the same content, one group
+/// per content, with the copy to keep first: the shortest name, since
+/// download copies gain suffixes like ` (1)`, then the first by name.  Only
+/// jars whose size another shares are hashed, `jobs` at a time.
+pub fn identical(dir: &str, jars: &[String], jobs: usize) -> Vec<Vec<String>> {
+    let mut by_size: BTreeMap<u64, Vec<&String>> = BTreeMap::new();
+    for jar in jars {
+        if let Ok(meta) = std::fs::metadata(Path::new(dir).join(jar)) {
+            by_size.entry(meta.len()).or_default().push(jar);
+        }
+    }
+    let candidates: Vec<&String> = by_size.into_values().filter(|v| v.len() > 1).flatten().collect();
+    let digests = crate::pool::map(&candidates, jobs, |_| false, |jar| sha256(&Path::new(dir).join(jar)).ok());
+    let mut by_hash: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (jar, digest) in candidates.iter().zip(digests) {
+        if let Some(d) = digest {
+            by_hash.entry(d).or_default().push(jar.to_string());
+        }
+    }
+    let mut groups: Vec<Vec<String>> = by_hash.into_values().filter(|g| g.len() > 1).collect();
+    for g in &mut groups {
+        g.sort_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+    }
+    groups.sort();
+    groups
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Unit tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_digests() {
+        let p = std::env::temp_dir().join(format!("lodestone-hash-{}", std::process::id()));
+        std::fs::write(&p, b"abc").unwrap();
+        assert_eq!(sha1(&p).unwrap(), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(
+            sha256(&p).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        );
+        std::fs::remove_file(p).unwrap();
+    }
+
+    #[test]
+    fn copies_group_with_the_plainest_name_first() {
+        let d = std::env::temp_dir().join(format!("lodestone-identical-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&d);
+        std::fs::create_dir_all(&d).unwrap();
+        for (name, body) in [("sodium.jar", "same"), ("sodium (1).jar", "same"), ("copy.jar", "same"), ("other.jar", "diff"), ("lone.jar", "x")] {
+            std::fs::write(d.join(name), body).unwrap();
+        }
+        let jars: Vec<String> = ["copy.jar", "lone.jar", "other.jar", "sodium (1).jar", "sodium.jar"].map(String::from).to_vec();
+        assert_eq!(identical(d.to_str().unwrap(), &jars, 2), [vec!["copy.jar", "sodium.jar", "sodium (1).jar"]]);
+        std::fs::remove_dir_all(d).unwrap();
+    }
+}
@@ -0,0 +1,359 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// history.rs — Per-run manifests for operations that change a mods folder
+//
+// Every move, delete or sort drops `<dir>/.lodestone/history/<run-id>.json`
+// recording the module, operation, side and each affected jar with its hash
+// and where it went.  A run is reversible when its jars still exist somewhere:
+// moves and sorts always are, deletes only when made with a backup (the jars
+// are moved into `<dir>/.lodestone/history/<run-id>/` instead of removed).
+//
+// A zip written by the zip operation is a backup of another kind; its jars
+// are put back with `lodestone restore` (see [`restore_from_backup`]).
+//
+// Names are recorded readably.  One that isn't valid Unicode also gets its
+// exact form (see [`raw_name`]), since the readable one names no file; in a
+// zip those go in a `ZIP_NAMES` entry, by entry index.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{hash, move_file, write_atomic, ModLoader, Side};
+
+/// One recorded operation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Run {
+    /// UTC start time as `YYYYMMDD-HHMMSS`, suffixed if two runs share a second
+    pub id:        String,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub operation: String,
+    pub module:    Option<String>,
+    pub tag:       Option<Side>,
+    /// Every side the operation selected, when it was more than one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags:      Vec<Side>,
+    /// Loader filter the operation was narrowed to, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loader:    Option<ModLoader>,
+    pub jars:      Vec<RunJar>,
+    #[serde(default)]
+    pub undone:    bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunJar {
+    pub name:   String,
+    /// The name on disk, when `name` is only a lossy rendering of it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_name: Option<String>,
+    /// SHA-256 before the operation; `None` if the jar could not be read
+    pub sha256: Option<String>,
+    /// Where the jar lives now; `None` once it has been deleted outright
+    pub now_at: Option<String>,
+    /// `now_at` on disk, likewise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_now_at: Option<String>,
+}
+
+impl RunJar {
+    /// The jar's file name in the folder it came from.
+    pub fn file(&self) -> OsString {
+        on_disk(&self.name, self.raw_name.as_deref())
+    }
+
+    pub fn now_path(&self) -> Option<PathBuf> {
+        self.now_at.as_deref().map(|p| PathBuf::from(on_disk(p, self.raw_now_at.as_deref())))
+    }
+}
+
+// ── Names on disk ─────────────────────────────────────────────────────────────
+
+/// `name` in a form a record can hold exactly, when it isn't valid Unicode:
+/// the hex of its bytes (of its UTF-16 units on Windows).  `None` for a
+/// name that is, which is recorded as it is.
+#[cfg(unix)]
+pub fn raw_name(name: &OsStr) -> Option<String> {
+    use std::os::unix::ffi::OsStrExt;
+    name.to_str().is_none().then(|| name.as_bytes().iter().map(|b| format!("{b:02x}")).collect())
+}
+
+#[cfg(unix)]
+fn from_raw(raw: &str) -> Option<OsString> {
+    use std::os::unix::ffi::OsStringExt;
+    let bytes = (0..raw.len()).step_by(2)
+        .map(|i| u8::from_str_radix(raw.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    Some(OsString::from_vec(bytes))
+}
+
+#[cfg(windows)]
+pub fn raw_name(name: &OsStr) -> Option<String> {
+    use std::os::windows::ffi::OsStrExt;
+    name.to_str().is_none().then(|| name.encode_wide().map(|u| format!("{u:04x}")).collect())
+}
+
+#[cfg(windows)]
+fn from_raw(raw: &str) -> Option<OsString> {
+    use std::os::windows::ffi::OsStringExt;
+    let units = (0..raw.len()).step_by(4)
+        .map(|i| u16::from_str_radix(raw.get(i..i + 4)?, 16).ok())
+        .collect::<Option<Vec<u16>>>()?;
+    Some(OsString::from_wide(&units))
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn raw_name(_: &OsStr) -> Option<String> {
+    None
+}
+
+#[cfg(not(any(unix, windows)))]
+fn from_raw(_: &str) -> Option<OsString> {
+    None
+}
+
+/// The name on disk recorded as `name`, with `raw` from [`raw_name`].
+pub fn on_disk(name: &str, raw: Option<&str>) -> OsString {
+    raw.and_then(from_raw).unwrap_or_else(|| name.into())
+}
+
+/// Entry of an op zip holding, by entry index, the [`raw_name`] of each jar
+/// whose name isn't valid Unicode.  Not a jar, so restoring skips it.
+pub const ZIP_NAMES: &str = ".lodestone-names.json";
+
+pub fn history_dir(dir: &str) -> PathBuf {
+    Path::new(dir).join(".lodestone").join("history")
+}
+
+/// Folder a delete-with-backup moves its jars into.
+pub fn backup_dir(dir: &str, id: &str) -> PathBuf {
+    history_dir(dir).join(id)
+}
+
+impl Run {
+    /// Start a run over `jars` in `dir`, hashing them while they are still there.
+    pub fn begin(operation: &str, module: Option<&str>, tag: Option<Side>, dir: &str, jars: &[&OsStr]) -> Self {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let base = utc_stamp(timestamp);
+        let history = history_dir(dir);
+        let id = (1..)
+            .map(|n| if n == 1 { base.clone() } else { format!("{base}-{n}") })
+            .find(|id| !history.join(format!("{id}.json")).exists() && !history.join(id).exists())
+            .expect("an unbounded range always yields a free id");
+        Self {
+            id, timestamp,
+            operation: operation.to_string(),
+            module:    module.map(String::from),
+            tag,
+            tags:   Vec::new(),
+            loader: None,
+            jars: jars.iter().map(|file| RunJar {
+                name:     file.to_string_lossy().into_owned(),
+                raw_name: raw_name(file),
+                sha256:   hash::sha256(&Path::new(dir).join(file)).ok(),
+                now_at:   None,
+                raw_now_at: None,
+            }).collect(),
+            undone: false,
+        }
+    }
+
+    /// After the operation: keep only jars that left `dir`, noting where each
+    /// ended up according to `dest` (`None` for "removed"), which is given
+    /// each jar's recorded name and its file name.
+    pub fn settle(&mut self, dir: &str, dest: impl Fn(&str, &OsStr) -> Option<PathBuf>) {
+        self.jars.retain(|j| !Path::new(dir).join(j.file()).exists());
+        for j in &mut self.jars {
+            let now = dest(&j.name, &j.file())
+                .filter(|p| p.is_file())
+                .map(|p| p.canonicalize().unwrap_or(p));
+            j.now_at = now.as_ref().map(|p| p.display().to_string());
+            j.raw_now_at = now.as_ref().and_then(|p| raw_name(p.as_os_str()));
+        }
+    }
+
+    /// (old, new) file name of every jar that now lives under another name.
+    pub fn renamed(&self) -> Vec<(&str, String)> {
+        self.jars.iter()
+            .filter_map(|j| {
+                let now = j.now_path()?.file_name()?.to_os_string();
+                (now != j.file()).then(|| (j.name.as_str(), now.to_string_lossy().into_owned()))
+            })
+            .collect()
+    }
+
+    pub fn reversible(&self) -> bool {
+        !self.undone && self.jars.iter().any(|j| j.now_at.is_some())
+    }
+
+    pub fn save(&self, dir: &str) -> std::io::Result<PathBuf> {
+        let history = history_dir(dir);
+        std::fs::create_dir_all(&history)?;
+        let path = history.join(format!("{}.json", self.id));
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+}
+
+/// Every recorded run for `dir`, oldest first.  Unreadable manifests are skipped.
+pub fn list(dir: &str) -> Vec<Run> {
+    let Ok(rd) = std::fs::read_dir(history_dir(dir)) else { return Vec::new() };
+    let mut runs: Vec<Run> = rd.filter_map(Result::ok)
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("json"))
+        .filter_map(|p| serde_json::from_str(&std::fs::read_to_string(p).ok()?).ok())
+        .collect();
+    runs.sort_by(|a, b| a.id.cmp(&b.id));
+    runs
+}
+
+/// Put the jars of run `id` back into `dir`.  Returns how many were restored
+/// and a note for each one that was skipped; the run is marked undone once
+/// nothing is left to restore.
+pub fn undo(dir: &str, id: &str) -> Result<(usize, Vec<String>), String> {
+    let mut run = list(dir).into_iter().find(|r| r.id == id)
+        .ok_or_else(|| format!("no run '{id}' in {}", history_dir(dir).display()))?;
+    if run.undone {
+        return Err(format!("run {id} was already undone"));
+    }
+    if !run.reversible() {
+        return Err(format!("run {id} ({}) cannot be undone: its jars were deleted without a backup", run.operation));
+    }
+
+    let (mut restored, mut skipped) = (0, Vec::new());
+    for j in &run.jars {
+        let Some(from) = j.now_path() else {
+            skipped.push(format!("{}: deleted without a backup", j.name));
+            continue;
+        };
+        let from = from.as_path();
+        let to = Path::new(dir).join(j.file());
+        if to.exists() {
+            // Either restored by an earlier, partial undo or replaced since
+            if from.exists() { skipped.push(format!("{}: a file with that name is back in the folder", j.name)); }
+            continue;
+        }
+        if !from.is_file() {
+            skipped.push(format!("{}: no longer at {}", j.name, from.display()));
+            continue;
+        }
+        if j.sha256.is_some() && hash::sha256(from).ok() != j.sha256 {
+            skipped.push(format!("{}: changed since the run", j.name));
+            continue;
+        }
+        move_file(from, &to).map_err(|e| format!("{}: {e}", j.name))?;
+        restored += 1;
+    }
+
+    if skipped.is_empty() {
+        run.undone = true;
+        run.save(dir).map_err(|e| e.to_string())?;
+        let _ = std::fs::remove_dir(backup_dir(dir, id));
+    }
+    Ok((restored, skipped))
+}
+
+/// Extract every jar in `backup_zip` into `dest_dir`.  Jars already in the
+/// folder are left alone unless `overwrite`.  Entries are flattened to their
+/// file name, so nothing can land outside `dest_dir`.  Returns how many
+/// were restored and the names skipped because they were already there.
+pub fn restore_from_backup(backup_zip: &str, dest_dir: &str, overwrite: bool) -> Result<(usize, Vec<String>), String> {
+    let file = std::fs::File::open(backup_zip).map_err(|e| format!("{backup_zip}: {e}"))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("{backup_zip}: {e}"))?;
+    let raw: BTreeMap<usize, String> = archive.by_name(ZIP_NAMES).ok()
+        .and_then(|e| serde_json::from_reader(e).ok())
+        .unwrap_or_default();
+    let (mut restored, mut skipped) = (0, Vec::new());
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(|e| format!("{backup_zip}: {e}"))?;
+        let Some(name) = entry.enclosed_name().and_then(|p| p.file_name()).map(|n| n.to_string_lossy().into_owned()) else {
+            continue;
+        };
+        if entry.is_dir() || !name.to_ascii_lowercase().ends_with(".jar") {
+            continue;
+        }
+        // The exact name, as long as it is still a single file name
+        let file = on_disk(&name, raw.get(&i).map(String::as_str));
+        let file = if Path::new(&file).file_name() == Some(file.as_os_str()) { file } else { name.clone().into() };
+        let to = Path::new(dest_dir).join(&file);
+        if to.exists() && !overwrite {
+            skipped.push(name);
+            continue;
+        }
+        let mut bytes = Vec::new();
+        std::io::Read::read_to_end(&mut entry, &mut bytes).map_err(|e| format!("{name}: {e}"))?;
+        write_atomic(&to, &bytes).map_err(|e| format!("{}: {e}", to.display()))?;
+        restored += 1;
+    }
+    Ok((restored, skipped))
+}
+
+/// `YYYYMMDD-HHMMSS` in UTC.
+fn utc_stamp(secs: u64) -> String {
+    let (year, month, day) = civil_date(secs);
+    let rem = secs % 86_400;
+    format!("{year:04}{month:02}{day:02}-{:02}{:02}{:02}", rem / 3600, rem / 60 % 60, rem % 60)
+}
+
+/// `YYYY-MM-DD` in UTC.
+pub fn utc_date(secs: u64) -> String {
+    let (year, month, day) = civil_date(secs);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// The UTC (year, month, day) `secs` after the Unix epoch.
+fn civil_date(secs: u64) -> (i64, i64, i64) {
+    let days = (secs / 86_400) as i64;
+    // Days since 1970-01-01 to a civil date (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Unit tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stamps_are_utc_dates() {
+        assert_eq!(utc_stamp(0), "19700101-000000");
+        assert_eq!(utc_stamp(1_709_210_096), "20240229-123456");
+    }
+
+    #[test]
+    fn backup_zips_restore_without_clobbering() {
+        let d = std::env::temp_dir().join(format!("lodestone-restore-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&d);
+        std::fs::create_dir_all(d.join("mods")).unwrap();
+        let backup = d.join("client.zip");
+        let mut w = zip::ZipWriter::new(std::fs::File::create(&backup).unwrap());
+        for (name, body) in [("sodium.jar", "new"), ("zoom.jar", "zoom"), ("notes.txt", "x")] {
+            w.start_file(name, zip::write::FileOptions::default()).unwrap();
+            std::io::Write::write_all(&mut w, body.as_bytes()).unwrap();
+        }
+        w.finish().unwrap();
+        std::fs::write(d.join("mods").join("sodium.jar"), "old").unwrap();
+
+        let (zip, mods) = (backup.to_str().unwrap(), d.join("mods").display().to_string());
+        assert_eq!(restore_from_backup(zip, &mods, false).unwrap(), (1, vec!["sodium.jar".to_string()]));
+        assert_eq!(std::fs::read_to_string(d.join("mods").join("sodium.jar")).unwrap(), "old");
+        assert!(!d.join("mods").join("notes.txt").exists());
+        assert_eq!(restore_from_backup(zip, &mods, true).unwrap(), (2, vec![]));
+        assert_eq!(std::fs::read_to_string(d.join("mods").join("sodium.jar")).unwrap(), "new");
+    }
+}
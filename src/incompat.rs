@@ -0,0 +1,72 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// incompat.rs — Installed mods that say they don't work together
+//
+// Mods declare what they break in their own metadata: Fabric's `breaks`
+// and `conflicts`, Quilt's `breaks`, and Forge/NeoForge dependencies with
+// `type = "incompatible"`.  `lodestone incompatible <dir>` reads those from
+// every jar in the folder (and the mods bundled inside them) and lists each
+// pair where the mod named is installed at a version the range covers.  A
+// range in a form we can't evaluate is listed too, marked as unchecked.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::collections::BTreeMap;
+
+use crate::version::declared_range_holds;
+use crate::ScanResult;
+
+/// One installed mod, as another mod's declaration can name it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Installed {
+    pub mod_id:  String,
+    pub version: Option<String>,
+    /// The jar in the folder it comes from (a bundle's outer jar)
+    pub jar:     String,
+}
+
+/// `by` declares that it breaks `with`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clash {
+    pub by:    Installed,
+    pub with:  Installed,
+    pub range: Option<String>,
+    /// False when the range couldn't be checked against `with`'s version
+    pub checked: bool,
+}
+
+/// Every installed mod by lowercase id, the first jar providing it winning.
+fn installed(results: &[ScanResult]) -> BTreeMap<String, Installed> {
+    let mut out = BTreeMap::new();
+    for r in results {
+        let Some(info) = &r.jar_info else { continue };
+        let top = std::iter::once((&info.mod_id, &info.version));
+        for (id, version) in top.chain(info.bundled.iter().map(|b| (&b.mod_id, &b.version))) {
+            out.entry(id.to_lowercase()).or_insert_with(|| Installed {
+                mod_id: id.clone(), version: version.clone(), jar: r.jar_name.clone(),
+            });
+        }
+    }
+    out
+}
+
+/// The declared incompatibilities among `results`, in jar then id order.
+/// A jar naming a mod it bundles itself is not reported.
+pub fn clashes(results: &[ScanResult]) -> Vec<Clash> {
+    let present = installed(results);
+    let mut out = Vec::new();
+    for r in results {
+        let Some(info) = &r.jar_info else { continue };
+        let by = Installed { mod_id: info.mod_id.clone(), version: info.version.clone(), jar: r.jar_name.clone() };
+        for b in &info.breaks {
+            let Some(with) = present.get(&b.mod_id.to_lowercase()) else { continue };
+            if with.jar == by.jar { continue; }
+            let holds = match (&b.version_range, &with.version) {
+                (None, _) => Some(true),
+                (Some(range), Some(v)) => declared_range_holds(v, range),
+                (Some(range), None) => (range.trim() == "*").then_some(true),
+            };
+            if holds == Some(false) { continue; }
+            out.push(Clash { by: by.clone(), with: with.clone(), range: b.version_range.clone(), checked: holds.is_some() });
+        }
+    }
+    out
+}
@@ -0,0 +1,77 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// lastscan.rs — What each jar looked like at the folder's last scan
+//
+// Every `lodestone scan` leaves `<dir>/.lodestone/last-scan.json` behind
+// with each jar it read and that jar's size and modification time:
+//
+//   { "jars": { "sodium.jar": { "size": 1048576, "modified": 1718029353 } } }
+//
+// `--changed-only` then scans (or with `op`, acts on) just the jars that are
+// new since, or whose size or time differ.  Jars that couldn't be read are
+// not recorded, so they keep coming back until they are fixed.  With no
+// record yet every jar counts as changed.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ScanResult;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stamp {
+    pub size:     u64,
+    /// Seconds since the Unix epoch
+    pub modified: u64,
+}
+
+impl Stamp {
+    pub fn of(path: &Path) -> Option<Self> {
+        let meta = std::fs::metadata(path).ok()?;
+        let modified = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_secs();
+        Some(Self { size: meta.len(), modified })
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct LastScan {
+    pub jars: BTreeMap<String, Stamp>,
+}
+
+pub fn record_path(dir: &str) -> PathBuf {
+    Path::new(dir).join(".lodestone").join("last-scan.json")
+}
+
+pub fn load(dir: &str) -> Option<LastScan> {
+    serde_json::from_str(&std::fs::read_to_string(record_path(dir)).ok()?).ok()
+}
+
+impl LastScan {
+    /// Whether `jar` in `dir` is new or different since this record.
+    pub fn changed(&self, dir: &str, jar: &str) -> bool {
+        self.jars.get(jar) != Stamp::of(&Path::new(dir).join(jar)).as_ref()
+    }
+
+    /// Take in a scan of `dir`: the jars it read are stamped as they are now,
+    /// and jars no longer in the folder are forgotten.
+    pub fn update(&mut self, dir: &str, results: &[ScanResult]) {
+        self.jars.retain(|name, _| Path::new(dir).join(name).is_file());
+        for r in results.iter().filter(|r| r.parse_error.is_none()) {
+            match Stamp::of(&Path::new(dir).join(&r.file_name)) {
+                Some(s) => { self.jars.insert(r.jar_name.clone(), s); }
+                None    => { self.jars.remove(&r.jar_name); }
+            }
+        }
+    }
+
+    pub fn save(&self, dir: &str) -> std::io::Result<PathBuf> {
+        let path = record_path(dir);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+}
@@ -0,0 +1,84 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// library.rs — Spotting library and API jars
+//
+// Libraries (architectury, fabric-api, cloth-config…) are needed on both
+// sides and nobody wants to classify them one by one.  A jar counts as one
+// when its id is on the known-libraries list, or when its metadata declares
+// mixins and no entrypoint at all.  A `lodestone-libraries.json` next to the
+// executable or in the working directory adds ids to the built-in list:
+//
+//   ["mylib", "someones-api"]
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::JarInfo;
+
+const CONFIG_FILE: &str = "lodestone-libraries.json";
+
+/// Library ids every pack runs into.
+const KNOWN: &[&str] = &[
+    "architectury", "balm", "bookshelf", "citadel", "cloth-config", "cloth_config", "collective",
+    "creativecore", "cupboard", "curios", "fabric-api", "fabric-language-kotlin", "fabric",
+    "forgeconfigapiport", "framework", "geckolib", "kotlinforforge", "midnightlib", "moonlight",
+    "owo", "placebo", "playeranimator", "puzzleslib", "resourcefullib", "supermartijn642corelib",
+    "terrablender", "trinkets", "yet_another_config_lib_v3",
+];
+
+/// The built-in ids followed by the user's, lowercased, loaded once per process.
+pub fn known() -> &'static [String] {
+    static KNOWN_IDS: OnceLock<Vec<String>> = OnceLock::new();
+    KNOWN_IDS.get_or_init(|| {
+        let mut all: Vec<String> = KNOWN.iter().map(|s| s.to_string()).collect();
+        all.extend(load_config().unwrap_or_default().into_iter().map(|s| s.to_lowercase()));
+        all
+    })
+}
+
+fn load_config() -> Option<Vec<String>> {
+    let exe_dir = std::env::current_exe().ok().and_then(|p| p.parent().map(PathBuf::from));
+    let raw = exe_dir.into_iter().chain([PathBuf::from(".")])
+        .find_map(|d| std::fs::read_to_string(d.join(CONFIG_FILE)).ok())?;
+    match serde_json::from_str(&raw) {
+        Ok(ids) => Some(ids),
+        Err(e) => {
+            eprintln!("warning: ignoring {CONFIG_FILE}: {e}");
+            None
+        }
+    }
+}
+
+/// Whether `id` is on the known-libraries list.
+pub fn is_known(id: &str) -> bool {
+    let id = id.to_lowercase();
+    known().contains(&id)
+}
+
+/// Why `info` looks like a library, if it does.  Either way the suggestion
+/// is `Both`.
+pub fn reason(info: &JarInfo) -> Option<&'static str> {
+    if is_known(&info.mod_id) {
+        Some("known library")
+    } else if info.mixin_only {
+        Some("mixins but no entrypoint")
+    } else {
+        None
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Unit tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_ids_match_in_any_case() {
+        assert!(is_known("architectury"));
+        assert!(is_known("Cloth-Config"));
+        assert!(!is_known("sodium"));
+    }
+}
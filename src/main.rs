@@ -7,17 +7,47 @@
 #![allow(dead_code)]
 
 mod bytecode;
+mod checksum;
+mod cli;
+mod compare;
+mod curseforge;
+mod expr;
+mod hash;
+mod history;
+mod incompat;
+mod lastscan;
+mod library;
+mod mapping;
+mod metadata;
+mod modrinth;
+mod packformat;
+mod pattern;
+mod placement;
+mod plan;
+mod pool;
+mod quarantine;
+mod remote;
+mod report;
+mod selftest;
+mod session;
+mod signing;
+mod stats;
+mod template;
+mod timing;
+mod version;
 
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::{OsStr, OsString};
 use std::fs;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 use iced::alignment;
 use iced::theme::Theme;
 use iced::widget::{
-    button, column, container, horizontal_rule, pick_list, row, scrollable, text,
+    button, checkbox, column, container, horizontal_rule, pick_list, row, scrollable, text,
     text_input, Space,
 };
 use iced::{Color, Element, Length, Settings, Size, Task};
@@ -49,6 +79,7 @@ mod pal {
     pub const ACCENT_TINT: Color = Color { r: 0.780, g: 0.416, b: 0.184, a: 0.10 }; // 10% tint
 
     // Status — desaturated to stay warm
+    #[allow(clippy::approx_constant)] // 0.318 is a colour channel, not 1/π
     pub const GREEN:  Color = Color { r: 0.196, g: 0.502, b: 0.318, a: 1.0 }; // muted sage green
     pub const AMBER:  Color = Color { r: 0.698, g: 0.502, b: 0.149, a: 1.0 }; // warm amber
     pub const RED:    Color = Color { r: 0.698, g: 0.239, b: 0.196, a: 1.0 }; // muted brick red
@@ -66,6 +97,10 @@ pub enum ModLoader {
     NeoForge,
     Fabric,
     Quilt,
+    /// Legacy 1.7–1.12 loader, `litemod.json`
+    LiteLoader,
+    /// Legacy 1.13 loader, `riftmod.json`
+    Rift,
 }
 
 impl std::fmt::Display for ModLoader {
@@ -76,11 +111,29 @@ impl std::fmt::Display for ModLoader {
             ModLoader::NeoForge => "NeoForge",
             ModLoader::Fabric   => "Fabric",
             ModLoader::Quilt    => "Quilt",
+            ModLoader::LiteLoader => "LiteLoader",
+            ModLoader::Rift     => "Rift",
         })
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+impl std::str::FromStr for ModLoader {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "unknown"  => Ok(ModLoader::Unknown),
+            "forge"    => Ok(ModLoader::Forge),
+            "neoforge" => Ok(ModLoader::NeoForge),
+            "fabric"   => Ok(ModLoader::Fabric),
+            "quilt"    => Ok(ModLoader::Quilt),
+            "liteloader" => Ok(ModLoader::LiteLoader),
+            "rift"     => Ok(ModLoader::Rift),
+            _ => Err(format!("unknown loader '{s}' (expected forge, neoforge, fabric, quilt, liteloader, rift or unknown)")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Side {
     Unknown,
     Client,
@@ -99,52 +152,583 @@ impl std::fmt::Display for Side {
     }
 }
 
+impl std::str::FromStr for Side {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "unknown" => Ok(Side::Unknown),
+            "client"  => Ok(Side::Client),
+            "server"  => Ok(Side::Server),
+            "both"    => Ok(Side::Both),
+            _ => Err(format!("unknown tag '{s}' (expected client, server, both or unknown)")),
+        }
+    }
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Module (JSON classification file)
 // ─────────────────────────────────────────────────────────────────────────────
 
+/// Fields are written in declaration order, so keep related ones together
+/// here rather than appending: saved modules are reviewed as git diffs.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModuleEntry {
     pub mod_version: String,
+    /// Versions that also count as a full match, e.g. `>=0.5.0,<0.6.0`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_range: Option<String>,
     pub mod_tag:     Side,
     pub mod_type:    ModLoader,
+    /// Never moved, deleted or quarantined by an operation, whatever its tag
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub protected:   bool,
+    /// Free-form remark from the module author, e.g. "needs client for config GUI"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note:        Option<String>,
+    /// Modrinth project slug, for pointing people at a download
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modrinth:    Option<String>,
+    /// CurseForge project slug
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub curseforge:  Option<String>,
+    /// Modpacks that include this mod, by the name `pack-tag` gave them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub packs:       Vec<String>,
+}
+
+/// `module_version` as written: a number (`1.0`) in older modules, a string
+/// such as `"1.2.3"` once it is bumped as semver.  Either is written back the
+/// way it was read.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ModuleVersion {
+    Number(f64),
+    Text(String),
+}
+
+impl std::fmt::Display for ModuleVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModuleVersion::Number(n) => write!(f, "{n}"),
+            ModuleVersion::Text(s)   => f.write_str(s),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 struct ModuleHeader {
     module_name:    String,
-    module_version: f64,
+    module_version: ModuleVersion,
     module_author:  String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description:    Option<String>,
+    /// Oldest Lodestone release that understands everything in the module
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    min_tool_version: Option<String>,
+    /// Anything else the author put in the header, kept as written
+    #[serde(flatten)]
+    extra:          serde_json::Map<String, serde_json::Value>,
 }
 
+/// Current module file format.  Bump this and add a step to [`migrate`]
+/// whenever the on-disk shape changes.
+const MODULE_FORMAT: u32 = 2;
+
 #[derive(Debug, Deserialize, Serialize)]
 struct ModuleJson {
+    /// Absent in the original format, which counts as 1
+    #[serde(default = "legacy_format")]
+    format_version: u32,
     header: ModuleHeader,
     mods:   BTreeMap<String, ModuleEntry>,
+    /// Unrecognised top-level keys, so a save doesn't drop them.  Without
+    /// serde_json's `preserve_order` feature these come out sorted by key,
+    /// which keeps saves byte-for-byte stable.
+    #[serde(flatten)]
+    extra:  serde_json::Map<String, serde_json::Value>,
+}
+
+fn legacy_format() -> u32 { 1 }
+
+/// The running build, compared against a module's `min_tool_version`.
+const TOOL_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Whether `running` predates `min`, comparing numeric components with
+/// missing ones as 0.  A `min` with no numbers never blocks.
+fn tool_older_than(running: &str, min: &str) -> bool {
+    let m = version::numeric_parts(min);
+    !m.is_empty() && version::compare_parts(&version::numeric_parts(running), &m).is_lt()
+}
+
+/// Upgrade a module document of any known format to the current one.
+/// Returns the parsed module and the format it was stored in.
+fn migrate(mut v: serde_json::Value) -> Result<(ModuleJson, u32), Box<dyn std::error::Error>> {
+    let from = v.get("format_version").and_then(|f| f.as_u64()).unwrap_or(1) as u32;
+    if from > MODULE_FORMAT {
+        return Err(format!(
+            "module format {from} is newer than this build understands ({MODULE_FORMAT}) — update Lodestone"
+        ).into());
+    }
+
+    // 1 → 2: hand-written modules often had numeric versions (`"mod_version": 1.2`)
+    if from < 2 {
+        if let Some(mods) = v.get_mut("mods").and_then(|m| m.as_object_mut()) {
+            for entry in mods.values_mut() {
+                if let Some(ver) = entry.get_mut("mod_version") {
+                    if let Some(n) = ver.as_number() {
+                        *ver = serde_json::Value::String(n.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    let mut raw: ModuleJson = serde_json::from_value(v)?;
+    raw.format_version = MODULE_FORMAT;
+    Ok((raw, from))
+}
+
+/// How module files are written.  Pretty is easier to review by hand; compact
+/// keeps very large modules small.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonStyle {
+    #[default]
+    Pretty,
+    Compact,
 }
 
 #[derive(Debug, Clone)]
 pub struct Module {
     pub name:    String,
-    pub version: f64,
+    pub version: ModuleVersion,
     pub author:  String,
     pub mods:    BTreeMap<String, ModuleEntry>,
+    pub description: Option<String>,
+    /// Oldest Lodestone release the author says the module needs
+    pub min_tool_version: Option<String>,
     pub path:    String,
+    /// Older format the file was upgraded from on load; saving rewrites it
+    /// in the current format.
+    pub migrated_from: Option<u32>,
+    /// Header and top-level fields Lodestone doesn't use, written back on save
+    header_extra: serde_json::Map<String, serde_json::Value>,
+    extra:        serde_json::Map<String, serde_json::Value>,
 }
 
 impl Module {
+    /// Read a module.  One that needs a newer Lodestone loads anyway, with
+    /// [`Module::too_new`] saying so, unless `LODESTONE_STRICT_VERSION` is set.
+    /// A URL is fetched into the cache first (see `remote.rs`), and the
+    /// module's `path` is then the cached copy.
     fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let raw: ModuleJson = serde_json::from_str(&fs::read_to_string(path)?)?;
-        Ok(Self {
+        let cached;
+        let path = if remote::is_url(path) {
+            cached = remote::fetch(path)?.display().to_string();
+            cached.as_str()
+        } else {
+            path
+        };
+        let (raw, from) = migrate(serde_json::from_str(&read_module_file(path)?)?)?;
+        for (id, e) in &raw.mods {
+            if let Some(r) = &e.version_range {
+                version::check_range(r).map_err(|e| format!("{id}: {e}"))?;
+            }
+        }
+        let module = Self {
             name:    raw.header.module_name,
             version: raw.header.module_version,
             author:  raw.header.module_author,
             mods:    raw.mods,
+            description: raw.header.description,
+            min_tool_version: raw.header.min_tool_version,
             path:    path.to_string(),
-        })
+            migrated_from: (from < MODULE_FORMAT).then_some(from),
+            header_extra: raw.header.extra,
+            extra:        raw.extra,
+        };
+        match module.too_new() {
+            Some(why) if std::env::var_os("LODESTONE_STRICT_VERSION").is_some() => Err(why.into()),
+            _ => Ok(module),
+        }
+    }
+
+    /// Why this build may misread the module, when its `min_tool_version`
+    /// is newer than the running Lodestone.
+    pub fn too_new(&self) -> Option<String> {
+        let min = self.min_tool_version.as_deref()?;
+        tool_older_than(TOOL_VERSION, min).then(|| format!(
+            "module needs Lodestone {min} or newer (this is {TOOL_VERSION}); fields it added may be ignored"
+        ))
+    }
+
+    /// A new module with no entries, to be saved at `path`.
+    fn new(path: &str, name: &str, author: &str) -> Self {
+        Self {
+            name: name.to_string(), version: ModuleVersion::Text("1.0.0".into()), author: author.to_string(),
+            path: path.to_string(),
+            ..Self::empty()
+        }
+    }
+
+    /// A module with no entries, for scans that only need detection.
+    fn empty() -> Self {
+        Self {
+            name: String::new(), version: ModuleVersion::Number(0.0), author: String::new(),
+            mods: BTreeMap::new(), description: None, min_tool_version: None,
+            path: String::new(), migrated_from: None,
+            header_extra: serde_json::Map::new(), extra: serde_json::Map::new(),
+        }
+    }
+
+    /// Give every entry for `loader` the tag `tag`; returns how many changed.
+    fn retag_by_loader(&mut self, loader: ModLoader, tag: Side) -> usize {
+        let mut n = 0;
+        for e in self.mods.values_mut().filter(|e| e.mod_type == loader && e.mod_tag != tag) {
+            e.mod_tag = tag;
+            n += 1;
+        }
+        n
+    }
+
+    /// Ids of the entries `pattern` matches, for previewing a retag.
+    fn ids_matching(&self, pattern: &pattern::IdPattern) -> Vec<String> {
+        self.mods.keys().filter(|id| pattern.is_match(id)).cloned().collect()
+    }
+
+    /// Give every entry whose id matches `pattern` the tag `tag`; returns how many changed.
+    fn retag_by_pattern(&mut self, pattern: &pattern::IdPattern, tag: Side) -> usize {
+        let ids = self.ids_matching(pattern);
+        self.retag_ids(&ids, tag)
+    }
+
+    /// Ids of the entries on the known-libraries list, for suggesting `Both`.
+    fn library_ids(&self) -> Vec<String> {
+        self.mods.keys().filter(|id| library::is_known(id)).cloned().collect()
+    }
+
+    /// Give the entries for `ids` the tag `tag`; returns how many changed.
+    fn retag_ids(&mut self, ids: &[String], tag: Side) -> usize {
+        let mut n = 0;
+        for id in ids {
+            if let Some(e) = self.mods.get_mut(id).filter(|e| e.mod_tag != tag) {
+                e.mod_tag = tag;
+                n += 1;
+            }
+        }
+        n
+    }
+
+    /// Entries with no jar in `results`, by id.  A jar counts when its
+    /// metadata names the mod, whatever its version, and so does a jar
+    /// bundling it.
+    fn missing_from<'a>(&'a self, results: &[ScanResult]) -> Vec<(&'a str, &'a ModuleEntry)> {
+        let present: BTreeSet<&str> = results.iter()
+            .filter_map(|r| r.jar_info.as_ref())
+            .flat_map(|i| std::iter::once(i.mod_id.as_str()).chain(i.bundled.iter().map(|b| b.mod_id.as_str())))
+            .collect();
+        self.mods.iter()
+            .filter(|(id, _)| !present.contains(id.as_str()))
+            .map(|(id, e)| (id.as_str(), e))
+            .collect()
+    }
+
+    /// Fold entries whose ids differ only in case (`JEI` and `jei`) into one
+    /// under the lowercase id.  The newest version wins, then the entry with
+    /// the most filled-in fields; the others only fill gaps.  Tag and loader
+    /// disagreements are reported in the merge rather than resolved silently.
+    fn dedupe_case(&mut self) -> Vec<CaseMerge> {
+        let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for id in self.mods.keys() {
+            groups.entry(id.to_lowercase()).or_default().push(id.clone());
+        }
+        let mut merges = Vec::new();
+        for (key, ids) in groups.into_iter().filter(|(_, ids)| ids.len() > 1) {
+            let mut entries: Vec<(String, ModuleEntry)> = ids.iter()
+                .filter_map(|id| self.mods.remove(id).map(|e| (id.clone(), e)))
+                .collect();
+            entries.sort_by(|(a_id, a), (b_id, b)| {
+                version::numeric_parts(&b.mod_version).cmp(&version::numeric_parts(&a.mod_version))
+                    .then_with(|| completeness(b).cmp(&completeness(a)))
+                    .then_with(|| (*b_id == key).cmp(&(*a_id == key)))
+            });
+            let mut rest = entries.into_iter();
+            let (kept_id, mut kept) = rest.next().expect("groups have two or more ids");
+            let mut conflicts = Vec::new();
+            for (id, e) in rest {
+                if kept.mod_tag == Side::Unknown {
+                    kept.mod_tag = e.mod_tag;
+                } else if e.mod_tag != Side::Unknown && e.mod_tag != kept.mod_tag {
+                    conflicts.push(format!("{id} is tagged {}, kept {} from {kept_id}", e.mod_tag, kept.mod_tag));
+                }
+                if e.mod_type != kept.mod_type && e.mod_type != ModLoader::Unknown {
+                    conflicts.push(format!("{id} is for {}, kept {} from {kept_id}", e.mod_type, kept.mod_type));
+                }
+                kept.note       = kept.note.or(e.note);
+                kept.modrinth   = kept.modrinth.or(e.modrinth);
+                kept.curseforge = kept.curseforge.or(e.curseforge);
+                kept.version_range = kept.version_range.or(e.version_range);
+                kept.protected |= e.protected;
+                for pack in &e.packs {
+                    if !kept.packs.contains(pack) { kept.packs.push(pack.clone()); }
+                }
+            }
+            self.mods.insert(key.clone(), kept);
+            merges.push(CaseMerge { key, merged: ids, conflicts });
+        }
+        merges
+    }
+
+    /// Tag every scanned mod with the side its own metadata declares: new
+    /// entries are added, `Unknown` ones filled in, and a tag someone already
+    /// set is only replaced with `overwrite`.  Jars that declare nothing, or
+    /// disagree with a tag kept as it was, are listed for review.
+    fn tag_from_declarations(&mut self, results: &[ScanResult], overwrite: bool) -> DeclaredTags {
+        let mut out = DeclaredTags::default();
+        for info in results.iter().filter_map(|r| r.jar_info.as_ref()) {
+            let id = &info.mod_id;
+            let Some(side) = info.declared_side else {
+                if self.mods.get(id).is_none_or(|e| e.mod_tag == Side::Unknown) {
+                    out.review.push(id.clone());
+                }
+                continue;
+            };
+            match self.mods.get_mut(id) {
+                None => {
+                    self.mods.insert(id.clone(), ModuleEntry {
+                        mod_version: info.version.clone().unwrap_or_else(|| "*".into()),
+                        mod_tag:     side,
+                        mod_type:    info.loader,
+                        protected: false, note: None, modrinth: None, curseforge: None, version_range: None, packs: Vec::new(),
+                    });
+                    out.added.push((id.clone(), side));
+                }
+                Some(e) if e.mod_tag == side => {}
+                Some(e) if e.mod_tag == Side::Unknown || overwrite => {
+                    out.updated.push((id.clone(), e.mod_tag, side));
+                    e.mod_tag = side;
+                }
+                Some(_) => out.review.push(id.clone()),
+            }
+        }
+        out
+    }
+
+    /// Add every scanned mod the module has no entry for, all tagged `tag`
+    /// with the jar's version and loader.  Returns the ids added, in folder
+    /// order; `Unknown` leaves them in the "need classification" queue.
+    fn add_unknowns(&mut self, results: &[ScanResult], tag: Side) -> Vec<String> {
+        let mut added = Vec::new();
+        for info in results.iter().filter(|r| r.module_entry.is_none()).filter_map(|r| r.jar_info.as_ref()) {
+            if self.mods.contains_key(&info.mod_id) { continue; }
+            self.mods.insert(info.mod_id.clone(), ModuleEntry {
+                mod_version: info.version.clone().unwrap_or_else(|| "*".into()),
+                mod_tag:     tag,
+                mod_type:    info.loader,
+                protected: false, note: None, modrinth: None, curseforge: None, version_range: None, packs: Vec::new(),
+            });
+            added.push(info.mod_id.clone());
+        }
+        added
+    }
+
+    /// Mark every entry whose Modrinth slug (or, without one, id) is in
+    /// `slugs` as part of the modpack `pack`.  Returns the ids newly marked
+    /// and the slugs no entry matched.  Matching ignores case.
+    fn mark_pack(&mut self, pack: &str, slugs: &[String]) -> (Vec<String>, Vec<String>) {
+        let wanted: BTreeSet<String> = slugs.iter().map(|s| s.to_lowercase()).collect();
+        let mut matched = BTreeSet::new();
+        let mut marked = Vec::new();
+        for (id, e) in self.mods.iter_mut() {
+            let key = e.modrinth.as_deref().unwrap_or(id).to_lowercase();
+            if !wanted.contains(&key) { continue; }
+            matched.insert(key);
+            if !e.packs.iter().any(|p| p == pack) {
+                e.packs.push(pack.to_string());
+                marked.push(id.clone());
+            }
+        }
+        let unmatched = wanted.into_iter().filter(|s| !matched.contains(s)).collect();
+        (marked, unmatched)
+    }
+
+    /// Drop the entries for `ids`; returns how many were there.
+    fn remove_entries(&mut self, ids: &[String]) -> usize {
+        ids.iter().filter(|id| self.mods.remove(id.as_str()).is_some()).count()
+    }
+
+    /// The module as it is written to disk, in the current format.
+    fn document(&self) -> ModuleJson {
+        ModuleJson {
+            format_version: MODULE_FORMAT,
+            header: ModuleHeader {
+                module_name:    self.name.clone(),
+                module_version: self.version.clone(),
+                module_author:  self.author.clone(),
+                description:    self.description.clone(),
+                min_tool_version: self.min_tool_version.clone(),
+                extra:          self.header_extra.clone(),
+            },
+            mods:  self.mods.clone(),
+            extra: self.extra.clone(),
+        }
+    }
+
+    /// Write the module back to `self.path`, replacing it only once the
+    /// whole document has been written.  The same module always produces
+    /// the same bytes, ending in a newline.  A `.gz` path is written gzipped.
+    fn save(&self, style: JsonStyle) -> Result<(), Box<dyn std::error::Error>> {
+        let raw = self.document();
+        let mut bytes = match style {
+            JsonStyle::Pretty  => serde_json::to_vec_pretty(&raw)?,
+            JsonStyle::Compact => serde_json::to_vec(&raw)?,
+        };
+        bytes.push(b'\n');
+        if is_gzip(&self.path) {
+            let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            gz.write_all(&bytes)?;
+            bytes = gz.finish()?;
+        }
+        write_atomic(Path::new(&self.path), &bytes)?;
+        Ok(())
+    }
+}
+
+/// Whether a module path names a gzipped file, e.g. `big.json.gz`.
+fn is_gzip(path: &str) -> bool {
+    path.to_ascii_lowercase().ends_with(".gz")
+}
+
+/// A module file's text, decompressed first when the path ends in `.gz`.
+fn read_module_file(path: &str) -> std::io::Result<String> {
+    if !is_gzip(path) {
+        return fs::read_to_string(path);
+    }
+    let mut raw = String::new();
+    flate2::read::GzDecoder::new(fs::File::open(path)?).read_to_string(&mut raw)?;
+    Ok(raw)
+}
+
+/// One set of case-variant module ids folded together by [`Module::dedupe_case`].
+#[derive(Debug, PartialEq)]
+pub struct CaseMerge {
+    /// The lowercase id the entry now lives under
+    pub key:       String,
+    /// The ids that were merged, as they were spelled
+    pub merged:    Vec<String>,
+    pub conflicts: Vec<String>,
+}
+
+/// What [`Module::tag_from_declarations`] changed, by mod id.
+#[derive(Debug, Default)]
+pub struct DeclaredTags {
+    /// New entries and the side their jar declares
+    pub added:   Vec<(String, Side)>,
+    /// Entries whose tag changed, from and to
+    pub updated: Vec<(String, Side, Side)>,
+    /// Mods that declare no side, or declare one other than the tag kept
+    pub review:  Vec<String>,
+}
+
+/// A mod id that two or more modules tag differently.
+#[derive(Debug, PartialEq)]
+pub struct TagConflict {
+    /// The id, lowercased so `JEI` and `jei` in different modules meet
+    pub id:   String,
+    /// Every module that tags it, as (module path, tag), in the order given
+    pub tags: Vec<(String, Side)>,
+}
+
+/// Ids whose tags disagree across `modules`.  `Unknown` only means nobody
+/// classified the mod yet, so it never conflicts with anything.
+pub fn tag_conflicts(modules: &[Module]) -> Vec<TagConflict> {
+    let mut by_id: BTreeMap<String, Vec<(String, Side)>> = BTreeMap::new();
+    for m in modules {
+        for (id, e) in m.mods.iter().filter(|(_, e)| e.mod_tag != Side::Unknown) {
+            by_id.entry(id.to_lowercase()).or_default().push((m.path.clone(), e.mod_tag));
+        }
+    }
+    by_id.into_iter()
+        .filter(|(_, tags)| tags.iter().any(|(_, t)| *t != tags[0].1))
+        .map(|(id, tags)| TagConflict { id, tags })
+        .collect()
+}
+
+/// One field of an entry that changed: (field, old, new).
+pub type FieldChange = (&'static str, String, String);
+
+/// What changed between two releases of a module, by mod id.
+#[derive(Debug, Default)]
+pub struct ModuleDiff {
+    pub added:   Vec<(String, ModuleEntry)>,
+    pub removed: Vec<String>,
+    /// Ids whose tag, version or loader changed
+    pub changed: Vec<(String, Vec<FieldChange>)>,
+}
+
+/// Compare `old` and `new` — typically v1.2 and v1.3 of the same module.
+pub fn diff_modules(old: &Module, new: &Module) -> ModuleDiff {
+    let mut diff = ModuleDiff {
+        removed: old.mods.keys().filter(|id| !new.mods.contains_key(*id)).cloned().collect(),
+        ..Default::default()
+    };
+    for (id, n) in &new.mods {
+        let Some(o) = old.mods.get(id) else {
+            diff.added.push((id.clone(), n.clone()));
+            continue;
+        };
+        let changes: Vec<_> = [
+            ("tag", o.mod_tag.to_string(), n.mod_tag.to_string()),
+            ("version", o.mod_version.clone(), n.mod_version.clone()),
+            ("type", o.mod_type.to_string(), n.mod_type.to_string()),
+        ].into_iter().filter(|(_, a, b)| a != b).collect();
+        if !changes.is_empty() {
+            diff.changed.push((id.clone(), changes));
+        }
+    }
+    diff
+}
+
+impl ModuleDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// The diff as a changelog: indented text, or Markdown sections to paste
+    /// into release notes.
+    pub fn changelog(&self, markdown: bool) -> Vec<String> {
+        let mut out = Vec::new();
+        let mut section = |title: &str, items: Vec<String>| {
+            if items.is_empty() { return; }
+            if markdown {
+                if !out.is_empty() { out.push(String::new()); }
+                out.push(format!("### {title} ({})", items.len()));
+                out.extend(items.into_iter().map(|i| format!("- {i}")));
+            } else {
+                out.push(format!("{title} ({}):", items.len()));
+                out.extend(items.into_iter().map(|i| format!("  {i}")));
+            }
+        };
+        let id = |id: &str| if markdown { format!("`{id}`") } else { id.to_string() };
+        section("Added", self.added.iter()
+            .map(|(i, e)| format!("{} {} ({}, {})", id(i), e.mod_version, e.mod_tag, e.mod_type))
+            .collect());
+        section("Removed", self.removed.iter().map(|i| id(i)).collect());
+        section("Changed", self.changed.iter()
+            .map(|(i, changes)| format!("{}: {}", id(i), changes.iter()
+                .map(|(field, a, b)| format!("{field} {a} → {b}"))
+                .collect::<Vec<_>>().join(", ")))
+            .collect());
+        out
     }
 }
 
+/// How many of an entry's fields say something.
+fn completeness(e: &ModuleEntry) -> usize {
+    [e.mod_version != "*", e.mod_tag != Side::Unknown, e.mod_type != ModLoader::Unknown,
+     e.note.is_some(), e.modrinth.is_some(), e.curseforge.is_some(), e.version_range.is_some()]
+        .into_iter().filter(|b| *b).count()
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Jar detection
 // ─────────────────────────────────────────────────────────────────────────────
@@ -155,11 +739,96 @@ pub struct JarInfo {
     pub loader:       ModLoader,
     pub version:      Option<String>,
     pub declared_side:Option<Side>,
+    /// Other mods this jar declares a dependency on (loader/platform ids excluded)
+    pub dependencies: Vec<Dependency>,
+    /// Mods this jar says it doesn't work alongside: Fabric's `breaks` and
+    /// `conflicts`, Quilt's `breaks`, Forge/NeoForge `incompatible` entries
+    pub breaks:       Vec<Dependency>,
+    /// Where `version` was read from
+    pub version_source: VersionSource,
+    /// Path of the mod's logo inside the jar, as its metadata declares it
+    pub icon:         Option<String>,
+    /// The file the id and loader were read from, e.g. `META-INF/mods.toml`
+    pub metadata_file: String,
+    /// Declares mixin configs but no entrypoint at all, as library and API
+    /// jars tend to
+    pub mixin_only:   bool,
+    /// Mods shipped inside this jar (jar-in-jar), including ones nested deeper
+    pub bundled:      Vec<BundledMod>,
+    /// Where the mod's metadata points people for it
+    pub links:        Links,
+}
+
+/// A mod's homepage and issue tracker: `displayURL` / `issueTrackerURL` in
+/// mods.toml, `contact.homepage` / `contact.issues` for Fabric and Quilt.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Links {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issues:   Option<String>,
+}
+
+impl Links {
+    pub fn is_empty(&self) -> bool {
+        self.homepage.is_none() && self.issues.is_none()
+    }
+
+    /// `contact.homepage` and `contact.issues` of a Fabric or Quilt metadata object.
+    fn from_contact(metadata: Option<&serde_json::Value>) -> Self {
+        let contact = metadata.and_then(|m| m.get("contact"));
+        let url = |key: &str| contact.and_then(|c| c.get(key)).and_then(|x| x.as_str())
+            .filter(|s| !s.is_empty()).map(String::from);
+        Self { homepage: url("homepage"), issues: url("issues") }
+    }
+}
+
+/// A mod bundled inside another jar, which doesn't show up as a file of its own.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BundledMod {
+    pub mod_id:  String,
+    pub version: Option<String>,
+    /// Where it sits inside the jar that bundles it, e.g. `META-INF/jars/fabric-api-base.jar`
+    pub path:    String,
+}
+
+/// One declared dependency, as far as the metadata format says.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dependency {
+    pub mod_id:        String,
+    /// Range in the format's own syntax: "[0.5,)" for Forge, ">=0.5" for Fabric
+    pub version_range: Option<String>,
+    /// False for optional, recommended or incompatible declarations
+    pub mandatory:     bool,
+}
+
+impl Dependency {
+    fn new(mod_id: &str, version_range: Option<String>, mandatory: bool) -> Self {
+        Self { mod_id: mod_id.to_string(), version_range, mandatory }
+    }
+}
+
+/// Which file in the jar supplied the version.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VersionSource {
+    /// The loader metadata file (mods.toml, fabric.mod.json, …)
+    #[default]
+    Metadata,
+    /// `Implementation-Version` in META-INF/MANIFEST.MF, used when the
+    /// metadata only has an unexpanded `${…}` build placeholder
+    Manifest,
+    /// `mod_version` / `version` from a bundled gradle.properties — only dev
+    /// or CI builds ship one, so treat the jar as a development artifact
+    GradleProperties,
 }
 
 #[derive(Debug, Clone)]
 pub struct ScanResult {
+    /// The file name for display; may be lossy on names that aren't UTF-8
     pub jar_name:     String,
+    /// The file name as it is on disk — join this, not `jar_name`, to reach the file
+    pub file_name:    OsString,
     pub jar_info:     Option<JarInfo>,
     pub parse_error:  Option<String>,
     pub module_entry: Option<ModuleEntry>,
@@ -169,33 +838,69 @@ pub struct ScanResult {
     pub bytecode_confidence: crate::bytecode::Confidence,
     /// A representative signal string shown in the UI tooltip
     pub bytecode_signal: Option<String>,
+    /// Signature consistency; `None` when the jar was not opened
+    pub signature: Option<signing::SignatureStatus>,
+    /// What kept a module match from being full
+    pub mismatch: Mismatch,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Which parts of a module entry a jar disagreed with.  Both false unless
+/// the match is Partial.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Mismatch {
+    pub version: bool,
+    pub loader:  bool,
+}
+
+impl Mismatch {
+    pub fn is_none(&self) -> bool {
+        !self.version && !self.loader
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum MatchQuality {
     Full,
+    /// Strict mode only: id, version and loader match but the module's tag is Unknown
+    Unclassified,
+    /// Id and loader match, but the jar declares no version to compare
+    IdOnly,
     Partial,
     Unidentified,
     Unknown,
+    /// The file is not a readable zip, so nothing about it can be trusted
+    Invalid,
 }
 
 impl ScanResult {
     fn status_label(&self) -> &'static str {
         match self.match_quality {
             MatchQuality::Full         => "Full match",
+            MatchQuality::Unclassified => "Classification needed",
+            MatchQuality::IdOnly       => "ID match (no version)",
             MatchQuality::Partial      => "Partial",
             MatchQuality::Unidentified => "Unidentified",
             MatchQuality::Unknown      => "Unknown",
+            MatchQuality::Invalid      => "Not a valid jar",
         }
     }
     fn status_color(&self) -> Color {
         match self.match_quality {
             MatchQuality::Full         => pal::GREEN,
+            MatchQuality::Unclassified => pal::PURPLE,
+            MatchQuality::IdOnly       => pal::GREEN,
             MatchQuality::Partial      => pal::AMBER,
             MatchQuality::Unidentified => pal::RED,
             MatchQuality::Unknown      => pal::FAINT,
+            MatchQuality::Invalid      => pal::RED,
         }
     }
+    /// Whether the module marks this jar's mod as protected.
+    fn is_protected(&self) -> bool {
+        self.module_entry.as_ref().is_some_and(|e| e.protected)
+    }
+
     fn effective_side(&self) -> Side {
         // Priority: module entry > manifest declared side > bytecode detection
         if let Some(s) = self.module_entry.as_ref().map(|e| e.mod_tag) {
@@ -213,6 +918,13 @@ impl ScanResult {
         }
     }
 
+    /// Loader by the same priority as the side: module entry, then the jar
+    fn loader(&self) -> ModLoader {
+        self.module_entry.as_ref().map(|e| e.mod_type)
+            .or_else(|| self.jar_info.as_ref().map(|i| i.loader))
+            .unwrap_or(ModLoader::Unknown)
+    }
+
     /// Human-readable source of the side determination
     fn side_source(&self) -> &'static str {
         if self.module_entry.is_some() { return "module"; }
@@ -220,6 +932,7 @@ impl ScanResult {
         match self.bytecode_confidence {
             crate::bytecode::Confidence::Annotation     => "annotation",
             crate::bytecode::Confidence::ClassReference => "bytecode",
+            crate::bytecode::Confidence::Layout         => "layout (heuristic)",
             crate::bytecode::Confidence::None           => "—",
         }
     }
@@ -242,34 +955,231 @@ fn json_str(v: &serde_json::Value) -> Option<String> {
         .or_else(|| v.as_f64().map(|f| f.to_string()))
 }
 
-fn parse_jar(path: &str) -> Result<Option<JarInfo>, Box<dyn std::error::Error>> {
-    let mut archive = zip::ZipArchive::new(fs::File::open(path)?)?;
-    for i in 0..archive.len() {
-        let mut e = archive.by_index(i)?;
-        let name = e.name().to_string();
-        if name.ends_with("mods.toml")        { return parse_forge(&mut e).map(Some); }
-        if name.ends_with("fabric.mod.json")  { return parse_fabric(&mut e).map(Some); }
-        if name.ends_with("quilt.mod.json")   { return parse_quilt(&mut e).map(Some); }
-        if name.ends_with("mcmod.info")       { return parse_legacy(&mut e).map(Some); }
+/// Dependency ids that name the game or a loader rather than another mod.
+const PLATFORM_IDS: &[&str] = &[
+    "minecraft", "java", "forge", "neoforge", "fabricloader", "fabric-loader", "quilt_loader",
+];
+
+/// Drop platform ids and collapse repeats, keeping the first declaration of
+/// each id (a jar bundling several mods may list the same dependency twice).
+fn mod_dependencies(mut deps: Vec<Dependency>) -> Vec<Dependency> {
+    deps.retain(|d| !PLATFORM_IDS.contains(&d.mod_id.as_str()));
+    deps.sort_by(|a, b| a.mod_id.cmp(&b.mod_id));
+    deps.dedup_by(|a, b| a.mod_id == b.mod_id);
+    deps
+}
+
+fn parse_jar(path: impl AsRef<Path>) -> Result<Option<JarInfo>, Box<dyn std::error::Error>> {
+    parse_archive(&mut zip::ZipArchive::new(fs::File::open(path)?)?, 0)
+}
+
+/// `modLoader` values in mods.toml that load on Forge or NeoForge: FML's
+/// own Java and no-code loaders, and the common language adapters.
+const FML_LANGUAGES: &[&str] = &["javafml", "lowcodefml", "kotlinforforge", "scorge", "kotori_scala"];
+
+/// Folders jar-in-jar bundles live in: Fabric/Quilt's, and Forge's JarJar.
+const NESTED_DIRS: &[&str] = &["META-INF/jars/", "META-INF/jarjar/"];
+
+/// How deep bundled jars inside bundled jars are followed.
+const NESTED_DEPTH: usize = 3;
+
+/// [`parse_jar`] over an open archive; `depth` counts how many jars it is
+/// nested in.
+fn parse_archive<R: Read + std::io::Seek>(
+    archive: &mut zip::ZipArchive<R>, depth: usize,
+) -> Result<Option<JarInfo>, Box<dyn std::error::Error>> {
+    // Every entry name first, so the patterns' order decides and not the zip's
+    let names: Vec<String> = archive.file_names().map(String::from).collect();
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+    let Some((pat, name)) = metadata::best(metadata::patterns(), &names) else { return Ok(None) };
+    let mut info = {
+        let mut e = archive.by_name(name)?;
+        match pat.parser {
+            metadata::Parser::ModsToml      => parse_forge(&mut e)?,
+            metadata::Parser::FabricModJson => parse_fabric(&mut e)?,
+            metadata::Parser::QuiltModJson  => parse_quilt(&mut e)?,
+            metadata::Parser::McmodInfo     => parse_legacy(&mut e)?,
+            metadata::Parser::LitemodJson   => parse_liteloader(&mut e)?,
+            metadata::Parser::RiftmodJson   => parse_rift(&mut e)?,
+        }
+    };
+    if let Some(l) = pat.loader { info.loader = l; }
+    info.metadata_file = name.to_string();
+
+    // Jars built without resource expansion ship "${file.jarVersion}" and the
+    // like; the manifest usually has the real value, and dev builds may still
+    // carry the gradle.properties it came from
+    let unresolved = |v: &Option<String>| v.as_deref().is_none_or(is_placeholder);
+    if unresolved(&info.version) {
+        if let Some(v) = manifest_version(archive) {
+            info.version = Some(v);
+            info.version_source = VersionSource::Manifest;
+        }
+    }
+    if unresolved(&info.version) {
+        if let Some(v) = gradle_version(archive) {
+            info.version = Some(v);
+            info.version_source = VersionSource::GradleProperties;
+        }
+    }
+    if depth < NESTED_DEPTH {
+        info.bundled = bundled_mods(archive, depth);
+    }
+    Ok(Some(info))
+}
+
+/// The mods shipped inside `archive` as jar-in-jar, and the ones inside
+/// those, flattened.  A bundled jar that can't be read is left out rather
+/// than failing its parent.
+fn bundled_mods<R: Read + std::io::Seek>(archive: &mut zip::ZipArchive<R>, depth: usize) -> Vec<BundledMod> {
+    let nested: Vec<String> = archive.file_names()
+        .filter(|n| NESTED_DIRS.iter().any(|d| n.starts_with(d)) && n.to_ascii_lowercase().ends_with(".jar"))
+        .map(String::from)
+        .collect();
+    let mut out = Vec::new();
+    for name in nested {
+        let mut bytes = Vec::new();
+        let Ok(mut e) = archive.by_name(&name) else { continue };
+        if e.read_to_end(&mut bytes).is_err() { continue; }
+        let Ok(mut inner) = zip::ZipArchive::new(std::io::Cursor::new(bytes)) else { continue };
+        let Ok(Some(info)) = parse_archive(&mut inner, depth + 1) else { continue };
+        out.push(BundledMod { mod_id: info.mod_id, version: info.version, path: name });
+        out.extend(info.bundled);
     }
-    Ok(None)
+    out
+}
+
+fn is_placeholder(v: &str) -> bool {
+    v.contains("${")
+}
+
+/// `Implementation-Version` from META-INF/MANIFEST.MF, if present and not
+/// itself a placeholder.
+fn manifest_version<R: Read + std::io::Seek>(archive: &mut zip::ZipArchive<R>) -> Option<String> {
+    let raw = read_zip_entry(&mut archive.by_name("META-INF/MANIFEST.MF").ok()?).ok()?;
+    raw.lines()
+        .find_map(|l| l.strip_prefix("Implementation-Version:"))
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty() && !is_placeholder(v))
+}
+
+/// `mod_version` (or plain `version`) from a gradle.properties anywhere in the jar.
+fn gradle_version<R: Read + std::io::Seek>(archive: &mut zip::ZipArchive<R>) -> Option<String> {
+    let name = archive.file_names().find(|n| n.ends_with("gradle.properties"))?.to_string();
+    let raw = read_zip_entry(&mut archive.by_name(&name).ok()?).ok()?;
+    let props: BTreeMap<&str, &str> = raw.lines()
+        .map(str::trim)
+        .filter(|l| !l.starts_with('#') && !l.starts_with('!'))
+        .filter_map(|l| l.split_once(['=', ':']))
+        .map(|(k, v)| (k.trim(), v.trim()))
+        .collect();
+    ["mod_version", "version"].iter()
+        .filter_map(|k| props.get(k))
+        .find(|v| !v.is_empty() && !is_placeholder(v))
+        .map(|v| v.to_string())
 }
 
 fn parse_forge(e: &mut zip::read::ZipFile) -> Result<JarInfo, Box<dyn std::error::Error>> {
     let raw = read_zip_entry(e)?;
-    let lower = raw.to_lowercase();
-    let loader = if lower.contains("neoforge") || lower.contains("neo-forge") {
-        ModLoader::NeoForge
-    } else {
-        ModLoader::Forge
-    };
     let parsed: toml::Value = toml::from_str(&raw)?;
     let first = parsed.get("mods").and_then(|v| v.as_array()).and_then(|a| a.first());
     let mod_id  = first.and_then(|m| m.get("modId")).and_then(|v| v.as_str())
                        .map(String::from).unwrap_or_else(|| "unknown".into());
     let version = first.and_then(|m| m.get("version").or_else(|| m.get("modVersion")))
                        .and_then(toml_str);
-    Ok(JarInfo { mod_id, loader, version, declared_side: None })
+    // [[dependencies.<modid>]] tables, one array per mod in the jar, each
+    // possibly listing several entries
+    let tables: Vec<&toml::Value> = parsed.get("dependencies").and_then(|v| v.as_table())
+        .map(|t| t.values().filter_map(|v| v.as_array()).flatten().collect())
+        .unwrap_or_default();
+    let incompatible = |d: &toml::Value| d.get("type").and_then(|v| v.as_str())
+        .is_some_and(|t| t.eq_ignore_ascii_case("incompatible"));
+    let (against, tables): (Vec<&toml::Value>, Vec<&toml::Value>) = tables.into_iter().partition(|d| incompatible(d));
+    let declared: Vec<Dependency> = tables.iter().copied().filter_map(forge_dependency).collect();
+    let breaks = mod_dependencies(against.into_iter().filter_map(forge_dependency).collect());
+
+    // A `side` on the mod itself, else the side its loader/minecraft
+    // dependencies are limited to.  Templates put side = "BOTH" on those, so
+    // only a one-sided restriction there says anything.
+    let declared_side = first.and_then(|m| m.get("side")).and_then(|v| v.as_str()).and_then(forge_side)
+        .or_else(|| {
+            let sides: BTreeSet<Side> = tables.iter()
+                .filter(|d| d.get("modId").and_then(|v| v.as_str()).is_some_and(|id| PLATFORM_IDS.contains(&id)))
+                .filter_map(|d| d.get("side").and_then(|v| v.as_str()).and_then(forge_side))
+                .collect();
+            match sides.into_iter().collect::<Vec<_>>()[..] {
+                [s @ (Side::Client | Side::Server)] => Some(s),
+                _ => None,
+            }
+        });
+
+    // An explicit dependency on the loader settles it; a language loader we
+    // don't know leaves it open; otherwise any mention of NeoForge in the
+    // file is the best hint left
+    let on = |id: &str| declared.iter().any(|d| d.mod_id == id);
+    let lower = raw.to_lowercase();
+    let language = parsed.get("modLoader").and_then(|v| v.as_str()).map(str::to_ascii_lowercase);
+    let loader = if on("neoforge") {
+        ModLoader::NeoForge
+    } else if on("forge") {
+        ModLoader::Forge
+    } else if language.is_some_and(|l| !FML_LANGUAGES.contains(&l.as_str())) {
+        ModLoader::Unknown
+    } else if lower.contains("neoforge") || lower.contains("neo-forge") {
+        ModLoader::NeoForge
+    } else {
+        ModLoader::Forge
+    };
+    let dependencies = mod_dependencies(declared);
+    // Per mod, or once for the whole file
+    let icon = first.and_then(|m| m.get("logoFile")).or_else(|| parsed.get("logoFile"))
+        .and_then(|v| v.as_str()).map(String::from);
+    // displayURL belongs to each mod, issueTrackerURL to the file
+    let url = |v: Option<&toml::Value>| v.and_then(|v| v.as_str()).filter(|s| !s.is_empty()).map(String::from);
+    let links = Links {
+        homepage: url(first.and_then(|m| m.get("displayURL"))),
+        issues:   url(parsed.get("issueTrackerURL")),
+    };
+    Ok(JarInfo {
+        mod_id, loader, version, declared_side, dependencies, breaks,
+        version_source: VersionSource::Metadata, icon, metadata_file: String::new(), mixin_only: false,
+        bundled: Vec::new(),
+        links,
+    })
+}
+
+/// `side = "CLIENT" | "SERVER" | "BOTH"` as used in mods.toml.
+fn forge_side(s: &str) -> Option<Side> {
+    match s.to_ascii_uppercase().as_str() {
+        "CLIENT" => Some(Side::Client),
+        "SERVER" => Some(Side::Server),
+        "BOTH"   => Some(Side::Both),
+        _        => None,
+    }
+}
+
+/// One `[[dependencies.<modid>]]` entry.  Forge marks optional ones with
+/// `mandatory = false`; NeoForge with `type = "optional"` (or "incompatible"…).
+fn forge_dependency(d: &toml::Value) -> Option<Dependency> {
+    let id = d.get("modId")?.as_str()?;
+    let mandatory = match d.get("type").and_then(|v| v.as_str()) {
+        Some(t) => t.eq_ignore_ascii_case("required"),
+        None    => d.get("mandatory").and_then(|v| v.as_bool()).unwrap_or(true),
+    };
+    let range = d.get("versionRange").and_then(|v| v.as_str()).map(String::from);
+    Some(Dependency::new(id, range, mandatory))
+}
+
+/// A Fabric/Quilt version predicate: a string or an array of alternatives.
+fn json_range(v: &serde_json::Value) -> Option<String> {
+    match v {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Array(a) => {
+            let alts: Vec<&str> = a.iter().filter_map(|x| x.as_str()).collect();
+            (!alts.is_empty()).then(|| alts.join(" || "))
+        }
+        _ => None,
+    }
 }
 
 fn parse_fabric(e: &mut zip::read::ZipFile) -> Result<JarInfo, Box<dyn std::error::Error>> {
@@ -277,13 +1187,49 @@ fn parse_fabric(e: &mut zip::read::ZipFile) -> Result<JarInfo, Box<dyn std::erro
     let mod_id  = v.get("id").and_then(|x| x.as_str()).map(String::from)
                    .unwrap_or_else(|| "unknown".into());
     let version = v.get("version").and_then(json_str);
-    let declared_side = v.get("environment").and_then(|x| x.as_str()).and_then(|s| match s {
+    let environment = v.get("environment").and_then(|x| x.as_str()).and_then(|s| match s {
         "client" => Some(Side::Client),
         "server" => Some(Side::Server),
         "*"      => Some(Side::Both),
         _        => None,
     });
-    Ok(JarInfo { mod_id, loader: ModLoader::Fabric, version, declared_side })
+    // "*" is the template default, so one-sided entrypoints say more than it does
+    let declared_side = match environment {
+        Some(Side::Client | Side::Server) => environment,
+        _ => fabric_entrypoint_side(&v).or(environment),
+    };
+    let dependencies = v.get("depends").and_then(|x| x.as_object())
+        .map(|o| mod_dependencies(o.iter()
+            .map(|(id, range)| Dependency::new(id, json_range(range), true))
+            .collect()))
+        .unwrap_or_default();
+    // `breaks` crashes the game, `conflicts` only warns; both are worth knowing
+    let breaks = mod_dependencies(["breaks", "conflicts"].iter()
+        .filter_map(|key| v.get(*key).and_then(|x| x.as_object()))
+        .flatten()
+        .map(|(id, range)| Dependency::new(id, json_range(range), false))
+        .collect());
+    Ok(JarInfo {
+        mod_id, loader: ModLoader::Fabric, version, declared_side, dependencies, breaks,
+        version_source: VersionSource::Metadata,
+        icon: v.get("icon").and_then(json_icon),
+        metadata_file: String::new(),
+        mixin_only: has_entries(v.get("mixins")) && !has_entrypoints(v.get("entrypoints")),
+        bundled: Vec::new(),
+        links: Links::from_contact(Some(&v)),
+    })
+}
+
+/// Client-only mods usually register just a `client` entrypoint; a `main`
+/// entrypoint runs everywhere, so only a lone `client` or `server` counts.
+fn fabric_entrypoint_side(v: &serde_json::Value) -> Option<Side> {
+    let eps = v.get("entrypoints")?.as_object()?;
+    let has = |key: &str| eps.get(key).and_then(|x| x.as_array()).is_some_and(|a| !a.is_empty());
+    match (has("main"), has("client"), has("server")) {
+        (false, true, false) => Some(Side::Client),
+        (false, false, true) => Some(Side::Server),
+        _ => None,
+    }
 }
 
 fn parse_quilt(e: &mut zip::read::ZipFile) -> Result<JarInfo, Box<dyn std::error::Error>> {
@@ -292,7 +1238,67 @@ fn parse_quilt(e: &mut zip::read::ZipFile) -> Result<JarInfo, Box<dyn std::error
     let mod_id  = ql.and_then(|l| l.get("id")).and_then(|x| x.as_str())
                     .map(String::from).unwrap_or_else(|| "unknown".into());
     let version = ql.and_then(|l| l.get("version")).and_then(json_str);
-    Ok(JarInfo { mod_id, loader: ModLoader::Quilt, version, declared_side: None })
+    // Entries are either a bare id string or an object with "id", "versions"
+    // and "optional"
+    let entries = |key: &str, mandatory: bool| ql.and_then(|l| l.get(key)).and_then(|x| x.as_array())
+        .map(|a| mod_dependencies(a.iter()
+            .filter_map(|d| match d.as_str() {
+                Some(id) => Some(Dependency::new(id, None, mandatory)),
+                None => Some(Dependency::new(
+                    d.get("id")?.as_str()?,
+                    d.get("versions").and_then(json_range),
+                    mandatory && !d.get("optional").and_then(|x| x.as_bool()).unwrap_or(false),
+                )),
+            })
+            .collect()))
+        .unwrap_or_default();
+    let (dependencies, breaks) = (entries("depends", true), entries("breaks", false));
+    Ok(JarInfo {
+        mod_id, loader: ModLoader::Quilt, version, declared_side: None, dependencies, breaks,
+        version_source: VersionSource::Metadata,
+        icon: ql.and_then(|l| l.get("metadata")).and_then(|m| m.get("icon")).and_then(json_icon),
+        metadata_file: String::new(),
+        mixin_only: has_entries(v.get("mixin")) && !has_entrypoints(ql.and_then(|l| l.get("entrypoints"))),
+        bundled: Vec::new(),
+        links: Links::from_contact(ql.and_then(|l| l.get("metadata"))),
+    })
+}
+
+/// A non-empty string, array or object, as `mixins` and entrypoints may be written.
+fn has_entries(v: Option<&serde_json::Value>) -> bool {
+    match v {
+        Some(serde_json::Value::String(s)) => !s.is_empty(),
+        Some(serde_json::Value::Array(a))  => !a.is_empty(),
+        Some(serde_json::Value::Object(o)) => !o.is_empty(),
+        _ => false,
+    }
+}
+
+/// Whether an `entrypoints` object registers anything under any key.
+fn has_entrypoints(v: Option<&serde_json::Value>) -> bool {
+    v.and_then(|x| x.as_object()).is_some_and(|eps| eps.values().any(|e| has_entries(Some(e))))
+}
+
+/// Fabric and Quilt `icon`: a path, or an object of pixel size → path, in
+/// which case the largest is taken.
+fn json_icon(v: &serde_json::Value) -> Option<String> {
+    match v {
+        serde_json::Value::String(p) => Some(p.clone()),
+        serde_json::Value::Object(sizes) => sizes.iter()
+            .filter_map(|(size, p)| Some((size.parse::<u32>().ok()?, p.as_str()?)))
+            .max_by_key(|(size, _)| *size)
+            .map(|(_, p)| p.to_string()),
+        _ => None,
+    }
+}
+
+/// The bytes of the icon at `icon` inside the jar at `path`.
+fn read_icon(path: impl AsRef<Path>, icon: &str) -> Option<Vec<u8>> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(path).ok()?).ok()?;
+    let mut entry = archive.by_name(icon.trim_start_matches('/')).ok()?;
+    let mut bytes = Vec::new();
+    entry.read_to_end(&mut bytes).ok()?;
+    Some(bytes)
 }
 
 fn parse_legacy(e: &mut zip::read::ZipFile) -> Result<JarInfo, Box<dyn std::error::Error>> {
@@ -301,76 +1307,514 @@ fn parse_legacy(e: &mut zip::read::ZipFile) -> Result<JarInfo, Box<dyn std::erro
     let mod_id  = first.and_then(|m| m.get("modid")).and_then(|x| x.as_str())
                        .map(String::from).unwrap_or_else(|| "unknown".into());
     let version = first.and_then(|m| m.get("version")).and_then(json_str);
-    Ok(JarInfo { mod_id, loader: ModLoader::Forge, version, declared_side: None })
+    Ok(JarInfo {
+        mod_id, loader: ModLoader::Forge, version, declared_side: None, dependencies: Vec::new(), breaks: Vec::new(),
+        version_source: VersionSource::Metadata,
+        icon: first.and_then(|m| m.get("logoFile")).and_then(|x| x.as_str())
+            .filter(|s| !s.is_empty()).map(String::from),
+        metadata_file: String::new(),
+        mixin_only: false,
+        bundled: Vec::new(),
+        links: Links::default(),
+    })
+}
+
+/// LiteLoader's `litemod.json` has no id; its `name` is what other mods and
+/// the loader refer to it by.
+fn parse_liteloader(e: &mut zip::read::ZipFile) -> Result<JarInfo, Box<dyn std::error::Error>> {
+    let v: serde_json::Value = serde_json::from_str(&read_zip_entry(e)?)?;
+    let mod_id  = v.get("name").and_then(|x| x.as_str())
+                   .map(String::from).unwrap_or_else(|| "unknown".into());
+    let version = v.get("version").and_then(json_str);
+    Ok(JarInfo {
+        mod_id, loader: ModLoader::LiteLoader, version, declared_side: None, dependencies: Vec::new(), breaks: Vec::new(),
+        version_source: VersionSource::Metadata,
+        icon: None,
+        metadata_file: String::new(),
+        mixin_only: false,
+        bundled: Vec::new(),
+        links: Links::default(),
+    })
+}
+
+/// Rift's `riftmod.json`: an `id`, and usually no version, which the
+/// manifest fallback then fills in.
+fn parse_rift(e: &mut zip::read::ZipFile) -> Result<JarInfo, Box<dyn std::error::Error>> {
+    let v: serde_json::Value = serde_json::from_str(&read_zip_entry(e)?)?;
+    let mod_id  = v.get("id").and_then(|x| x.as_str())
+                   .map(String::from).unwrap_or_else(|| "unknown".into());
+    let version = v.get("version").and_then(json_str);
+    Ok(JarInfo {
+        mod_id, loader: ModLoader::Rift, version, declared_side: None, dependencies: Vec::new(), breaks: Vec::new(),
+        version_source: VersionSource::Metadata,
+        icon: None,
+        metadata_file: String::new(),
+        mixin_only: false,
+        bundled: Vec::new(),
+        links: Links::default(),
+    })
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
 // Scan
 // ─────────────────────────────────────────────────────────────────────────────
 
-#[derive(Debug, Clone, Default)]
+/// Knobs that change how jars are matched against a module.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScanOptions {
+    /// A full match also requires the module to actually classify the mod,
+    /// i.e. its tag is something other than Unknown.
+    pub strict: bool,
+    /// Identify jars listed in a CurseForge `minecraftinstance.json` from that
+    /// file instead of opening them — faster, but ids are CurseForge slugs.
+    pub curseforge: bool,
+    pub version_tolerance: version::VersionTolerance,
+    /// Stop at the first jar that can't be read instead of scanning the rest
+    pub fail_fast: bool,
+    /// Guess a side from the jar's assets/ and data/ folders when nothing
+    /// better is known (see [`bytecode::layout_side`])
+    pub layout_hint: bool,
+    /// Also scan dot-prefixed jars, which are skipped by default
+    pub include_hidden: bool,
+    /// Identify jars from the folder's saved mapping (see `mapping.rs`)
+    /// instead of opening them; unmapped or changed jars are opened as usual.
+    pub mapping: bool,
+    /// Jars opened at once; 0 for one per CPU (see `pool.rs`)
+    pub jobs: usize,
+    /// Only jars new or changed since the folder's last recorded scan (see
+    /// `lastscan.rs`); every jar when there is no record.
+    pub changed_only: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct ScanSummary {
     pub total: usize,
     pub full:  usize,
+    pub unclassified: usize,
+    /// Matched by id and loader from a jar that declares no version
+    pub id_only: usize,
     pub partial: usize,
     pub unidentified: usize,
+    /// Jars with no metadata Lodestone can read — usually not mods at all
     pub unknown: usize,
+    /// Jars whose metadata was read but names no loader Lodestone can tell
+    /// apart — a gap in detection rather than a stray file
+    pub unknown_loader: usize,
+    /// Files that are not readable zips
+    pub invalid: usize,
+    /// Partial matches whose version differed (a jar can count here and below)
+    pub version_mismatch: usize,
+    /// Partial matches built for another loader
+    pub loader_mismatch:  usize,
+}
+
+/// File names of the jars directly inside `dir`, sorted.  The extension is
+/// matched case-insensitively — Windows downloads often end in `.JAR`.
+/// Hidden jars are left out; see [`list_jars_with`].
+fn list_jars(dir: &str) -> Vec<String> {
+    list_jars_with(dir, false)
 }
 
-fn scan_directory(dir: &str, module: &Module) -> (Vec<ScanResult>, ScanSummary) {
-    let mut jars: Vec<String> = fs::read_dir(dir)
+/// A dot-prefixed name — `.old.jar`, or a `.disabled/` or `.lodestone/`
+/// folder.  Anything that walks a mods folder skips these unless asked not to,
+/// so stashed copies are never scanned, moved or deleted by accident.
+fn is_hidden(p: &Path) -> bool {
+    p.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with('.'))
+}
+
+/// The immediate subfolders of `dir`, sorted, for treating each as its own
+/// pack.  Hidden ones (`.lodestone/` among them) are skipped unless asked.
+fn pack_dirs(dir: &str, include_hidden: bool) -> Vec<String> {
+    let mut dirs: Vec<String> = fs::read_dir(dir)
         .map(|rd| rd
             .filter_map(Result::ok)
             .map(|e| e.path())
-            .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("jar"))
-            .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
-            .collect()
-        )
+            .filter(|p| p.is_dir() && (include_hidden || !is_hidden(p)))
+            .map(|p| p.display().to_string())
+            .collect())
         .unwrap_or_default();
-    jars.sort();
-
-    let mut results = Vec::new();
-    for jar_name in jars {
-        let path = format!("{}/{}", dir.trim_end_matches('/'), jar_name);
+    dirs.sort();
+    dirs
+}
 
-        let (jar_info, parse_error) = match parse_jar(&path) {
-            Ok(i)  => (i, None),
-            Err(e) => (None, Some(e.to_string())),
-        };
+/// The mods folder of a game instance folder: `mods/`, or `.minecraft/mods/`
+/// as some launchers lay it out.
+fn instance_mods_dir(instance: &str) -> Option<String> {
+    [Path::new(instance).join("mods"), Path::new(instance).join(".minecraft").join("mods")]
+        .into_iter()
+        .find(|p| p.is_dir())
+        .map(|p| p.display().to_string())
+}
 
-        // Bytecode analysis — runs regardless of whether a module is loaded
-        let bc = bytecode::analyse_jar(&path).unwrap_or_else(bytecode::BytecodeEvidence::unknown);
+/// [`list_jars`], with hidden jars included when `include_hidden` is set.
+fn list_jars_with(dir: &str, include_hidden: bool) -> Vec<String> {
+    jar_files(dir, include_hidden).into_iter().map(|n| n.to_string_lossy().into_owned()).collect()
+}
 
-        let (module_entry, match_quality) = if let Some(info) = &jar_info {
-            if let Some(entry) = module.mods.get(&info.mod_id).cloned() {
-                let version_ok = entry.mod_version == "*"
-                    || info.version.as_deref().map(|v| v == entry.mod_version).unwrap_or(false);
-                let loader_ok  = info.loader == entry.mod_type;
-                let q = if version_ok && loader_ok { MatchQuality::Full } else { MatchQuality::Partial };
-                (Some(entry), q)
-            } else {
-                (None, MatchQuality::Unidentified)
-            }
-        } else {
-            (None, MatchQuality::Unknown)
-        };
+/// [`list_jars_with`] with the names exactly as on disk, for anything that
+/// has to open or move the files.
+fn jar_files(dir: &str, include_hidden: bool) -> Vec<OsString> {
+    let mut jars: Vec<OsString> = fs::read_dir(dir)
+        .map(|rd| rd
+            .filter_map(Result::ok)
+            .map(|e| e.path())
+            .filter(|p| include_hidden || !is_hidden(p))
+            .filter(|p| p.is_file())
+            .filter(|p| p.extension().and_then(|s| s.to_str())
+                .map(|e| e.eq_ignore_ascii_case("jar")).unwrap_or(false))
+            .filter_map(|p| p.file_name().map(|n| n.to_os_string()))
+            .collect()
+        )
+        .unwrap_or_default();
+    jars.sort();
+    jars
+}
 
-        results.push(ScanResult {
-            jar_name, jar_info, parse_error, module_entry, match_quality,
-            bytecode_side:       Some(bc.side),
-            bytecode_confidence: bc.confidence,
-            bytecode_signal:     bc.signal,
-        });
+fn scan_directory(dir: &str, module: &Module, opts: &ScanOptions) -> (Vec<ScanResult>, ScanSummary) {
+    scan_directory_with(dir, module, opts, &|_| {})
+}
+
+/// Like [`scan_directory`], but hands each result to `on_result` as soon as its
+/// jar has been processed so callers can stream output instead of buffering.
+fn scan_directory_with(
+    dir: &str, module: &Module, opts: &ScanOptions, on_result: &(dyn Fn(&ScanResult) + Sync),
+) -> (Vec<ScanResult>, ScanSummary) {
+    let (results, summary, _) = scan_directory_timed(dir, module, opts, on_result);
+    (results, summary)
+}
+
+/// [`scan_directory_with`], also returning how long each phase took.
+fn scan_directory_timed(
+    dir: &str, module: &Module, opts: &ScanOptions, on_result: &(dyn Fn(&ScanResult) + Sync),
+) -> (Vec<ScanResult>, ScanSummary, timing::ScanTiming) {
+    let mut timing = timing::ScanTiming::default();
+    let started = Instant::now();
+    let instance = if opts.curseforge { curseforge::load_instance(dir) } else { None };
+    let mapping = if opts.mapping { mapping::load(dir) } else { None };
+    let mut jars = jar_files(dir, opts.include_hidden);
+    if let Some(last) = opts.changed_only.then(|| lastscan::load(dir)).flatten() {
+        jars.retain(|f| last.changed(dir, &f.to_string_lossy()));
+    }
+    timing.enumerate = started.elapsed();
+
+    let scanned = pool::map(&jars, opts.jobs, |(r, _): &(ScanResult, _)| r.parse_error.is_some() && opts.fail_fast, |file_name| {
+        let jar_name = file_name.to_string_lossy().into_owned();
+        let listed = instance.as_ref().and_then(|m| m.get(&jar_name)).cloned()
+            .or_else(|| mapping.as_ref().and_then(|m| m.lookup(dir, &jar_name)));
+        let mut t = timing::JarTiming::default();
+        let r = scan_jar(dir, file_name.clone(), module, opts, listed, &mut t);
+        on_result(&r);
+        (r, t)
+    });
+    let mut results = Vec::with_capacity(scanned.len());
+    for (r, t) in scanned {
+        timing.add(&r.jar_name, t);
+        results.push(r);
     }
 
     let summary = ScanSummary {
         total:        results.len(),
         full:         results.iter().filter(|r| r.match_quality == MatchQuality::Full).count(),
+        unclassified: results.iter().filter(|r| r.match_quality == MatchQuality::Unclassified).count(),
+        id_only:      results.iter().filter(|r| r.match_quality == MatchQuality::IdOnly).count(),
         partial:      results.iter().filter(|r| r.match_quality == MatchQuality::Partial).count(),
         unidentified: results.iter().filter(|r| r.match_quality == MatchQuality::Unidentified).count(),
         unknown:      results.iter().filter(|r| r.match_quality == MatchQuality::Unknown).count(),
+        unknown_loader: results.iter()
+            .filter(|r| r.jar_info.as_ref().is_some_and(|i| i.loader == ModLoader::Unknown))
+            .count(),
+        invalid:      results.iter().filter(|r| r.match_quality == MatchQuality::Invalid).count(),
+        version_mismatch: results.iter().filter(|r| r.mismatch.version).count(),
+        loader_mismatch:  results.iter().filter(|r| r.mismatch.loader).count(),
     };
-    (results, summary)
+    timing.wall = started.elapsed();
+    (results, summary, timing)
+}
+
+/// Identify and match one jar.  `listed` is its identification from an
+/// instance manifest or saved mapping, in which case the jar is only opened
+/// for a version the listing lacks.  Time spent in each phase goes into `t`.
+fn scan_jar(
+    dir: &str, file_name: OsString, module: &Module, opts: &ScanOptions, listed: Option<JarInfo>,
+    t: &mut timing::JarTiming,
+) -> ScanResult {
+    let path = Path::new(dir).join(&file_name);
+    let jar_name = file_name.to_string_lossy().into_owned();
+    let started = Instant::now();
+
+    let from_manifest = listed.is_some();
+    // Checked up front so a corrupt file is never reasoned about by the
+    // bytecode or signature passes, nor swept up by a side filter
+    if !from_manifest {
+        if let Err(e) = fs::File::open(&path).map_err(|e| e.to_string())
+            .and_then(|f| zip::ZipArchive::new(f).map(drop).map_err(|e| e.to_string()))
+        {
+            t.parse = started.elapsed();
+            return ScanResult {
+                jar_name, file_name, jar_info: None,
+                parse_error:         Some(format!("not a valid jar/zip: {e}")),
+                module_entry:        None,
+                match_quality:       MatchQuality::Invalid,
+                bytecode_side:       None,
+                bytecode_confidence: bytecode::BytecodeEvidence::unknown().confidence,
+                bytecode_signal:     None,
+                signature:           None,
+                mismatch:            Mismatch::default(),
+            };
+        }
+    }
+    let (jar_info, parse_error) = match listed {
+        Some(mut i) => {
+            // An instance manifest names the mod but not its version; the
+            // jar's own metadata has that
+            if i.version.is_none() {
+                if let Ok(Some(own)) = parse_jar(&path) {
+                    i.version = own.version;
+                    i.version_source = own.version_source;
+                }
+            }
+            (Some(i), None)
+        }
+        None => match parse_jar(&path) {
+            Ok(i)  => (i, None),
+            Err(e) => (None, Some(e.to_string())),
+        },
+    };
+    t.parse = started.elapsed();
+
+    // Bytecode analysis — runs regardless of whether a module is loaded
+    let started = Instant::now();
+    let bc = if from_manifest {
+        bytecode::BytecodeEvidence::unknown()
+    } else {
+        bytecode::analyse_jar(&path, opts.layout_hint).unwrap_or_else(bytecode::BytecodeEvidence::unknown)
+    };
+    let signature = if from_manifest { None } else { signing::check_jar(&path) };
+    t.analyse = started.elapsed();
+
+    let started = Instant::now();
+
+    let mut mismatch = Mismatch::default();
+    let (module_entry, match_quality) = if let Some(info) = &jar_info {
+        if let Some(entry) = module.mods.get(&info.mod_id).cloned() {
+            let version_ok = entry.mod_version == "*"
+                || info.version.as_deref().is_some_and(|v| {
+                    version::versions_match(v, &entry.mod_version, opts.version_tolerance)
+                        || entry.version_range.as_deref().is_some_and(|r| version::in_range(v, r).unwrap_or(false))
+                });
+            let loader_ok  = info.loader == entry.mod_type;
+            // Nothing to compare is not a mismatch, but it isn't a full match either
+            let unversioned = !version_ok && info.version.as_deref().is_none_or(is_placeholder);
+            mismatch = Mismatch { version: !version_ok && !unversioned, loader: !loader_ok };
+            // An entry still tagged Unknown needs classifying first, whatever
+            // its jar's version says
+            let q = if opts.strict && entry.mod_tag == Side::Unknown {
+                MatchQuality::Unclassified
+            } else if unversioned && loader_ok {
+                MatchQuality::IdOnly
+            } else if !(version_ok && loader_ok) {
+                MatchQuality::Partial
+            } else {
+                MatchQuality::Full
+            };
+            (Some(entry), q)
+        } else {
+            (None, MatchQuality::Unidentified)
+        }
+    } else {
+        (None, MatchQuality::Unknown)
+    };
+    t.matching = started.elapsed();
+
+    ScanResult {
+        jar_name, file_name, jar_info, parse_error, module_entry, match_quality,
+        bytecode_side:       (!from_manifest).then_some(bc.side),
+        bytecode_confidence: bc.confidence,
+        bytecode_signal:     bc.signal,
+        signature,
+        mismatch,
+    }
+}
+
+// ── Warnings ──────────────────────────────────────────────────────────────────
+
+/// What a [`Warning`] is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WarningKind {
+    EmptyModule,
+    LoaderMismatch,
+    Mapping,
+    PackFormat,
+    Signature,
+    SideDisagreement,
+    Unreadable,
+}
+
+impl std::fmt::Display for WarningKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            WarningKind::EmptyModule      => "empty module",
+            WarningKind::LoaderMismatch   => "wrong module?",
+            WarningKind::Mapping          => "mapping",
+            WarningKind::PackFormat       => "pack format",
+            WarningKind::Signature        => "signature inconsistent",
+            WarningKind::SideDisagreement => "side disagreement",
+            WarningKind::Unreadable       => "could not read",
+        })
+    }
+}
+
+/// Something a scan found worth a look that didn't make it fail: the scan's
+/// counts are still right, but a misdetection or a skipped file may explain
+/// them.  Hard errors stay errors and never end up here.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Warning {
+    pub kind:    WarningKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jar:     Option<String>,
+    pub message: String,
+}
+
+impl Warning {
+    fn new(kind: WarningKind, message: impl Into<String>) -> Self {
+        Self { kind, jar: None, message: message.into() }
+    }
+
+    fn for_jar(kind: WarningKind, r: &ScanResult, message: impl Into<String>) -> Self {
+        Self { kind, jar: Some(r.jar_name.clone()), message: message.into() }
+    }
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.jar {
+            Some(jar) => write!(f, "{jar}: {}", self.message),
+            None      => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Every warning about a finished scan: the module-wide ones first, then
+/// each jar's in folder order.
+fn scan_warnings(results: &[ScanResult], module: &Module) -> Vec<Warning> {
+    let mut out = Vec::new();
+    if module.mods.is_empty() {
+        out.push(Warning::new(WarningKind::EmptyModule, EMPTY_MODULE_HINT));
+    }
+    if let Some(w) = loader_mismatch_warning(results, module) {
+        out.push(Warning::new(WarningKind::LoaderMismatch, w));
+    }
+    out.extend(signature_warnings(results));
+    out.extend(declared_side_warnings(results));
+    out.extend(results.iter().filter_map(|r| Some(Warning::for_jar(WarningKind::Unreadable, r, r.parse_error.as_ref()?))));
+    out
+}
+
+/// Every jar whose signature doesn't hold together, and why.
+fn signature_warnings(results: &[ScanResult]) -> Vec<Warning> {
+    results.iter()
+        .filter_map(|r| match &r.signature {
+            Some(signing::SignatureStatus::Inconsistent(why)) => Some(Warning::for_jar(WarningKind::Signature, r, why)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Jars whose own metadata names a single side the module disagrees with.
+/// A module tagging a `Both` jar as client-only is normal and not reported.
+fn declared_side_warnings(results: &[ScanResult]) -> Vec<Warning> {
+    results.iter()
+        .filter_map(|r| {
+            let tag = r.module_entry.as_ref()?.mod_tag;
+            let declared = r.jar_info.as_ref()?.declared_side?;
+            let one_sided = matches!(declared, Side::Client | Side::Server);
+            (one_sided && tag != Side::Unknown && tag != declared)
+                .then(|| Warning::for_jar(WarningKind::SideDisagreement, r, format!("module says {tag}, the jar declares {declared}")))
+        })
+        .collect()
+}
+
+impl ScanSummary {
+    /// One-line summary used by both the GUI log and the CLI.
+    fn describe(&self) -> String {
+        let mut s = format!("{} jars — {} full, ", self.total, self.full);
+        if self.unclassified > 0 {
+            s += &format!("{} need classification, ", self.unclassified);
+        }
+        if self.id_only > 0 {
+            s += &format!("{} ID match (no version), ", self.id_only);
+        }
+        s += &format!("{} partial, {} unidentified", self.partial, self.unidentified);
+        if self.invalid > 0 {
+            s += &format!(", {} not valid jars", self.invalid);
+        }
+        // Kept apart: the first is worth a bug report, the second a look at the folder
+        let unread = [
+            (self.unknown_loader, "with metadata but no detected loader"),
+            (self.unknown, "with no mod metadata"),
+        ].into_iter().filter(|(n, _)| *n > 0).map(|(n, what)| format!("{n} {what}")).collect::<Vec<_>>();
+        if !unread.is_empty() {
+            s += &format!("; {}", unread.join(", "));
+        }
+        s + "."
+    }
+
+    /// Why nothing matched, when nothing did — an empty folder, the wrong
+    /// module, and a version bump all look alike in the counts alone.
+    pub fn diagnosis(&self) -> Option<String> {
+        if self.total == 0 {
+            return Some("the folder has no jars".into());
+        }
+        if self.full + self.unclassified + self.id_only > 0 {
+            return None;
+        }
+        let identified = self.total - self.unknown - self.invalid;
+        let (r, v, l) = (self.partial, self.version_mismatch, self.loader_mismatch);
+        Some(if identified == 0 {
+            format!("none of the {} jar(s) had readable mod metadata", self.total)
+        } else if r == 0 {
+            format!("the module recognized 0 of {identified} identified mod(s) — is it the right module for this pack?")
+        } else if v == r && l == 0 {
+            format!("the module recognized {r} mod(s) but all versions differed — try a looser version tolerance")
+        } else if l == r && v == 0 {
+            format!("the module recognized {r} mod(s) but all were listed for a different loader")
+        } else {
+            format!("the module recognized {r} mod(s) but none matched fully ({v} version and {l} loader difference(s))")
+        })
+    }
+}
+
+/// Most common non-Unknown loader in `loaders`.
+fn dominant_loader(loaders: impl Iterator<Item = ModLoader>) -> Option<ModLoader> {
+    let mut counts: Vec<(ModLoader, usize)> = Vec::new();
+    for l in loaders.filter(|l| *l != ModLoader::Unknown) {
+        match counts.iter_mut().find(|(k, _)| *k == l) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((l, 1)),
+        }
+    }
+    counts.into_iter()
+        .max_by_key(|(_, n)| *n)
+        .map(|(l, _)| l)
+}
+
+/// Warn when the module is clearly aimed at a different loader than the folder,
+/// e.g. a Fabric module pointed at a Forge pack — nearly nothing would match.
+fn loader_mismatch_warning(results: &[ScanResult], module: &Module) -> Option<String> {
+    let folder_loaders = || results.iter().filter_map(|r| r.jar_info.as_ref()).map(|i| i.loader);
+    let folder_dom = dominant_loader(folder_loaders())?;
+    let module_dom = dominant_loader(module.mods.values().map(|e| e.mod_type))?;
+    if folder_dom == module_dom { return None; }
+
+    // Only a clear mismatch: the module's loader must be a small minority in the folder
+    let detected = folder_loaders().filter(|l| *l != ModLoader::Unknown).count();
+    let module_share = folder_loaders().filter(|l| *l == module_dom).count() as f64 / detected as f64;
+    if module_share >= 0.25 { return None; }
+
+    Some(format!(
+        "Module is mostly {module_dom} but your folder is mostly {folder_dom} — wrong module?"
+    ))
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -378,76 +1822,696 @@ fn scan_directory(dir: &str, module: &Module) -> (Vec<ScanResult>, ScanSummary)
 // ─────────────────────────────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Operation { Zip, Move, Delete, Export }
+pub enum Operation {
+    Zip, Move, Copy, Delete, Export,
+    /// Every scanned jar in one file, sectioned by side or as a Markdown
+    /// table — ignores the target side
+    Report,
+    /// Move into the folder's `.lodestone_quarantine/`, out of the game's
+    /// reach but one command from coming back (see `quarantine.rs`)
+    Quarantine,
+}
 
 impl std::fmt::Display for Operation {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(match self {
             Operation::Zip    => "Zip",
             Operation::Move   => "Move",
+            Operation::Copy   => "Copy",
             Operation::Delete => "Delete",
             Operation::Export => "Export list",
+            Operation::Report => "Export report",
+            Operation::Quarantine => "Quarantine",
         })
     }
 }
 
+impl std::str::FromStr for Operation {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "zip"    => Ok(Operation::Zip),
+            "move"   => Ok(Operation::Move),
+            "copy"   => Ok(Operation::Copy),
+            "delete" => Ok(Operation::Delete),
+            "export" => Ok(Operation::Export),
+            "report" => Ok(Operation::Report),
+            "quarantine" => Ok(Operation::Quarantine),
+            _ => Err(format!("unknown operation '{s}' (expected zip, move, copy, delete, export, report or quarantine)")),
+        }
+    }
+}
+
+/// How much an operation changes the scanned folder.  Decides whether the CLI
+/// asks before running it and whether a history run is recorded, so a new
+/// operation only has to pick its class here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Impact {
+    /// Only writes output elsewhere; the folder is untouched
+    ReadOnly,
+    /// Takes jars out of the folder but keeps them
+    Alters,
+    /// Removes jars
+    Destroys,
+}
+
+impl Operation {
+    pub fn impact(self) -> Impact {
+        match self {
+            Operation::Zip | Operation::Copy | Operation::Export | Operation::Report => Impact::ReadOnly,
+            Operation::Move | Operation::Quarantine => Impact::Alters,
+            Operation::Delete => Impact::Destroys,
+        }
+    }
+
+    /// Whether `--output` has to be given; delete and quarantine have
+    /// nowhere, or only one place, to put the jars.
+    pub fn needs_output(self) -> bool {
+        !matches!(self, Operation::Delete | Operation::Quarantine)
+    }
+}
+
+/// What has to be typed to confirm a delete.  `LODESTONE_CONFIRM` picks it:
+/// `tag` asks for the name of what is being deleted (`client`, `server`…),
+/// `random` for a word chosen afresh each time, and any other value is the
+/// phrase itself.  Unset, it is `DELETE`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfirmPhrase {
+    Literal(String),
+    Tag,
+    Random,
+}
+
+impl Default for ConfirmPhrase {
+    fn default() -> Self {
+        ConfirmPhrase::Literal("DELETE".into())
+    }
+}
+
+/// Short, unambiguous words for [`ConfirmPhrase::Random`].
+const CONFIRM_WORDS: &[&str] = &[
+    "anvil", "beacon", "cobble", "dropper", "ember", "furnace", "gravel", "hopper",
+    "lantern", "magma", "observer", "piston", "quartz", "sapling", "trident", "wither",
+];
+
+impl ConfirmPhrase {
+    pub fn from_env() -> Self {
+        std::env::var("LODESTONE_CONFIRM").ok().map(|v| Self::parse(&v)).unwrap_or_default()
+    }
+
+    fn parse(v: &str) -> Self {
+        match v.trim() {
+            ""       => Self::default(),
+            "tag"    => ConfirmPhrase::Tag,
+            "random" => ConfirmPhrase::Random,
+            p        => ConfirmPhrase::Literal(p.to_string()),
+        }
+    }
+
+    /// The phrase for deleting the jars `filter` selects.  Random phrases
+    /// differ between calls, so ask once per confirmation.
+    pub fn phrase(&self, filter: &OpFilter) -> String {
+        self.phrase_for(&filter.target_word())
+    }
+
+    /// [`ConfirmPhrase::phrase`] with the word for what is being deleted
+    /// given directly, for selections that aren't an [`OpFilter`].
+    pub fn phrase_for(&self, target: &str) -> String {
+        match self {
+            ConfirmPhrase::Literal(p) => p.clone(),
+            ConfirmPhrase::Tag        => target.to_string(),
+            ConfirmPhrase::Random     => {
+                let nanos = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or(0);
+                CONFIRM_WORDS[nanos as usize % CONFIRM_WORDS.len()].to_string()
+            }
+        }
+    }
+}
+
+/// A set of sides, for operations that act on several at once ("Client +
+/// Both").  Kept as a bitset so [`OpFilter`] stays `Copy`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Sides(u8);
+
+impl Sides {
+    /// Every side, in checklist order.
+    pub const ALL: [Side; 4] = [Side::Client, Side::Server, Side::Both, Side::Unknown];
+
+    pub fn of(sides: &[Side]) -> Self {
+        let mut set = Self::default();
+        for &s in sides { set.insert(s); }
+        set
+    }
+
+    fn bit(side: Side) -> u8 {
+        1 << side as u8
+    }
+
+    pub fn insert(&mut self, side: Side) {
+        self.0 |= Self::bit(side);
+    }
+
+    pub fn toggle(&mut self, side: Side, on: bool) {
+        if on { self.insert(side) } else { self.0 &= !Self::bit(side) }
+    }
+
+    pub fn contains(&self, side: Side) -> bool {
+        self.0 & Self::bit(side) != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// The sides in the set, in [`Sides::ALL`] order.
+    pub fn iter(&self) -> impl Iterator<Item = Side> + '_ {
+        Self::ALL.into_iter().filter(|s| self.contains(*s))
+    }
+
+    /// The one side in the set, if it holds exactly one.
+    pub fn single(&self) -> Option<Side> {
+        let mut it = self.iter();
+        match (it.next(), it.next()) {
+            (Some(s), None) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Sides {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<String> = self.iter().map(|s| s.to_string()).collect();
+        f.write_str(&names.join(" + "))
+    }
+}
+
+/// `client,both` → Client + Both.
+impl std::str::FromStr for Sides {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let sides = s.split(',').map(str::trim).filter(|t| !t.is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<Side>, _>>()?;
+        if sides.is_empty() {
+            return Err("no tag given".into());
+        }
+        Ok(Self::of(&sides))
+    }
+}
+
+/// Which jars an operation selects: one or more sides, optionally narrowed
+/// to one loader ("every Forge client mod").
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpFilter {
+    pub sides:  Sides,
+    pub loader: Option<ModLoader>,
+    /// Select every jar whose id is in the module, whatever its tag — for
+    /// pulling known mods out of a messy folder.  `sides` is ignored.
+    pub matched_only: bool,
+    /// Select only files that are not valid jars.  They are never selected
+    /// otherwise, so getting rid of them is always a deliberate choice.
+    pub invalid: bool,
+    /// Act on at most this many of the selected jars, in folder order
+    pub limit:   Option<usize>,
+}
+
+impl OpFilter {
+    pub fn side(side: Side) -> Self {
+        Self::sides(&[side])
+    }
+
+    pub fn sides(sides: &[Side]) -> Self {
+        Self { sides: Sides::of(sides), loader: None, matched_only: false, invalid: false, limit: None }
+    }
+
+    /// One lowercase word for what is selected: the side(s), `module` or
+    /// `invalid`.  Several sides are joined with `+`, e.g. `client+both`.
+    pub fn target_word(&self) -> String {
+        if self.invalid {
+            "invalid".into()
+        } else if self.matched_only {
+            "module".into()
+        } else {
+            self.sides.iter().map(|s| s.to_string().to_lowercase()).collect::<Vec<_>>().join("+")
+        }
+    }
+
+    /// `sides.contains(mod_tag) && (loader.is_none() || mod_type == loader)`, using
+    /// the module's loader when the jar is in it and the detected one otherwise.
+    fn matches(&self, r: &ScanResult) -> bool {
+        if r.match_quality == MatchQuality::Invalid || self.invalid {
+            return r.match_quality == MatchQuality::Invalid && self.invalid;
+        }
+        let selected = if self.matched_only { r.module_entry.is_some() } else { self.sides.contains(r.effective_side()) };
+        selected && self.loader.is_none_or(|l| r.loader() == l)
+    }
+}
+
+/// Loader choice in the GUI, where "any" has to be a pick-list entry too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LoaderChoice(Option<ModLoader>);
+
+impl std::fmt::Display for LoaderChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(l) => write!(f, "{l}"),
+            None    => f.write_str("Any loader"),
+        }
+    }
+}
+
+/// The scanned jars `op` acts on under `filter`.  A report covers every side
+/// but still honours the loader.  Protected mods are never taken out of the
+/// folder; see [`protected_targets`].
+fn op_targets(op: Operation, results: &[ScanResult], filter: OpFilter) -> Vec<&ScanResult> {
+    results.iter()
+        .filter(|r| if op == Operation::Report {
+            filter.loader.is_none_or(|l| r.loader() == l)
+        } else {
+            filter.matches(r)
+        })
+        .filter(|r| !(op.impact() != Impact::ReadOnly && r.is_protected()))
+        .take(filter.limit.unwrap_or(usize::MAX))
+        .collect()
+}
+
+/// The jars `op` would have taken under `filter` but leaves alone because
+/// the module protects them.  Only operations that remove jars skip any.
+fn protected_targets(op: Operation, results: &[ScanResult], filter: OpFilter) -> Vec<&ScanResult> {
+    if op.impact() == Impact::ReadOnly {
+        return Vec::new();
+    }
+    results.iter().filter(|r| r.is_protected() && filter.matches(r)).collect()
+}
+
+/// Per-jar failures from an operation that kept going past them.
+#[derive(Debug)]
+pub struct FileErrors {
+    /// Jars that were processed
+    pub done:   usize,
+    /// (jar, error) for each one that wasn't
+    pub failed: Vec<(String, String)>,
+}
+
+impl std::fmt::Display for FileErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} file(s) done, {} failed", self.done, self.failed.len())?;
+        for (jar, e) in &self.failed {
+            write!(f, "\n  {jar}: {e}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for FileErrors {}
+
+/// Run `act` on every target jar still in `dir`.  With `fail_fast` the first
+/// failure is returned as is; otherwise failures are collected into
+/// [`FileErrors`] and the rest of the jars are still processed.
+fn each_jar(
+    dir: &str, targets: &[&ScanResult], fail_fast: bool,
+    mut act: impl FnMut(&ScanResult, &Path) -> Result<(), Box<dyn std::error::Error>>,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut errors = FileErrors { done: 0, failed: Vec::new() };
+    for r in targets {
+        let src = Path::new(dir).join(&r.file_name);
+        if !src.is_file() { continue; }
+        match act(r, &src) {
+            Ok(())               => errors.done += 1,
+            Err(e) if fail_fast  => return Err(format!("{}: {e}", r.jar_name).into()),
+            Err(e)               => errors.failed.push((r.jar_name.clone(), e.to_string())),
+        }
+    }
+    if errors.failed.is_empty() { Ok(errors.done) } else { Err(Box::new(errors)) }
+}
+
+/// The scanned jars named in `jars` that `op` acts on, in the order given,
+/// for acting on an explicit list instead of a filter.  Every name has to be
+/// in `results`; protected mods are left out as in [`op_targets`].
+pub fn named_targets<'a>(op: Operation, results: &'a [ScanResult], jars: &[String]) -> Result<Vec<&'a ScanResult>, String> {
+    let mut targets = Vec::with_capacity(jars.len());
+    let mut absent = Vec::new();
+    for name in jars {
+        match results.iter().find(|r| r.jar_name == *name) {
+            Some(r) => targets.push(r),
+            None    => absent.push(name.as_str()),
+        }
+    }
+    if !absent.is_empty() {
+        return Err(format!("not in the folder: {}", absent.join(", ")));
+    }
+    targets.retain(|r| !(op.impact() != Impact::ReadOnly && r.is_protected()));
+    Ok(targets)
+}
+
 fn run_operation(
     op: Operation, dir: &str,
-    results: &[ScanResult], filter_side: Side, output: &str,
+    results: &[ScanResult], filter: OpFilter, output: &str, fail_fast: bool,
 ) -> Result<usize, Box<dyn std::error::Error>> {
-    let targets: Vec<&ScanResult> = results.iter()
-        .filter(|r| r.effective_side() == filter_side).collect();
+    apply_operation(op, dir, &op_targets(op, results, filter), output, fail_fast)
+}
+
+/// Where `p` really is, following symlinks and `..`, for paths that may not
+/// exist yet: the parent is resolved and the last component kept.
+fn resolved(p: &Path) -> PathBuf {
+    if let Ok(c) = p.canonicalize() {
+        return c;
+    }
+    let parent = p.parent().filter(|q| !q.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    match (parent.canonicalize(), p.file_name()) {
+        (Ok(c), Some(name)) => c.join(name),
+        _ => p.to_path_buf(),
+    }
+}
+
+/// Refuse outputs that would feed back into the scanned folder: moving or
+/// copying jars into the folder they are in moves them onto themselves, and
+/// a file named `*.jar` written there is picked up by the next scan as a
+/// mod.  Other files beside the jars are never listed, and subfolders are
+/// never descended into, so both are fine.
+fn check_output(op: Operation, dir: &str, output: &str) -> Result<(), String> {
+    let folder = resolved(Path::new(dir));
+    let out = resolved(Path::new(output));
+    let feeds_back = match op {
+        Operation::Delete | Operation::Quarantine => false,
+        Operation::Move | Operation::Copy => out == folder,
+        Operation::Zip | Operation::Export | Operation::Report => {
+            out.parent() == Some(folder.as_path())
+                && out.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("jar"))
+        }
+    };
+    if feeds_back {
+        return Err(format!("{op}: refusing to write into {dir}, the folder being scanned — choose an output outside it or in a subfolder"));
+    }
+    Ok(())
+}
 
+fn apply_operation(
+    op: Operation, dir: &str, targets: &[&ScanResult], output: &str, fail_fast: bool,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    check_output(op, dir, output)?;
     match op {
         Operation::Zip => {
             use zip::write::FileOptions;
-            let mut w = zip::ZipWriter::new(fs::File::create(output)?);
+            let file = fs::File::create(output).map_err(|e| io_context(e, &format!("writing {output}")))?;
+            let mut w = zip::ZipWriter::new(file);
             let opts = FileOptions::default();
-            let mut n = 0usize;
-            for r in &targets {
-                let src = Path::new(dir).join(&r.jar_name);
-                if src.is_file() {
-                    let mut buf = Vec::new();
-                    fs::File::open(&src)?.read_to_end(&mut buf)?;
-                    w.start_file(&r.jar_name, opts)?;
-                    w.write_all(&buf)?;
-                    n += 1;
+            let (mut entries, mut raw) = (0, BTreeMap::new());
+            let n = each_jar(dir, targets, fail_fast, |r, src| {
+                let mut buf = Vec::new();
+                fs::File::open(src).and_then(|mut f| f.read_to_end(&mut buf))
+                    .map_err(|e| io_context(e, &format!("reading {}", src.display())))?;
+                w.start_file(&r.jar_name, opts)?;
+                w.write_all(&buf)?;
+                if let Some(name) = history::raw_name(&r.file_name) {
+                    raw.insert(entries, name);
                 }
+                entries += 1;
+                Ok(())
+            });
+            if !raw.is_empty() {
+                w.start_file(history::ZIP_NAMES, opts)?;
+                w.write_all(serde_json::to_string(&raw)?.as_bytes())?;
             }
+            // Close the archive even when some jars were left out of it
             w.finish()?;
-            Ok(n)
-        }
-        Operation::Move => {
-            fs::create_dir_all(output)?;
-            let mut n = 0usize;
-            for r in &targets {
-                let src = Path::new(dir).join(&r.jar_name);
-                let dst = Path::new(output).join(&r.jar_name);
-                if src.is_file() {
-                    if fs::rename(&src, &dst).is_err() {
-                        fs::copy(&src, &dst)?;
-                        fs::remove_file(&src)?;
-                    }
-                    n += 1;
-                }
-            }
-            Ok(n)
+            n
         }
-        Operation::Delete => {
-            let mut n = 0usize;
-            for r in &targets {
-                let p = Path::new(dir).join(&r.jar_name);
-                if p.is_file() { fs::remove_file(p)?; n += 1; }
-            }
-            Ok(n)
+        Operation::Move => move_into(dir, targets, output, false, fail_fast).map(|(n, _)| n),
+        Operation::Copy => {
+            create_output_dir(output)?;
+            each_jar(dir, targets, fail_fast, |r, src| {
+                let dst = Path::new(output).join(&r.file_name);
+                fs::copy(src, &dst).map_err(|e| io_context(e, &format!("writing {}", dst.display())))?;
+                Ok(())
+            })
         }
+        Operation::Delete => each_jar(dir, targets, fail_fast, |_, src| {
+            fs::remove_file(src).map_err(|e| io_context(e, &format!("removing {}", src.display())))
+        }),
+        Operation::Quarantine => quarantine_into(dir, targets, fail_fast).map(|(n, _)| n),
+        // Lists are built in full and written in one go, so a failed write
+        // never leaves a shorter list that looks complete
         Operation::Export => {
-            let mut f = fs::File::create(output)?;
-            let mut n = 0usize;
-            for r in &targets { writeln!(f, "{}", r.jar_name)?; n += 1; }
-            Ok(n)
+            let names: String = targets.iter().map(|r| format!("{}\n", r.jar_name)).collect();
+            write_atomic(Path::new(output), names.as_bytes()).map_err(|e| io_context(e, &format!("writing {output}")))?;
+            Ok(targets.len())
+        }
+        Operation::Report => {
+            let report = report::render(targets, report::ReportFormat::for_output(output));
+            write_atomic(Path::new(output), report.as_bytes()).map_err(|e| io_context(e, &format!("writing {output}")))?;
+            Ok(targets.len())
+        }
+    }
+}
+
+/// Move `targets` into the folder `output`.  A jar whose name is taken there
+/// overwrites it, unless `rename`, when it gets the first free `name-N.jar`
+/// instead.  Returns the count and, by jar, each new name given.
+fn move_into(
+    dir: &str, targets: &[&ScanResult], output: &str, rename: bool, fail_fast: bool,
+) -> Result<(usize, BTreeMap<String, PathBuf>), Box<dyn std::error::Error>> {
+    create_output_dir(output)?;
+    let mut renamed = BTreeMap::new();
+    let n = each_jar(dir, targets, fail_fast, |r, src| {
+        let mut dst = Path::new(output).join(&r.file_name);
+        if rename && dst.exists() {
+            dst = free_name(Path::new(output), &r.file_name);
+            renamed.insert(r.jar_name.clone(), dst.clone());
+        }
+        move_file(src, &dst).map_err(|e| io_context(e, &format!("moving {} to {}", src.display(), dst.display())))
+    })?;
+    Ok((n, renamed))
+}
+
+/// Move `targets` into the folder's quarantine and list them in its manifest.
+/// A jar whose name is already held there gets the first free `name-N.jar`,
+/// so nothing quarantined earlier is lost.  Returns the count and, by jar,
+/// each new name given.
+fn quarantine_into(
+    dir: &str, targets: &[&ScanResult], fail_fast: bool,
+) -> Result<(usize, BTreeMap<String, PathBuf>), Box<dyn std::error::Error>> {
+    let q = quarantine::quarantine_dir(dir);
+    create_output_dir(&q.to_string_lossy())?;
+    let (mut moved, mut renamed) = (Vec::new(), BTreeMap::new());
+    let n = each_jar(dir, targets, fail_fast, |r, src| {
+        let mut dst = q.join(&r.file_name);
+        if dst.exists() {
+            dst = free_name(&q, &r.file_name);
+            renamed.insert(r.jar_name.clone(), dst.clone());
+        }
+        move_file(src, &dst).map_err(|e| io_context(e, &format!("moving {} to {}", src.display(), dst.display())))?;
+        moved.push((r.file_name.clone(), dst.file_name().unwrap_or_default().to_os_string()));
+        Ok(())
+    });
+    // Whatever moved is listed, even if a later jar failed
+    let moved: Vec<(&ScanResult, &OsStr)> = moved.iter()
+        .filter_map(|(file, held)| targets.iter().find(|r| r.file_name == *file).map(|r| (*r, held.as_os_str())))
+        .collect();
+    quarantine::record(dir, &moved).map_err(|e| io_context(e, &format!("writing {}", q.display())))?;
+    Ok((n?, renamed))
+}
+
+/// `folder/<stem>-N.<ext>` for the lowest N not already taken.
+fn free_name(folder: &Path, file_name: &OsStr) -> PathBuf {
+    let name = Path::new(file_name);
+    let stem = name.file_stem().unwrap_or(file_name);
+    (1..)
+        .map(|n| {
+            let mut candidate = stem.to_os_string();
+            candidate.push(format!("-{n}"));
+            if let Some(ext) = name.extension() {
+                candidate.push(".");
+                candidate.push(ext);
+            }
+            folder.join(candidate)
+        })
+        .find(|p| !p.exists())
+        .expect("an unbounded range always yields a free name")
+}
+
+/// How a recorded operation carries itself out, beyond which jars it takes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OpOptions<'a> {
+    /// Destination file or folder; unused by Delete and Quarantine
+    pub output: &'a str,
+    /// Delete moves the jars into the run's backup folder instead of
+    /// removing them, so `lodestone undo` can bring them back
+    pub backup: bool,
+    /// Move never overwrites; see [`move_into`]
+    pub rename: bool,
+    /// Stop at the first jar that fails instead of carrying on
+    pub fail_fast: bool,
+}
+
+/// [`run_operation`], recording a history manifest when it changes the
+/// folder; see [`OpOptions`].  Returns the count and the id of the recorded
+/// run, if any.
+fn run_recorded(
+    op: Operation, dir: &str, results: &[ScanResult], filter: OpFilter, module_path: &str, opts: OpOptions,
+) -> Result<(usize, Option<String>), Box<dyn std::error::Error>> {
+    let targets = op_targets(op, results, filter);
+    if op.impact() == Impact::ReadOnly {
+        return apply_operation(op, dir, &targets, opts.output, opts.fail_fast).map(|n| (n, None));
+    }
+    let by_side = !filter.matched_only && !filter.invalid;
+    let tag = by_side.then(|| filter.sides.single()).flatten();
+    let mut run = history::Run::begin(&op.to_string(), Some(module_path), tag, dir, &present_names(dir, &targets));
+    run.loader = filter.loader;
+    if by_side && tag.is_none() {
+        run.tags = filter.sides.iter().collect();
+    }
+    record_operation(op, dir, &targets, run, opts)
+}
+
+/// [`run_recorded`] for the jars named in `jars` rather than a filter, the
+/// module (if `results` were scanned against one) only saying which are
+/// protected.  Fails before touching anything if a name isn't in `results`.
+pub fn run_listed(
+    op: Operation, dir: &str, results: &[ScanResult], jars: &[String], opts: OpOptions,
+) -> Result<(usize, Option<String>), Box<dyn std::error::Error>> {
+    let targets = named_targets(op, results, jars)?;
+    if op.impact() == Impact::ReadOnly {
+        return apply_operation(op, dir, &targets, opts.output, opts.fail_fast).map(|n| (n, None));
+    }
+    let run = history::Run::begin(&op.to_string(), None, None, dir, &present_names(dir, &targets));
+    record_operation(op, dir, &targets, run, opts)
+}
+
+/// File names of the targets still in `dir`.
+fn present_names<'a>(dir: &str, targets: &[&'a ScanResult]) -> Vec<&'a OsStr> {
+    targets.iter()
+        .filter(|r| Path::new(dir).join(&r.file_name).is_file())
+        .map(|r| r.file_name.as_os_str())
+        .collect()
+}
+
+/// Carry out a folder-changing `op` on `targets` and save `run` with
+/// whatever actually left the folder.
+fn record_operation(
+    op: Operation, dir: &str, targets: &[&ScanResult], mut run: history::Run, opts: OpOptions,
+) -> Result<(usize, Option<String>), Box<dyn std::error::Error>> {
+    let OpOptions { output, backup, rename, fail_fast } = opts;
+    let dest = match op {
+        Operation::Move => Some(PathBuf::from(output)),
+        Operation::Quarantine => Some(quarantine::quarantine_dir(dir)),
+        _ if backup     => Some(history::backup_dir(dir, &run.id)),
+        _               => None,
+    };
+    let mut renamed = BTreeMap::new();
+    let outcome = match &dest {
+        Some(_) if op == Operation::Quarantine => {
+            quarantine_into(dir, targets, fail_fast).map(|(n, r)| { renamed = r; n })
+        }
+        Some(d) if op == Operation::Move && rename => {
+            check_output(op, dir, output)?;
+            move_into(dir, targets, &d.to_string_lossy(), true, fail_fast).map(|(n, r)| { renamed = r; n })
         }
+        Some(d) => apply_operation(Operation::Move, dir, targets, &d.to_string_lossy(), fail_fast),
+        None    => apply_operation(Operation::Delete, dir, targets, output, fail_fast),
+    };
+    // Record whatever left the folder, even if the operation stopped part-way
+    run.settle(dir, |name, file| renamed.get(name).cloned().or_else(|| dest.as_ref().map(|d| d.join(file))));
+    if !run.jars.is_empty() {
+        run.save(dir).map_err(|e| io_context(e, &format!("writing {}", history::history_dir(dir).display())))?;
+    }
+    let n = outcome?;
+    Ok((n, (!run.jars.is_empty()).then_some(run.id)))
+}
+
+/// Re-open a zip written by [`run_operation`] and read every expected entry
+/// back in full, which checks its CRC.  Catches archives left truncated or
+/// corrupt by e.g. the disk filling up mid-write.
+fn verify_zip(path: &str, expected: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(path)?)
+        .map_err(|e| format!("{path} cannot be re-opened: {e}"))?;
+    for name in expected {
+        let mut entry = archive.by_name(name)
+            .map_err(|e| format!("{path}: entry {name} is missing: {e}"))?;
+        std::io::copy(&mut entry, &mut std::io::sink())
+            .map_err(|e| format!("{path}: entry {name} does not read back: {e}"))?;
+    }
+    Ok(())
+}
+
+/// Byte-order mark that some Windows editors need to read a file as UTF-8.
+const UTF8_BOM: &str = "\u{feff}";
+
+/// Start the text file at `path` with [`UTF8_BOM`], unless it already does.
+/// The file is replaced in one go, like every other text output.
+fn add_bom(path: &str) -> std::io::Result<()> {
+    let text = fs::read_to_string(path)?;
+    if text.starts_with(UTF8_BOM) {
+        return Ok(());
+    }
+    write_atomic(Path::new(path), format!("{UTF8_BOM}{text}").as_bytes())
+}
+
+/// Write `contents` to `path` in one step: into a sibling `.tmp` file that is
+/// renamed over `path` only once complete.  On failure `path` is untouched
+/// and the temporary file removed.
+fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    let written = fs::write(&tmp, contents).and_then(|()| fs::rename(&tmp, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    written
+}
+
+/// `fs::create_dir_all` for an operation's destination.
+fn create_output_dir(dir: &str) -> Result<(), Box<dyn std::error::Error>> {
+    fs::create_dir_all(dir).map_err(|e| io_context(e, &format!("creating {dir}")))
+}
+
+/// Permission errors reworded into what to do about them, with `action`
+/// ("writing out.zip") saying what was refused; any other error passes
+/// through as is.  Locked-down servers otherwise just say "os error 13".
+pub fn io_context(e: std::io::Error, action: &str) -> Box<dyn std::error::Error> {
+    if e.kind() != std::io::ErrorKind::PermissionDenied {
+        return e.into();
     }
+    let fix = if action.starts_with("reading") {
+        "run with appropriate privileges"
+    } else {
+        "run with appropriate privileges or choose a writable output dir"
+    };
+    format!("permission denied {action} — {fix}").into()
+}
+
+/// Rename, falling back to copy + remove when crossing filesystems.
+fn move_file(src: &Path, dst: &Path) -> std::io::Result<()> {
+    if fs::rename(src, dst).is_err() {
+        fs::copy(src, dst)?;
+        fs::remove_file(src)?;
+    }
+    Ok(())
+}
+
+/// Move every scanned jar into the destination for its side in one pass, e.g.
+/// splitting a combined pack into `client/` and `server/`.  Sides without a
+/// destination are left in place.  Returns how many jars went to each side.
+/// Failures are handled as in [`run_operation`].
+fn sort_by_side(
+    dir: &str, results: &[ScanResult], dests: &BTreeMap<Side, String>, fail_fast: bool,
+) -> Result<BTreeMap<Side, usize>, Box<dyn std::error::Error>> {
+    for d in dests.values() {
+        check_output(Operation::Move, dir, d)?;
+        create_output_dir(d)?;
+    }
+    let mut counts = BTreeMap::new();
+    let targets: Vec<&ScanResult> = results.iter().filter(|r| dests.contains_key(&r.effective_side())).collect();
+    each_jar(dir, &targets, fail_fast, |r, src| {
+        let side = r.effective_side();
+        let dst = Path::new(&dests[&side]).join(&r.file_name);
+        move_file(src, &dst).map_err(|e| io_context(e, &format!("moving {} to {}", src.display(), dst.display())))?;
+        *counts.entry(side).or_insert(0) += 1;
+        Ok(())
+    })?;
+    Ok(counts)
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -467,7 +2531,8 @@ fn discover_modules() -> Vec<String> {
         if let Ok(rd) = fs::read_dir(dir) {
             for e in rd.filter_map(Result::ok) {
                 let p = e.path();
-                if p.extension().and_then(|s| s.to_str()) == Some("json") {
+                let name = p.file_name().and_then(|s| s.to_str()).unwrap_or_default().to_ascii_lowercase();
+                if name.ends_with(".json") || name.ends_with(".json.gz") {
                     found.push(p.display().to_string());
                 }
             }
@@ -491,41 +2556,75 @@ struct App {
     directory:       String,
     scan_results:    Vec<ScanResult>,
     summary:         ScanSummary,
-    op_side:         Side,
+    op_sides:        Sides,
+    op_loader:       Option<ModLoader>,
+    op_matched_only: bool,
+    op_invalid: bool,
     op:              Operation,
     op_output:       String,
     op_confirm:      String,
+    /// How deletes are confirmed, and the phrase the current one wants
+    confirm:         ConfirmPhrase,
+    op_phrase:       String,
+    op_verify_zip:   bool,
+    /// Delete into the run's history folder rather than removing outright
+    op_backup:       bool,
+    /// Move under a numbered name instead of overwriting a jar at the output
+    op_rename:       bool,
     active_panel:    Panel,
     filter_side:     Option<Side>,
+    scan_options:    ScanOptions,
+    /// Directory of the most recent scan, even if it turned up no jars
+    scanned_dir:     Option<String>,
     log:             Vec<(String, LogLevel)>,
 }
 
 #[derive(Debug, Clone, Copy)]
 enum LogLevel { Info, Ok, Warn, Err }
 
+const EMPTY_MODULE_HINT: &str =
+    "Module has no entries — every jar will be unidentified. Tag mods with `lodestone inspect --tag` first.";
+
 impl Default for App {
     fn default() -> Self {
         let modules = discover_modules();
         let sel = modules.first().cloned();
-        Self {
+        let mut app = Self {
             modules, selected_module: sel, loaded_module: None,
             directory: String::new(),
             scan_results: Vec::new(), summary: ScanSummary::default(),
-            op_side: Side::Client, op: Operation::Zip,
+            op_sides: Sides::of(&[Side::Client]), op_loader: None, op_matched_only: false, op_invalid: false, op: Operation::Zip,
             op_output: String::new(), op_confirm: String::new(),
-            active_panel: Panel::Scan, filter_side: None,
+            confirm: ConfirmPhrase::from_env(), op_phrase: String::new(), op_verify_zip: true, op_backup: true, op_rename: false,
+            active_panel: Panel::Scan, filter_side: None, scanned_dir: None,
+            scan_options: ScanOptions::default(),
             log: vec![("Lodestone ready.".into(), LogLevel::Info)],
-        }
+        };
+        app.refresh_phrase();
+        app
     }
 }
 
-impl App {
+impl App {
+    /// Pick the phrase the delete card asks for, for the current selection.
+    fn refresh_phrase(&mut self) {
+        self.op_phrase = self.confirm.phrase(&self.op_filter());
+        self.op_confirm.clear();
+    }
+
     fn push_log(&mut self, msg: impl Into<String>, level: LogLevel) {
         let s = msg.into();
         if self.log.last().map(|(t, _)| t == &s).unwrap_or(false) { return; }
         self.log.push((s, level));
         if self.log.len() > 200 { self.log.drain(0..self.log.len() - 200); }
     }
+
+    fn op_filter(&self) -> OpFilter {
+        OpFilter {
+            sides: self.op_sides, loader: self.op_loader,
+            matched_only: self.op_matched_only, invalid: self.op_invalid, limit: None,
+        }
+    }
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -542,11 +2641,20 @@ enum Msg {
     BrowseDir,
     DirPicked(Option<PathBuf>),
     ScanDir,
+    StrictToggled(bool),
+    LayoutHintToggled(bool),
+    ToleranceSelected(version::VersionTolerance),
     FilterSide(Option<Side>),
-    OpSideSelected(Side),
+    OpSideToggled(Side, bool),
+    OpLoaderSelected(LoaderChoice),
+    OpMatchedOnlyToggled(bool),
+    OpInvalidToggled(bool),
     OpSelected(Operation),
     OpOutputChanged(String),
     OpConfirmChanged(String),
+    OpVerifyZipToggled(bool),
+    OpBackupToggled(bool),
+    OpRenameToggled(bool),
     RunOp,
 }
 
@@ -573,10 +2681,26 @@ fn update(app: &mut App, msg: Msg) -> Task<Msg> {
             Some(path) => match Module::load(path) {
                 Ok(m) => {
                     let msg = format!("'{}' — {} entries.", m.name, m.mods.len());
+                    let empty = m.mods.is_empty();
+                    let migrated = m.migrated_from;
+                    let too_new = m.too_new();
                     app.scan_results.clear();
                     app.summary = ScanSummary::default();
+                    app.scanned_dir = None;
                     app.loaded_module = Some(m);
                     app.push_log(msg, LogLevel::Ok);
+                    if let Some(why) = too_new {
+                        app.push_log(why, LogLevel::Warn);
+                    }
+                    if let Some(from) = migrated {
+                        app.push_log(
+                            format!("Module uses format {from}; it was upgraded to {MODULE_FORMAT} in memory."),
+                            LogLevel::Info,
+                        );
+                    }
+                    if empty {
+                        app.push_log(EMPTY_MODULE_HINT, LogLevel::Warn);
+                    }
                 }
                 Err(e) => app.push_log(format!("Load failed: {e}"), LogLevel::Err),
             },
@@ -603,22 +2727,58 @@ fn update(app: &mut App, msg: Msg) -> Task<Msg> {
                 app.push_log("Choose a mods directory first.", LogLevel::Warn);
                 return Task::none();
             }
-            let (results, summary) = scan_directory(&dir, module);
-            let msg = format!(
-                "{} jars — {} full, {} partial, {} unidentified.",
-                summary.total, summary.full, summary.partial, summary.unidentified
-            );
+            let (results, summary) = scan_directory(&dir, module, &app.scan_options);
+            app.scanned_dir = Some(dir.clone());
+            if results.is_empty() {
+                app.scan_results.clear();
+                app.summary = summary;
+                app.push_log(format!("No jars found in {dir}."), LogLevel::Warn);
+                return Task::none();
+            }
+            let msg = summary.describe();
+            let diagnosis = summary.diagnosis();
+            let mismatch = loader_mismatch_warning(&results, module);
+            let bad_sigs: Vec<String> = signature_warnings(&results).iter().map(Warning::to_string).collect();
+            let disagreements: Vec<String> = declared_side_warnings(&results).iter().map(Warning::to_string).collect();
             app.scan_results = results;
             app.summary = summary;
             app.push_log(msg, LogLevel::Ok);
+            if let Some(d) = diagnosis {
+                app.push_log(format!("No full matches: {d}."), LogLevel::Warn);
+            }
+            if let Some(w) = mismatch {
+                app.push_log(w, LogLevel::Warn);
+            }
+            if !bad_sigs.is_empty() {
+                app.push_log(
+                    format!("{} jar(s) with inconsistent signatures — {}", bad_sigs.len(), bad_sigs.join("; ")),
+                    LogLevel::Warn,
+                );
+            }
+            if !disagreements.is_empty() {
+                app.push_log(
+                    format!("{} jar(s) declare a different side than the module — {}", disagreements.len(), disagreements.join("; ")),
+                    LogLevel::Warn,
+                );
+            }
         }
 
+        Msg::StrictToggled(b) => app.scan_options.strict = b,
+        Msg::LayoutHintToggled(b) => app.scan_options.layout_hint = b,
+        Msg::ToleranceSelected(t) => app.scan_options.version_tolerance = t,
+
         Msg::FilterSide(s) => app.filter_side = s,
 
-        Msg::OpSideSelected(s) => app.op_side = s,
-        Msg::OpSelected(o) => { app.op = o; app.op_output.clear(); app.op_confirm.clear(); }
+        Msg::OpSideToggled(s, on) => { app.op_sides.toggle(s, on); app.refresh_phrase(); }
+        Msg::OpLoaderSelected(l) => app.op_loader = l.0,
+        Msg::OpMatchedOnlyToggled(b) => { app.op_matched_only = b; app.refresh_phrase(); }
+        Msg::OpInvalidToggled(b)     => { app.op_invalid = b; app.refresh_phrase(); }
+        Msg::OpSelected(o) => { app.op = o; app.op_output.clear(); app.refresh_phrase(); }
         Msg::OpOutputChanged(v) => app.op_output = v,
         Msg::OpConfirmChanged(v) => app.op_confirm = v,
+        Msg::OpVerifyZipToggled(b) => app.op_verify_zip = b,
+        Msg::OpBackupToggled(b) => app.op_backup = b,
+        Msg::OpRenameToggled(b) => app.op_rename = b,
 
         Msg::RunOp => {
             if app.loaded_module.is_none() {
@@ -626,23 +2786,65 @@ fn update(app: &mut App, msg: Msg) -> Task<Msg> {
                 return Task::none();
             }
             if app.scan_results.is_empty() {
-                app.push_log("Scan a directory first.", LogLevel::Warn);
+                let msg = match &app.scanned_dir {
+                    Some(d) => format!("No jars found in {d} — nothing to operate on."),
+                    None    => "Scan a directory first.".to_string(),
+                };
+                app.push_log(msg, LogLevel::Warn);
                 return Task::none();
             }
-            if app.op == Operation::Delete && app.op_confirm.trim() != "DELETE" {
-                app.push_log("Type DELETE to confirm deletion.", LogLevel::Warn);
+            if app.op.impact() == Impact::Destroys && app.op_confirm.trim() != app.op_phrase {
+                app.push_log(format!("Type {} to confirm deletion.", app.op_phrase), LogLevel::Warn);
                 return Task::none();
             }
             let output = app.op_output.trim().to_string();
-            if app.op != Operation::Delete && output.is_empty() {
+            if app.op.needs_output() && output.is_empty() {
                 app.push_log("Enter an output path.", LogLevel::Warn);
                 return Task::none();
             }
             let dir = app.directory.trim().to_string();
-            match run_operation(app.op, &dir, &app.scan_results, app.op_side, &output) {
-                Ok(n)  => app.push_log(format!("{n} file(s) affected."), LogLevel::Ok),
+            // Captured before running: a move or delete takes the files away
+            let filter = app.op_filter();
+            let expected: Vec<String> = op_targets(app.op, &app.scan_results, filter).iter()
+                .filter(|r| Path::new(&dir).join(&r.file_name).is_file())
+                .map(|r| r.jar_name.clone())
+                .collect();
+            let protected: Vec<String> = protected_targets(app.op, &app.scan_results, filter).iter()
+                .map(|r| r.jar_name.clone())
+                .collect();
+            for jar in protected {
+                app.push_log(format!("{jar}: protected, skipped."), LogLevel::Warn);
+            }
+            let footprint = stats::Footprint::of(&dir, &op_targets(app.op, &app.scan_results, filter));
+            let module_path = app.loaded_module.as_ref().map(|m| m.path.clone()).unwrap_or_default();
+            let module_name = app.loaded_module.as_ref().map(|m| m.name.as_str()).unwrap_or_default();
+            let output = match template::expand(&output, &template::Fields::today(&filter.target_word(), module_name, expected.len())) {
+                Ok(o) => o,
+                Err(e) => { app.push_log(format!("Error: {e}"), LogLevel::Err); return Task::none(); }
+            };
+            match run_recorded(app.op, &dir, &app.scan_results, filter, &module_path, OpOptions { output: &output, backup: app.op_backup, rename: app.op_rename, ..Default::default() }) {
+                Ok((n, _)) if app.op == Operation::Zip && app.op_verify_zip => {
+                    let names: Vec<&str> = expected.iter().map(String::as_str).collect();
+                    match verify_zip(&output, &names) {
+                        Ok(())  => app.push_log(format!("{n} file(s) zipped and verified."), LogLevel::Ok),
+                        Err(e)  => app.push_log(format!("Verification failed: {e}"), LogLevel::Err),
+                    }
+                }
+                Ok((n, Some(id))) => app.push_log(format!("{n} file(s) affected — recorded as run {id}."), LogLevel::Ok),
+                Ok((n, None))     => app.push_log(format!("{n} file(s) affected."), LogLevel::Ok),
                 Err(e) => app.push_log(format!("Error: {e}"), LogLevel::Err),
             }
+            if app.op == Operation::Delete && footprint.gone() > 0 {
+                let size = stats::human_size(footprint.gone());
+                app.push_log(if app.op_backup {
+                    format!("{size} moved to the backup — nothing freed until it is deleted without one.")
+                } else {
+                    format!("Freed {size}.")
+                }, LogLevel::Ok);
+            }
+            if app.op.impact() == Impact::Destroys {
+                app.refresh_phrase();
+            }
         }
     }
     Task::none()
@@ -808,6 +3010,24 @@ fn input_style_danger() -> iced::widget::text_input::Style {
     }
 }
 
+fn checkbox_style(status: checkbox::Status) -> checkbox::Style {
+    let (checked, hovered) = match status {
+        checkbox::Status::Active   { is_checked } => (is_checked, false),
+        checkbox::Status::Hovered  { is_checked } => (is_checked, true),
+        checkbox::Status::Disabled { is_checked } => (is_checked, false),
+    };
+    checkbox::Style {
+        background: if checked { pal::ACCENT.into() } else { pal::BG.into() },
+        icon_color: Color::WHITE,
+        border: iced::border::Border {
+            color:  if checked || hovered { pal::ACCENT } else { pal::LINE },
+            width:  1.0,
+            radius: 4.0.into(),
+        },
+        text_color: Some(pal::MUTED),
+    }
+}
+
 fn pick_style() -> iced::widget::pick_list::Style {
     iced::widget::pick_list::Style {
         text_color:        pal::INK,
@@ -836,7 +3056,7 @@ fn view_topbar(app: &App) -> Element<'_, Msg> {
         format!(
             "{} jars  ·  {} matched  ·  {} unidentified",
             app.summary.total,
-            app.summary.full + app.summary.partial,
+            app.summary.full + app.summary.unclassified + app.summary.id_only + app.summary.partial,
             app.summary.unidentified
         )
     };
@@ -932,6 +3152,7 @@ fn view_scan(app: &App) -> Element<'_, Msg> {
             text(format!("v{}  ·  {}  ·  {} entries", m.version, m.author, m.mods.len()))
                 .size(11).style(tc(pal::MUTED)),
         ]
+        .push_maybe(m.description.as_ref().map(|d| text(d).size(11).style(tc(pal::FAINT))))
         .spacing(2)
         .into()
     } else {
@@ -980,6 +3201,36 @@ fn view_scan(app: &App) -> Element<'_, Msg> {
             .spacing(8)
             .align_y(alignment::Vertical::Center),
             Space::with_height(10),
+            row![
+                text("Versions").size(12).style(tc(pal::MUTED)),
+                pick_list(
+                    [
+                        version::VersionTolerance::Exact,
+                        version::VersionTolerance::Minor,
+                        version::VersionTolerance::Major,
+                    ],
+                    Some(app.scan_options.version_tolerance),
+                    Msg::ToleranceSelected,
+                )
+                .text_size(12)
+                .style(|_, _| pick_style())
+                .width(Length::Fill),
+            ]
+            .spacing(8)
+            .align_y(alignment::Vertical::Center),
+            Space::with_height(8),
+            checkbox("Strict — require a tag for a full match", app.scan_options.strict)
+                .on_toggle(Msg::StrictToggled)
+                .text_size(12)
+                .size(14)
+                .style(|_, status| checkbox_style(status)),
+            Space::with_height(6),
+            checkbox("Guess sides from assets/data folders (heuristic)", app.scan_options.layout_hint)
+                .on_toggle(Msg::LayoutHintToggled)
+                .text_size(12)
+                .size(14)
+                .style(|_, status| checkbox_style(status)),
+            Space::with_height(10),
             btn_primary("Scan directory").on_press(Msg::ScanDir),
         ]
         .spacing(0)
@@ -1051,11 +3302,21 @@ fn view_scan(app: &App) -> Element<'_, Msg> {
             let loader  = r.jar_info.as_ref().map(|j| j.loader).unwrap_or(ModLoader::Unknown);
             let version = r.jar_info.as_ref().and_then(|j| j.version.as_deref()).unwrap_or("—");
             let side    = r.effective_side();
+            let note    = r.module_entry.as_ref().and_then(|e| e.note.as_deref());
+
+            let mod_id_cell: Element<'_, Msg> = match note {
+                Some(n) => column![
+                    text(mod_id).size(12).style(tc(pal::MUTED)),
+                    text(n).size(10).style(tc(pal::FAINT)),
+                ].spacing(2).into(),
+                None => text(mod_id).size(12).style(tc(pal::MUTED)).into(),
+            };
 
             let loader_color = match loader {
                 ModLoader::Fabric   => pal::ACCENT,
                 ModLoader::Quilt    => pal::PURPLE,
                 ModLoader::Forge    | ModLoader::NeoForge => pal::AMBER,
+                ModLoader::LiteLoader | ModLoader::Rift => pal::MUTED,
                 ModLoader::Unknown  => pal::FAINT,
             };
             let side_color = match side {
@@ -1086,8 +3347,7 @@ fn view_scan(app: &App) -> Element<'_, Msg> {
                     row![
                         text(&r.jar_name).size(12).style(tc(pal::INK))
                             .width(Length::FillPortion(5)),
-                        text(mod_id).size(12).style(tc(pal::MUTED))
-                            .width(Length::FillPortion(3)),
+                        container(mod_id_cell).width(Length::FillPortion(3)),
                         text(loader.to_string()).size(12).style(tc(loader_color))
                             .width(Length::FillPortion(2)),
                         text(version).size(12).style(tc(pal::MUTED))
@@ -1144,20 +3404,60 @@ fn view_scan(app: &App) -> Element<'_, Msg> {
 // View — Operate panel
 // ─────────────────────────────────────────────────────────────────────────────
 
+/// One tick box in the TARGET SIDES checklist.
+fn side_toggle(app: &App, side: Side) -> Element<'_, Msg> {
+    checkbox(side.to_string(), app.op_sides.contains(side))
+        .on_toggle(move |on| Msg::OpSideToggled(side, on))
+        .text_size(12)
+        .size(14)
+        .style(|_, status| checkbox_style(status))
+        .into()
+}
+
 fn view_operate(app: &App) -> Element<'_, Msg> {
-    let affected = app.scan_results.iter()
-        .filter(|r| r.effective_side() == app.op_side).count();
+    // Nothing to act on — say why instead of showing controls that can't do anything
+    if app.scan_results.is_empty() {
+        let (title, hint) = match &app.scanned_dir {
+            Some(d) => ("No jars found".to_string(), format!("{d} contains no .jar files. Pick another folder on the Scan tab.")),
+            None    => ("Nothing scanned yet".to_string(), "Load a module and scan a directory on the Scan tab.".to_string()),
+        };
+        return container(
+            column![
+                text(title).size(14).style(tc(pal::MUTED)),
+                text(hint).size(12).style(tc(pal::FAINT)),
+                Space::with_height(6),
+                btn_ghost("Go to Scan").on_press(Msg::NavPanel(Panel::Scan)),
+            ]
+            .spacing(6)
+            .align_x(alignment::Horizontal::Center),
+        )
+        .center_x(Length::Fill)
+        .height(240)
+        .center_y(240)
+        .into();
+    }
+
+    let affected = op_targets(app.op, &app.scan_results, app.op_filter()).len();
 
     let op_card = card_container(column![
         eyebrow("ACTION"),
         Space::with_height(10),
         row![
             column![
-                eyebrow("TARGET SIDE"),
+                eyebrow("TARGET SIDES"),
+                Space::with_height(6),
+                row![side_toggle(app, Side::Client), side_toggle(app, Side::Server)].spacing(10),
+                Space::with_height(4),
+                row![side_toggle(app, Side::Both), side_toggle(app, Side::Unknown)].spacing(10),
+            ].spacing(0).width(Length::FillPortion(1)),
+            column![
+                eyebrow("LOADER"),
                 Space::with_height(6),
                 pick_list(
-                    vec![Side::Client, Side::Server, Side::Both, Side::Unknown],
-                    Some(app.op_side), Msg::OpSideSelected,
+                    [None, Some(ModLoader::Forge), Some(ModLoader::NeoForge), Some(ModLoader::Fabric), Some(ModLoader::Quilt),
+                     Some(ModLoader::LiteLoader), Some(ModLoader::Rift)]
+                        .map(LoaderChoice).to_vec(),
+                    Some(LoaderChoice(app.op_loader)), Msg::OpLoaderSelected,
                 )
                 .style(|_, _| pick_style()).width(Length::Fill),
             ].spacing(0).width(Length::FillPortion(1)),
@@ -1165,41 +3465,94 @@ fn view_operate(app: &App) -> Element<'_, Msg> {
                 eyebrow("OPERATION"),
                 Space::with_height(6),
                 pick_list(
-                    vec![Operation::Zip, Operation::Move, Operation::Delete, Operation::Export],
+                    vec![Operation::Zip, Operation::Move, Operation::Copy, Operation::Delete, Operation::Quarantine, Operation::Export, Operation::Report],
                     Some(app.op), Msg::OpSelected,
                 )
                 .style(|_, _| pick_style()).width(Length::Fill),
             ].spacing(0).width(Length::FillPortion(1)),
         ].spacing(12),
+        Space::with_height(10),
+        checkbox("Every jar in the module, whatever its side", app.op_matched_only)
+            .on_toggle(Msg::OpMatchedOnlyToggled)
+            .text_size(12)
+            .size(14)
+            .style(|_, status| checkbox_style(status)),
+        Space::with_height(6),
+        checkbox("Only files that aren't valid jars", app.op_invalid)
+            .on_toggle(Msg::OpInvalidToggled)
+            .text_size(12)
+            .size(14)
+            .style(|_, status| checkbox_style(status)),
     ].spacing(0).into());
 
     let output_card: Element<'_, Msg> = if app.op == Operation::Delete {
+        let size = stats::human_size(stats::Footprint::of(app.directory.trim(), &op_targets(app.op, &app.scan_results, app.op_filter())).total());
         card_container(column![
             eyebrow("CONFIRMATION REQUIRED"),
             Space::with_height(6),
-            text("This permanently deletes matching files. Type DELETE below to confirm.")
+            text(if app.op_backup {
+                format!("Matching files ({size}) are moved into the folder's .lodestone history and can be restored with `lodestone undo`. Type {} below to confirm.", app.op_phrase)
+            } else {
+                format!("This permanently deletes matching files, freeing {size}. Type {} below to confirm.", app.op_phrase)
+            })
                 .size(12).style(tc(pal::AMBER)),
             Space::with_height(8),
-            text_input("Type DELETE to confirm…", &app.op_confirm)
+            text_input(&format!("Type {} to confirm…", app.op_phrase), &app.op_confirm)
                 .on_input(Msg::OpConfirmChanged)
                 .style(|_, _| input_style_danger())
                 .padding([9, 12]).size(13),
+            Space::with_height(10),
+            checkbox("Keep a backup so the delete can be undone", app.op_backup)
+                .on_toggle(Msg::OpBackupToggled)
+                .text_size(12)
+                .size(14)
+                .style(|_, status| checkbox_style(status)),
+        ].spacing(0).into())
+    } else if app.op == Operation::Quarantine {
+        card_container(column![
+            eyebrow("QUARANTINE"),
+            Space::with_height(6),
+            text(format!("Matching files are moved into {}/ in this folder, where the game won't load them. \
+                Put them back with `lodestone restore-quarantine`, or delete them with `lodestone empty-quarantine`.",
+                quarantine::DIR))
+                .size(12).style(tc(pal::MUTED)),
         ].spacing(0).into())
     } else {
         let placeholder = match app.op {
             Operation::Zip    => "Output .zip file path",
             Operation::Move   => "Destination directory",
+            Operation::Copy   => "Destination directory",
             Operation::Export => "Output .txt file path",
-            Operation::Delete => unreachable!(),
+            Operation::Report => "Output .txt or .md file path",
+            Operation::Delete | Operation::Quarantine => unreachable!(),
         };
-        card_container(column![
+        let mut body = column![
             eyebrow("OUTPUT PATH"),
             Space::with_height(8),
             text_input(placeholder, &app.op_output)
                 .on_input(Msg::OpOutputChanged)
                 .style(|_, _| input_style_base())
                 .padding([9, 12]).size(13),
-        ].spacing(0).into())
+        ].spacing(0);
+        if app.op == Operation::Zip {
+            body = body.push(Space::with_height(10)).push(
+                checkbox("Verify the archive after writing", app.op_verify_zip)
+                    .on_toggle(Msg::OpVerifyZipToggled)
+                    .text_size(12)
+                    .size(14)
+                    .style(|_, status| checkbox_style(status)),
+            );
+        }
+        if app.op == Operation::Move {
+            body = body.push(Space::with_height(10)).push(
+                checkbox("Rename instead of overwriting jars already there", app.op_rename)
+                    .on_toggle(Msg::OpRenameToggled)
+                    .text_size(12)
+                    .size(14)
+                    .style(|_, status| checkbox_style(status)),
+            );
+        }
+        card_container(body.into())
     };
 
     // Preview strip
@@ -1209,8 +3562,26 @@ fn view_operate(app: &App) -> Element<'_, Msg> {
             Space::with_width(10),
             column![
                 text("files will be affected").size(12).style(tc(pal::INK)),
-                text(format!("filtered to {} side", app.op_side))
-                    .size(11).style(tc(pal::MUTED)),
+                text(if app.op == Operation::Report {
+                    "all sides, grouped".to_string()
+                } else {
+                    let what = if app.op_invalid {
+                        "only files that aren't valid jars".to_string()
+                    } else if app.op_matched_only {
+                        "filtered to mods in the module".to_string()
+                    } else {
+                        match app.op_sides.single() {
+                            Some(side) => format!("filtered to {side} side"),
+                            None if app.op_sides.is_empty() => "no side ticked".to_string(),
+                            None => format!("filtered to {} sides", app.op_sides),
+                        }
+                    };
+                    match app.op_loader {
+                        Some(l) => format!("{what}, {l} only"),
+                        None    => what,
+                    }
+                })
+                .size(11).style(tc(pal::MUTED)),
             ].spacing(2),
         ]
         .align_y(alignment::Vertical::Center),
@@ -1233,7 +3604,10 @@ fn view_operate(app: &App) -> Element<'_, Msg> {
         btn_primary(match app.op {
             Operation::Zip    => "Create zip",
             Operation::Move   => "Move files",
+            Operation::Copy   => "Copy files",
             Operation::Export => "Export list",
+            Operation::Report => "Export report",
+            Operation::Quarantine => "Quarantine files",
             Operation::Delete => unreachable!(),
         })
         .on_press(Msg::RunOp)
@@ -1316,6 +3690,12 @@ fn view(app: &App) -> Element<'_, Msg> {
 // ─────────────────────────────────────────────────────────────────────────────
 
 fn main() -> iced::Result {
+    // Any arguments switch to the command-line interface; none opens the GUI
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args));
+    }
+
     iced::application("Lodestone", update, view)
         .theme(|_| Theme::Light)
         .window(iced::window::Settings {
@@ -1326,3 +3706,1466 @@ fn main() -> iced::Result {
         .settings(Settings { antialiasing: true, ..Default::default() })
         .run_with(|| (App::default(), Task::none()))
 }
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Unit tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh, empty directory under the system temp dir.  Shared with the
+    /// other modules' tests, so names must be unique across them.
+    pub(crate) fn temp_dir(name: &str) -> PathBuf {
+        let d = std::env::temp_dir().join(format!("lodestone-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&d);
+        fs::create_dir_all(&d).unwrap();
+        d
+    }
+
+    /// Write a jar containing the given `(entry name, contents)` pairs.
+    pub(crate) fn write_jar(path: &Path, entries: &[(&str, &str)]) {
+        let mut w = zip::ZipWriter::new(fs::File::create(path).unwrap());
+        for (name, body) in entries {
+            w.start_file(*name, zip::write::FileOptions::default()).unwrap();
+            w.write_all(body.as_bytes()).unwrap();
+        }
+        w.finish().unwrap();
+    }
+
+    /// A module entry for any version with just a tag and loader set, for
+    /// tests to adjust with struct update syntax.
+    pub(crate) fn tagged(tag: Side, loader: ModLoader) -> ModuleEntry {
+        ModuleEntry {
+            mod_version: "*".into(), mod_tag: tag, mod_type: loader,
+            protected: false, note: None, modrinth: None, curseforge: None, version_range: None, packs: Vec::new(),
+        }
+    }
+
+    fn module_with(mods: &[(&str, &str, Side, ModLoader)]) -> Module {
+        let mut m = Module::empty();
+        for (id, version, tag, loader) in mods {
+            m.mods.insert(id.to_string(), ModuleEntry { mod_version: version.to_string(), ..tagged(*tag, *loader) });
+        }
+        m
+    }
+
+    const FABRIC_SODIUM: &str = r#"{"id": "sodium", "version": "0.5.8"}"#;
+
+    #[test]
+    fn strict_match_requires_a_tag() {
+        let d = temp_dir("strict");
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        let dir = d.to_str().unwrap();
+        let module = module_with(&[("sodium", "0.5.8", Side::Unknown, ModLoader::Fabric)]);
+
+        let (r, _) = scan_directory(dir, &module, &ScanOptions::default());
+        assert_eq!(r[0].match_quality, MatchQuality::Full);
+
+        let (r, summary) = scan_directory(dir, &module, &ScanOptions { strict: true, ..Default::default() });
+        assert_eq!(r[0].match_quality, MatchQuality::Unclassified);
+        assert_eq!(summary.unclassified, 1);
+
+        // A jar with no version to compare is unclassified too, not an id match
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", r#"{"id": "sodium"}"#)]);
+        let (r, _) = scan_directory(dir, &module, &ScanOptions::default());
+        assert_eq!(r[0].match_quality, MatchQuality::IdOnly);
+        let (r, _) = scan_directory(dir, &module, &ScanOptions { strict: true, ..Default::default() });
+        assert_eq!(r[0].match_quality, MatchQuality::Unclassified);
+    }
+
+    #[test]
+    fn retag_by_loader_only_touches_that_loader() {
+        let mut m = module_with(&[
+            ("sodium", "*", Side::Client,  ModLoader::Fabric),
+            ("lithium", "*", Side::Unknown, ModLoader::Fabric),
+            ("create", "*", Side::Both,    ModLoader::Forge),
+        ]);
+        assert_eq!(m.retag_by_loader(ModLoader::Fabric, Side::Unknown), 1);
+        assert!(m.mods.values().filter(|e| e.mod_type == ModLoader::Fabric).all(|e| e.mod_tag == Side::Unknown));
+        assert_eq!(m.mods["create"].mod_tag, Side::Both);
+    }
+
+    #[test]
+    fn pretty_and_compact_modules_load_identically() {
+        let d = temp_dir("json-style");
+        let mut m = module_with(&[("create", "0.5.1", Side::Both, ModLoader::Forge)]);
+        m.name = "Round trip".into();
+        m.mods.get_mut("create").unwrap().note = Some("needs client too".into());
+
+        let mut loaded = Vec::new();
+        for (file, style) in [("pretty.json", JsonStyle::Pretty), ("compact.json", JsonStyle::Compact)] {
+            m.path = d.join(file).display().to_string();
+            m.save(style).unwrap();
+            loaded.push(Module::load(&m.path).unwrap());
+        }
+        let compact_raw = fs::read_to_string(d.join("compact.json")).unwrap();
+        assert!(!compact_raw.trim_end().contains('\n'));
+        assert_eq!(loaded[0].name, loaded[1].name);
+        assert_eq!(format!("{:?}", loaded[0].mods), format!("{:?}", loaded[1].mods));
+    }
+
+    #[test]
+    fn saving_is_byte_for_byte_stable() {
+        let d = temp_dir("stable");
+        let path = d.join("module.json");
+        fs::write(&path, r#"{"mods": {"create": {"mod_type": "Forge", "mod_tag": "Both", "mod_version": "0.5.1",
+            "note": "n", "version_range": ">=0.5"}}, "zeta": 1, "alpha": 2,
+            "header": {"module_author": "t", "module_name": "t", "module_version": 1.0, "z": 1, "a": 2}}"#).unwrap();
+        let m = Module::load(path.to_str().unwrap()).unwrap();
+        m.save(JsonStyle::Pretty).unwrap();
+        let first = fs::read_to_string(&path).unwrap();
+        Module::load(path.to_str().unwrap()).unwrap().save(JsonStyle::Pretty).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), first);
+        assert!(first.ends_with("}\n"));
+        let at = |key: &str| first.find(key).unwrap();
+        assert!(at("\"mod_version\"") < at("\"version_range\"") && at("\"version_range\"") < at("\"mod_tag\""));
+        assert!(at("\"alpha\"") < at("\"zeta\"") && at("\"a\"") < at("\"z\""));
+    }
+
+    #[test]
+    fn gzipped_modules_round_trip() {
+        let d = temp_dir("gzip");
+        let mut m = module_with(&[
+            ("create", "0.5.1", Side::Both,   ModLoader::Forge),
+            ("sodium", "0.5.8", Side::Client, ModLoader::Fabric),
+        ]);
+        m.name = "Big pack".into();
+        let mut loaded = Vec::new();
+        for file in ["plain.json", "packed.json.gz"] {
+            m.path = d.join(file).display().to_string();
+            m.save(JsonStyle::Pretty).unwrap();
+            loaded.push(Module::load(&m.path).unwrap());
+        }
+        let packed = fs::read(d.join("packed.json.gz")).unwrap();
+        assert_eq!(&packed[..2], &[0x1f, 0x8b], "written as gzip");
+        assert_eq!(loaded[0].name, loaded[1].name);
+        assert_eq!(format!("{:?}", loaded[0].mods), format!("{:?}", loaded[1].mods));
+    }
+
+    #[test]
+    fn sort_by_side_moves_each_jar_to_its_destination() {
+        let d = temp_dir("sort");
+        let mods = d.join("mods");
+        fs::create_dir_all(&mods).unwrap();
+        write_jar(&mods.join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        write_jar(&mods.join("lithium.jar"), &[("fabric.mod.json", r#"{"id": "lithium", "version": "1"}"#)]);
+        write_jar(&mods.join("other.jar"), &[("fabric.mod.json", r#"{"id": "other", "version": "1"}"#)]);
+        let module = module_with(&[
+            ("sodium",  "*", Side::Client, ModLoader::Fabric),
+            ("lithium", "*", Side::Server, ModLoader::Fabric),
+        ]);
+        let dir = mods.to_str().unwrap();
+        let (results, _) = scan_directory(dir, &module, &ScanOptions::default());
+
+        let dests = BTreeMap::from([
+            (Side::Client, d.join("client").display().to_string()),
+            (Side::Server, d.join("server").display().to_string()),
+        ]);
+        let counts = sort_by_side(dir, &results, &dests, false).unwrap();
+        assert_eq!(counts, BTreeMap::from([(Side::Client, 1), (Side::Server, 1)]));
+        assert!(d.join("client/sodium.jar").is_file());
+        assert!(d.join("server/lithium.jar").is_file());
+        assert!(mods.join("other.jar").is_file(), "sides without a destination stay put");
+    }
+
+    #[test]
+    fn verify_zip_rejects_a_truncated_archive() {
+        let d = temp_dir("verify-zip");
+        let mods = d.join("mods");
+        fs::create_dir_all(&mods).unwrap();
+        write_jar(&mods.join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        let module = module_with(&[("sodium", "*", Side::Client, ModLoader::Fabric)]);
+        let dir = mods.to_str().unwrap();
+        let (results, _) = scan_directory(dir, &module, &ScanOptions::default());
+
+        let out = d.join("client.zip").display().to_string();
+        assert_eq!(run_operation(Operation::Zip, dir, &results, OpFilter::side(Side::Client), &out, false).unwrap(), 1);
+        assert!(verify_zip(&out, &["sodium.jar"]).is_ok());
+        assert!(verify_zip(&out, &["missing.jar"]).is_err());
+
+        let bytes = fs::read(&out).unwrap();
+        let truncated = d.join("truncated.zip");
+        fs::write(&truncated, &bytes[..bytes.len() / 2]).unwrap();
+        assert!(verify_zip(truncated.to_str().unwrap(), &["sodium.jar"]).is_err());
+    }
+
+    #[test]
+    fn legacy_modules_are_migrated() {
+        let v1 = serde_json::json!({
+            "header": { "module_name": "Old", "module_version": 1.0, "module_author": "a" },
+            "mods": { "jei": { "mod_version": 15.2, "mod_tag": "Both", "mod_type": "Forge" } }
+        });
+        let (raw, from) = migrate(v1).unwrap();
+        assert_eq!(from, 1);
+        assert_eq!(raw.format_version, MODULE_FORMAT);
+        assert_eq!(raw.mods["jei"].mod_version, "15.2");
+
+        let future = serde_json::json!({ "format_version": MODULE_FORMAT + 1 });
+        assert!(migrate(future).is_err());
+    }
+
+    #[test]
+    fn report_groups_jars_by_side() {
+        let d = temp_dir("report");
+        let mods = d.join("mods");
+        fs::create_dir_all(&mods).unwrap();
+        write_jar(&mods.join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        write_jar(&mods.join("blank.jar"), &[("readme.txt", "")]);
+        let module = module_with(&[("sodium", "*", Side::Client, ModLoader::Fabric)]);
+        let dir = mods.to_str().unwrap();
+        let (results, _) = scan_directory(dir, &module, &ScanOptions::default());
+
+        let out = d.join("report.txt");
+        let n = run_operation(Operation::Report, dir, &results, OpFilter::side(Side::Server), out.to_str().unwrap(), false).unwrap();
+        assert_eq!(n, 2);
+        assert_eq!(
+            fs::read_to_string(&out).unwrap(),
+            "# Client\nsodium.jar\n\n# Server\n\n# Both\n\n# Unknown\nblank.jar\n",
+        );
+    }
+
+    #[test]
+    fn flags_jars_with_broken_signatures() {
+        let d = temp_dir("signing");
+        let signed = d.join("signed.jar");
+        write_jar(&signed, &[
+            ("META-INF/MANIFEST.MF", "Manifest-Version: 1.0\n\nName: a.class\nSHA-256-Digest: x\n"),
+            ("META-INF/MOD.SF", "Signature-Version: 1.0\n\nName: a.class\nSHA-256-Digest: y\n"),
+            ("META-INF/MOD.RSA", "block"),
+            ("a.class", ""),
+        ]);
+        let stripped = d.join("stripped.jar");
+        write_jar(&stripped, &[
+            ("META-INF/MANIFEST.MF", "Manifest-Version: 1.0\n\nName: a.class\nSHA-256-Digest: x\n"),
+            ("a.class", ""),
+        ]);
+        let removed = d.join("removed.jar");
+        write_jar(&removed, &[
+            ("META-INF/MOD.SF", "Signature-Version: 1.0\n\nName: a.class\nSHA-256-Digest: y\n"),
+            ("META-INF/MOD.RSA", "block"),
+        ]);
+
+        use signing::SignatureStatus::*;
+        assert_eq!(signing::check_jar(signed.to_str().unwrap()), Some(Signed));
+        assert!(matches!(signing::check_jar(stripped.to_str().unwrap()), Some(Inconsistent(_))));
+        assert!(matches!(signing::check_jar(removed.to_str().unwrap()), Some(Inconsistent(_))));
+
+        let (results, _) = scan_directory(d.to_str().unwrap(), &Module::empty(), &ScanOptions::default());
+        assert_eq!(signature_warnings(&results).len(), 2);
+    }
+
+    #[test]
+    fn jar_extension_is_case_insensitive() {
+        let d = temp_dir("jar-case");
+        // Stems differ too, so the names can coexist on case-insensitive disks
+        for name in ["Bar.Jar", "Baz.jar", "Foo.JAR", "notes.txt"] {
+            fs::write(d.join(name), b"").unwrap();
+        }
+        let jars = list_jars(d.to_str().unwrap());
+        assert_eq!(jars, vec!["Bar.Jar", "Baz.jar", "Foo.JAR"]);
+    }
+
+    #[test]
+    fn hidden_jars_and_folders_are_skipped_unless_included() {
+        let d = temp_dir("hidden");
+        fs::create_dir_all(d.join(".disabled")).unwrap();
+        write_jar(&d.join(".disabled").join("old.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        write_jar(&d.join(".stash.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        let dir = d.to_str().unwrap();
+
+        let (results, _) = scan_directory(dir, &Module::empty(), &ScanOptions::default());
+        assert_eq!(results.iter().map(|r| r.jar_name.as_str()).collect::<Vec<_>>(), vec!["sodium.jar"]);
+        let all = ScanOptions { include_hidden: true, ..Default::default() };
+        let (results, _) = scan_directory(dir, &Module::empty(), &all);
+        assert_eq!(results.iter().map(|r| r.jar_name.as_str()).collect::<Vec<_>>(), vec![".stash.jar", "sodium.jar"]);
+
+        // Operations only ever see scanned jars, so the hidden ones stay put
+        let (results, _) = scan_directory(dir, &Module::empty(), &ScanOptions::default());
+        let filter = OpFilter::side(Side::Unknown);
+        run_operation(Operation::Delete, dir, &results, filter, "", false).unwrap();
+        assert!(d.join(".stash.jar").is_file());
+
+        // Listing never descends, so a hidden folder only matters as a pack
+        fs::create_dir_all(d.join("vanilla")).unwrap();
+        let packs = |hidden| pack_dirs(dir, hidden).iter()
+            .map(|p| Path::new(p).file_name().unwrap().to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(packs(false), ["vanilla"]);
+        assert_eq!(packs(true), [".disabled", "vanilla"]);
+    }
+
+    #[test]
+    fn placeholder_version_falls_back_to_manifest() {
+        let d = temp_dir("placeholder");
+        let jar = d.join("create.jar");
+        write_jar(&jar, &[
+            ("META-INF/MANIFEST.MF", "Manifest-Version: 1.0\nImplementation-Version: 0.5.1.f\n"),
+            ("META-INF/mods.toml", "modLoader = \"javafml\"\n[[mods]]\nmodId = \"create\"\nversion = \"${file.jarVersion}\"\n"),
+        ]);
+        let info = parse_jar(jar.to_str().unwrap()).unwrap().unwrap();
+        assert_eq!(info.version.as_deref(), Some("0.5.1.f"));
+        assert_eq!(info.version_source, VersionSource::Manifest);
+    }
+
+    #[test]
+    fn backed_up_delete_can_be_undone() {
+        let d = temp_dir("history");
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        let dir = d.to_str().unwrap();
+        let module = module_with(&[("sodium", "0.5.8", Side::Client, ModLoader::Fabric)]);
+        let (results, _) = scan_directory(dir, &module, &ScanOptions::default());
+
+        let (n, id) = run_recorded(Operation::Delete, dir, &results, OpFilter::side(Side::Client), "test.json", OpOptions { backup: true, ..Default::default() }).unwrap();
+        let id = id.expect("a delete that removed a jar is recorded");
+        assert_eq!(n, 1);
+        assert!(!d.join("sodium.jar").exists());
+
+        let runs = history::list(dir);
+        assert_eq!(runs.len(), 1);
+        assert!(runs[0].reversible());
+        assert!(runs[0].jars[0].sha256.is_some());
+
+        assert_eq!(history::undo(dir, &id).unwrap(), (1, Vec::new()));
+        assert!(d.join("sodium.jar").is_file());
+        assert!(history::undo(dir, &id).is_err(), "a run can only be undone once");
+    }
+
+    #[test]
+    fn loader_filter_narrows_targets() {
+        let d = temp_dir("loader-filter");
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        write_jar(&d.join("oculus.jar"), &[("META-INF/mods.toml", "[[mods]]\nmodId = \"oculus\"\nversion = \"1.6\"\n")]);
+        let module = module_with(&[
+            ("sodium", "0.5.8", Side::Client, ModLoader::Fabric),
+            ("oculus", "1.6",   Side::Client, ModLoader::Forge),
+        ]);
+        let (results, _) = scan_directory(d.to_str().unwrap(), &module, &ScanOptions::default());
+
+        assert_eq!(op_targets(Operation::Delete, &results, OpFilter::side(Side::Client)).len(), 2);
+        let forge = OpFilter { loader: Some(ModLoader::Forge), ..OpFilter::side(Side::Client) };
+        let targets = op_targets(Operation::Delete, &results, forge);
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].jar_name, "oculus.jar");
+    }
+
+    #[test]
+    fn only_matched_ignores_tags_and_copy_keeps_sources() {
+        let d = temp_dir("only-matched");
+        let (src, out) = (d.join("downloads"), d.join("known"));
+        fs::create_dir_all(&src).unwrap();
+        write_jar(&src.join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        write_jar(&src.join("mystery.jar"), &[("fabric.mod.json", r#"{"id": "mystery", "version": "1"}"#)]);
+        let module = module_with(&[("sodium", "*", Side::Unknown, ModLoader::Fabric)]);
+        let dir = src.to_str().unwrap();
+        let (results, _) = scan_directory(dir, &module, &ScanOptions::default());
+
+        let filter = OpFilter { matched_only: true, ..OpFilter::side(Side::Client) };
+        assert_eq!(run_operation(Operation::Copy, dir, &results, filter, out.to_str().unwrap(), false).unwrap(), 1);
+        assert!(out.join("sodium.jar").is_file());
+        assert!(!out.join("mystery.jar").exists());
+        assert!(src.join("sodium.jar").is_file());
+    }
+
+    #[test]
+    fn enumerates_every_mods_toml_dependency() {
+        let d = temp_dir("deps");
+        let jar = d.join("addon.jar");
+        write_jar(&jar, &[("META-INF/mods.toml", r#"
+modLoader = "javafml"
+[[mods]]
+modId = "addon"
+version = "1.0"
+description = "Works on Forge, unlike the NeoForge port"
+
+[[dependencies.addon]]
+modId = "forge"
+mandatory = true
+versionRange = "[47,)"
+[[dependencies.addon]]
+modId = "minecraft"
+mandatory = true
+versionRange = "[1.20.1]"
+[[dependencies.addon]]
+modId = "create"
+mandatory = true
+versionRange = "[0.5.1,)"
+[[dependencies.addon]]
+modId = "jei"
+mandatory = false
+
+[[dependencies.addon_compat]]
+modId = "create"
+type = "optional"
+"#)]);
+        let info = parse_jar(jar.to_str().unwrap()).unwrap().unwrap();
+        // The forge dependency outweighs the word "NeoForge" in the description
+        assert_eq!(info.loader, ModLoader::Forge);
+        assert_eq!(info.dependencies, vec![
+            Dependency::new("create", Some("[0.5.1,)".into()), true),
+            Dependency::new("jei", None, false),
+        ]);
+
+        let neo = d.join("neo.jar");
+        write_jar(&neo, &[("META-INF/mods.toml", "[[mods]]\nmodId = \"neo\"\n[[dependencies.neo]]\nmodId = \"neoforge\"\ntype = \"required\"\n")]);
+        assert_eq!(parse_jar(neo.to_str().unwrap()).unwrap().unwrap().loader, ModLoader::NeoForge);
+    }
+
+    #[test]
+    fn mods_toml_side_is_reported_against_the_module() {
+        let d = temp_dir("declared-side");
+        write_jar(&d.join("zoom.jar"), &[("META-INF/mods.toml",
+            "[[mods]]\nmodId = \"zoom\"\nversion = \"1\"\n[[dependencies.zoom]]\nmodId = \"minecraft\"\nside = \"CLIENT\"\n")]);
+        write_jar(&d.join("lib.jar"), &[("META-INF/mods.toml",
+            "[[mods]]\nmodId = \"lib\"\nversion = \"1\"\n[[dependencies.lib]]\nmodId = \"minecraft\"\nside = \"BOTH\"\n")]);
+        let module = module_with(&[
+            ("zoom", "1", Side::Server, ModLoader::Forge),
+            ("lib",  "1", Side::Server, ModLoader::Forge),
+        ]);
+        let (results, _) = scan_directory(d.to_str().unwrap(), &module, &ScanOptions::default());
+
+        let zoom = results.iter().find(|r| r.jar_name == "zoom.jar").unwrap();
+        assert_eq!(zoom.jar_info.as_ref().unwrap().declared_side, Some(Side::Client));
+        assert_eq!(zoom.effective_side(), Side::Server, "the module still wins");
+        let lib = results.iter().find(|r| r.jar_name == "lib.jar").unwrap();
+        assert_eq!(lib.jar_info.as_ref().unwrap().declared_side, None, "template BOTH is not a declaration");
+
+        let warnings = declared_side_warnings(&results);
+        assert_eq!(warnings.iter().map(Warning::to_string).collect::<Vec<_>>(), ["zoom.jar: module says Server, the jar declares Client"]);
+        assert_eq!(warnings[0].jar.as_deref(), Some("zoom.jar"));
+    }
+
+    #[test]
+    fn only_delete_is_destructive() {
+        use Operation::*;
+        for op in [Zip, Copy, Export, Report] {
+            assert_eq!(op.impact(), Impact::ReadOnly, "{op}");
+        }
+        assert_eq!(Move.impact(), Impact::Alters);
+        assert_eq!(Delete.impact(), Impact::Destroys);
+    }
+
+    #[test]
+    fn dev_jars_fall_back_to_gradle_properties() {
+        let d = temp_dir("gradle");
+        let jar = d.join("dev.jar");
+        write_jar(&jar, &[
+            ("META-INF/mods.toml", "[[mods]]\nmodId = \"dev\"\nversion = \"${mod_version}\"\n"),
+            ("gradle.properties", "# build\norg.gradle.jvmargs=-Xmx3G\nmod_version = 2.1.0-beta\n"),
+        ]);
+        let info = parse_jar(jar.to_str().unwrap()).unwrap().unwrap();
+        assert_eq!(info.version.as_deref(), Some("2.1.0-beta"));
+        assert_eq!(info.version_source, VersionSource::GradleProperties);
+        assert_eq!(info.metadata_file, "META-INF/mods.toml");
+    }
+
+    #[test]
+    fn retag_by_pattern_hits_a_family_of_ids() {
+        let mut m = module_with(&[
+            ("xaerominimap", "*", Side::Unknown, ModLoader::Forge),
+            ("journeymap",   "*", Side::Unknown, ModLoader::Forge),
+            ("create",       "*", Side::Both,    ModLoader::Forge),
+        ]);
+        let p: pattern::IdPattern = ".*map.*".parse().unwrap();
+        assert_eq!(m.ids_matching(&p), vec!["journeymap", "xaerominimap"]);
+        assert_eq!(m.retag_by_pattern(&p, Side::Client), 2);
+        assert_eq!(m.mods["create"].mod_tag, Side::Both);
+    }
+
+    #[test]
+    fn no_match_diagnosis_names_the_cause() {
+        let d = temp_dir("diagnosis");
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        let dir = d.to_str().unwrap();
+
+        let old = module_with(&[("sodium", "0.4.0", Side::Client, ModLoader::Fabric)]);
+        let (results, summary) = scan_directory(dir, &old, &ScanOptions::default());
+        assert_eq!(results[0].mismatch, Mismatch { version: true, loader: false });
+        assert_eq!(summary.version_mismatch, 1);
+        assert!(summary.diagnosis().unwrap().contains("all versions differed"));
+
+        let other = module_with(&[("create", "*", Side::Both, ModLoader::Forge)]);
+        let (_, summary) = scan_directory(dir, &other, &ScanOptions::default());
+        assert!(summary.diagnosis().unwrap().contains("0 of 1"));
+
+        let right = module_with(&[("sodium", "0.5.8", Side::Client, ModLoader::Fabric)]);
+        let (_, summary) = scan_directory(dir, &right, &ScanOptions::default());
+        assert_eq!(summary.diagnosis(), None);
+    }
+
+    #[test]
+    fn lists_module_mods_missing_from_the_folder() {
+        let d = temp_dir("missing");
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        let mut m = module_with(&[
+            ("sodium", "0.4.0", Side::Client, ModLoader::Fabric),
+            ("lithium", "*",    Side::Both,   ModLoader::Fabric),
+        ]);
+        m.mods.get_mut("lithium").unwrap().modrinth = Some("lithium".into());
+        let (results, _) = scan_directory(d.to_str().unwrap(), &m, &ScanOptions::default());
+
+        let missing = m.missing_from(&results);
+        assert_eq!(missing.len(), 1, "an outdated jar is not missing");
+        let text = report::missing_manifest(&m.name, &missing);
+        assert!(text.contains("lithium"));
+        assert!(text.contains("modrinth: lithium"));
+        assert!(!text.contains("sodium"));
+
+        let stale: Vec<String> = missing.iter().map(|(id, _)| id.to_string()).collect();
+        assert_eq!(m.remove_entries(&stale), 1);
+        assert!(m.mods.contains_key("sodium") && !m.mods.contains_key("lithium"));
+    }
+
+    #[test]
+    fn fabric_entrypoints_suggest_a_side() {
+        let d = temp_dir("entrypoints");
+        let side_of = |name: &str, json: &str| {
+            let jar = d.join(name);
+            write_jar(&jar, &[("fabric.mod.json", json)]);
+            parse_jar(jar.to_str().unwrap()).unwrap().unwrap().declared_side
+        };
+        assert_eq!(side_of("zoom.jar",
+            r#"{"id": "zoom", "environment": "*", "entrypoints": {"client": ["a.Zoom"]}}"#), Some(Side::Client));
+        assert_eq!(side_of("core.jar",
+            r#"{"id": "core", "entrypoints": {"main": ["a.Core"], "client": ["a.CoreClient"]}}"#), None);
+        assert_eq!(side_of("lib.jar",
+            r#"{"id": "lib", "environment": "*", "entrypoints": {"main": ["a.Lib"]}}"#), Some(Side::Both));
+        assert_eq!(side_of("srv.jar",
+            r#"{"id": "srv", "environment": "server", "entrypoints": {"client": ["a.X"]}}"#), Some(Side::Server));
+    }
+
+    #[test]
+    fn invalid_jars_are_flagged_and_only_selected_explicitly() {
+        let d = temp_dir("invalid");
+        fs::write(d.join("broken.jar"), b"not a zip").unwrap();
+        write_jar(&d.join("plain.jar"), &[("readme.txt", "no metadata")]);
+        let (results, summary) = scan_directory(d.to_str().unwrap(), &Module::empty(), &ScanOptions::default());
+        let broken = results.iter().find(|r| r.jar_name == "broken.jar").unwrap();
+        assert_eq!(broken.match_quality, MatchQuality::Invalid);
+        assert_eq!((summary.invalid, summary.unknown), (1, 1));
+
+        let unknown = op_targets(Operation::Move, &results, OpFilter::side(Side::Unknown));
+        assert_eq!(unknown.iter().map(|r| r.jar_name.as_str()).collect::<Vec<_>>(), vec!["plain.jar"]);
+        let invalid = OpFilter { invalid: true, ..OpFilter::side(Side::Unknown) };
+        let picked = op_targets(Operation::Delete, &results, invalid);
+        assert_eq!(picked.iter().map(|r| r.jar_name.as_str()).collect::<Vec<_>>(), vec!["broken.jar"]);
+    }
+
+    #[test]
+    fn keep_going_collects_failures_and_fail_fast_stops() {
+        let d = temp_dir("failfast");
+        fs::write(d.join("a.jar"), b"a").unwrap();
+        fs::write(d.join("b.jar"), b"b").unwrap();
+        fs::write(d.join("c.jar"), b"c").unwrap();
+        let dir = d.to_str().unwrap();
+        let (results, _) = scan_directory(dir, &Module::empty(), &ScanOptions::default());
+        assert_eq!(results.len(), 3);
+        let stopped = scan_directory(dir, &Module::empty(), &ScanOptions { fail_fast: true, ..Default::default() });
+        assert_eq!(stopped.0.len(), 1, "a.jar is not a zip");
+
+        // Copying into a path that is a file fails for every jar
+        let targets: Vec<&ScanResult> = results.iter().collect();
+        let blocked = d.join("blocked");
+        fs::write(&blocked, b"").unwrap();
+        let copy = |r: &ScanResult, src: &Path| -> Result<(), Box<dyn std::error::Error>> {
+            fs::copy(src, blocked.join(&r.file_name))?;
+            Ok(())
+        };
+        let err = each_jar(dir, &targets, false, copy).unwrap_err();
+        let errors = err.downcast_ref::<FileErrors>().unwrap();
+        assert_eq!((errors.done, errors.failed.len()), (0, 3));
+        let err = each_jar(dir, &targets, true, copy).unwrap_err();
+        assert!(err.downcast_ref::<FileErrors>().is_none());
+        assert!(err.to_string().starts_with("a.jar: "));
+    }
+
+    #[test]
+    fn reads_declared_icons() {
+        let d = temp_dir("icons");
+        let jar = d.join("sodium.jar");
+        write_jar(&jar, &[
+            ("fabric.mod.json", r#"{"id": "sodium", "icon": {"16": "assets/s16.png", "128": "/assets/s128.png"}}"#),
+            ("assets/s128.png", "PNG128"),
+        ]);
+        let path = jar.to_str().unwrap();
+        let icon = parse_jar(path).unwrap().unwrap().icon.unwrap();
+        assert_eq!(icon, "/assets/s128.png");
+        assert_eq!(read_icon(path, &icon).as_deref(), Some(&b"PNG128"[..]));
+
+        let forge = d.join("create.jar");
+        write_jar(&forge, &[("META-INF/mods.toml", "[[mods]]\nmodId = \"create\"\nlogoFile = \"logo.png\"\n")]);
+        let info = parse_jar(forge.to_str().unwrap()).unwrap().unwrap();
+        assert_eq!(info.icon.as_deref(), Some("logo.png"));
+        assert_eq!(read_icon(forge.to_str().unwrap(), "logo.png"), None, "declared but not shipped");
+    }
+
+    #[test]
+    fn custom_module_metadata_survives_a_save() {
+        let d = temp_dir("extra");
+        let path = d.join("module.json");
+        fs::write(&path, r#"{
+            "format_version": 2,
+            "header": {"module_name": "t", "module_version": 1.0, "module_author": "t",
+                       "description": "Server pack", "homepage": "https://example.org"},
+            "mods": {},
+            "changelog": ["1.0: first"]
+        }"#).unwrap();
+        let m = Module::load(path.to_str().unwrap()).unwrap();
+        assert_eq!(m.description.as_deref(), Some("Server pack"));
+        m.save(JsonStyle::Pretty).unwrap();
+
+        let v: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(v["header"]["homepage"], "https://example.org");
+        assert_eq!(v["header"]["description"], "Server pack");
+        assert_eq!(v["changelog"][0], "1.0: first");
+    }
+
+    #[test]
+    fn confirm_phrase_follows_the_setting() {
+        let client = OpFilter::side(Side::Client);
+        assert_eq!(ConfirmPhrase::parse("").phrase(&client), "DELETE");
+        assert_eq!(ConfirmPhrase::parse("yes, delete").phrase(&client), "yes, delete");
+        assert_eq!(ConfirmPhrase::parse("tag").phrase(&client), "client");
+        assert_eq!(ConfirmPhrase::parse("tag").phrase(&OpFilter { invalid: true, ..client }), "invalid");
+        assert!(CONFIRM_WORDS.contains(&ConfirmPhrase::parse("random").phrase(&client).as_str()));
+    }
+
+    #[test]
+    fn case_variant_ids_are_merged_under_lowercase() {
+        let mut m = module_with(&[
+            ("JEI", "15.2.0", Side::Client, ModLoader::Forge),
+            ("jei", "15.3.1", Side::Both,   ModLoader::Forge),
+            ("Jei", "*",      Side::Unknown, ModLoader::Forge),
+            ("create", "*",   Side::Both,   ModLoader::Forge),
+        ]);
+        m.mods.get_mut("Jei").unwrap().note = Some("config GUI only".into());
+        let merges = m.dedupe_case();
+        assert_eq!(merges.len(), 1);
+        assert_eq!(merges[0].key, "jei");
+        assert_eq!(merges[0].conflicts, vec!["JEI is tagged Client, kept Both from jei"]);
+        assert_eq!(m.mods.keys().collect::<Vec<_>>(), vec!["create", "jei"]);
+        let jei = &m.mods["jei"];
+        assert_eq!((jei.mod_version.as_str(), jei.mod_tag), ("15.3.1", Side::Both));
+        assert_eq!(jei.note.as_deref(), Some("config GUI only"));
+    }
+
+    #[test]
+    fn min_tool_version_flags_older_builds() {
+        assert!(tool_older_than("0.2.0", "0.3"));
+        assert!(tool_older_than("0.2.0", "v1.0.0"));
+        assert!(!tool_older_than("0.2.0", "0.2"));
+        assert!(!tool_older_than("0.2.0", "0.1.9"));
+        assert!(!tool_older_than("0.2.0", "next"));
+
+        let d = temp_dir("min-tool");
+        let path = d.join("module.json");
+        fs::write(&path, r#"{"format_version": 2,
+            "header": {"module_name": "t", "module_version": 1.0, "module_author": "t",
+                       "min_tool_version": "999.0"},
+            "mods": {}}"#).unwrap();
+        let m = Module::load(path.to_str().unwrap()).unwrap();
+        assert!(m.too_new().unwrap().contains("999.0"));
+        m.save(JsonStyle::Pretty).unwrap();
+        let v: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(v["header"]["min_tool_version"], "999.0");
+    }
+
+    #[test]
+    fn compact_lines_fit_one_jar_each() {
+        let d = temp_dir("compact");
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        write_jar(&d.join("blank.jar"), &[("readme.txt", "hi")]);
+        fs::write(d.join("broken.jar"), b"not a zip").unwrap();
+        let module = module_with(&[("sodium", "0.5.8", Side::Client, ModLoader::Fabric)]);
+        let (r, _) = scan_directory(d.to_str().unwrap(), &module, &ScanOptions::default());
+        let lines: Vec<String> = r.iter().map(report::compact_line).collect();
+        assert!(lines.contains(&"✓ sodium 0.5.8 [Fabric/Client]".to_string()), "{lines:?}");
+        assert!(lines.contains(&"? blank.jar [no metadata]".to_string()), "{lines:?}");
+        assert!(lines.contains(&"✗ broken.jar [not a valid jar]".to_string()), "{lines:?}");
+    }
+
+    #[test]
+    fn listed_jars_are_acted_on_without_a_module() {
+        let d = temp_dir("listed");
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        write_jar(&d.join("blank.jar"), &[("readme.txt", "hi")]);
+        write_jar(&d.join("kept.jar"), &[("readme.txt", "hi")]);
+        let dir = d.to_str().unwrap();
+        let out = d.join("out");
+        let (results, _) = scan_directory(dir, &Module::empty(), &ScanOptions::default());
+
+        let typo = ["blank.jar".to_string(), "nope.jar".to_string()];
+        assert!(run_listed(Operation::Move, dir, &results, &typo, OpOptions { output: out.to_str().unwrap(), backup: true, ..Default::default() }).is_err());
+        assert!(d.join("blank.jar").is_file(), "nothing moves when a name is wrong");
+
+        let jars = ["sodium.jar".to_string(), "blank.jar".to_string()];
+        let (n, id) = run_listed(Operation::Move, dir, &results, &jars, OpOptions { output: out.to_str().unwrap(), backup: true, ..Default::default() }).unwrap();
+        assert_eq!(n, 2);
+        assert!(id.is_some());
+        assert!(out.join("sodium.jar").is_file() && out.join("blank.jar").is_file());
+        assert!(d.join("kept.jar").is_file());
+        assert_eq!(history::list(dir)[0].module, None);
+    }
+
+    #[test]
+    fn pattern_order_beats_entry_order() {
+        let d = temp_dir("priority");
+        let jar = d.join("hybrid.jar");
+        write_jar(&jar, &[
+            ("fabric.mod.json", FABRIC_SODIUM),
+            ("META-INF/mods.toml", "[[mods]]\nmodId = \"sodiumforge\"\nversion = \"0.5.8\"\n"),
+        ]);
+        let info = parse_jar(&jar).unwrap().unwrap();
+        assert_eq!((info.mod_id.as_str(), info.loader), ("sodiumforge", ModLoader::Forge));
+        assert_eq!(info.metadata_file, "META-INF/mods.toml");
+    }
+
+    #[test]
+    fn manifest_jars_take_their_version_from_the_jar() {
+        let d = temp_dir("cf-version");
+        let mods = d.join("mods");
+        fs::create_dir_all(&mods).unwrap();
+        fs::write(d.join("minecraftinstance.json"), r#"{
+            "baseModLoader": { "name": "forge-47.2.0" },
+            "installedAddons": [{ "name": "Just Enough Items (JEI)",
+                "webSiteURL": "https://www.curseforge.com/minecraft/mc-mods/jei",
+                "installedFile": { "fileName": "jei-1.20.1-forge-15.2.0.27.jar" } }]
+        }"#).unwrap();
+        write_jar(&mods.join("jei-1.20.1-forge-15.2.0.27.jar"),
+            &[("META-INF/mods.toml", "[[mods]]\nmodId = \"jeimod\"\nversion = \"15.2.0.27\"\n")]);
+        let module = module_with(&[("jei", "15.2.0.27", Side::Both, ModLoader::Forge)]);
+        let opts = ScanOptions { curseforge: true, ..Default::default() };
+        let (r, _) = scan_directory(mods.to_str().unwrap(), &module, &opts);
+        assert_eq!(r[0].jar_info.as_ref().unwrap().mod_id, "jei", "the manifest's id is kept");
+        assert_eq!(r[0].match_quality, MatchQuality::Full);
+    }
+
+    #[test]
+    fn listed_protected_jars_are_never_deleted() {
+        let d = temp_dir("listed-protected");
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        write_jar(&d.join("blank.jar"), &[("readme.txt", "hi")]);
+        let dir = d.to_str().unwrap();
+        let mut module = module_with(&[("sodium", "0.5.8", Side::Client, ModLoader::Fabric)]);
+        module.mods.get_mut("sodium").unwrap().protected = true;
+        let (results, _) = scan_directory(dir, &module, &ScanOptions::default());
+
+        let jars = ["sodium.jar".to_string(), "blank.jar".to_string()];
+        let (n, _) = run_listed(Operation::Delete, dir, &results, &jars, OpOptions { backup: true, ..Default::default() }).unwrap();
+        assert_eq!(n, 1);
+        assert!(d.join("sodium.jar").is_file());
+        assert!(!d.join("blank.jar").exists());
+    }
+
+    #[test]
+    fn libraries_are_suggested_both() {
+        let d = temp_dir("library");
+        let lib = d.join("somelib.jar");
+        write_jar(&lib, &[("fabric.mod.json", r#"{"id": "somelib", "version": "1.0", "mixins": ["somelib.mixins.json"]}"#)]);
+        let content = d.join("content.jar");
+        write_jar(&content, &[("fabric.mod.json", r#"{"id": "content", "version": "1.0", "mixins": ["content.mixins.json"],
+            "entrypoints": {"main": ["org.example.Content"]}}"#)]);
+
+        let info = parse_jar(lib.to_str().unwrap()).unwrap().unwrap();
+        assert!(info.mixin_only);
+        assert_eq!(library::reason(&info), Some("mixins but no entrypoint"));
+        let info = parse_jar(content.to_str().unwrap()).unwrap().unwrap();
+        assert_eq!(library::reason(&info), None);
+
+        let mut m = module_with(&[
+            ("architectury", "*", Side::Unknown, ModLoader::Fabric),
+            ("sodium", "*", Side::Client, ModLoader::Fabric),
+        ]);
+        let ids = m.library_ids();
+        assert_eq!(ids, vec!["architectury"]);
+        assert_eq!(m.retag_ids(&ids, Side::Both), 1);
+        assert_eq!(m.mods["architectury"].mod_tag, Side::Both);
+    }
+
+    #[test]
+    fn permission_errors_say_what_to_do() {
+        let denied = || std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        assert_eq!(io_context(denied(), "writing out.zip").to_string(),
+            "permission denied writing out.zip — run with appropriate privileges or choose a writable output dir");
+        assert_eq!(io_context(denied(), "reading mods").to_string(),
+            "permission denied reading mods — run with appropriate privileges");
+        let missing = std::io::Error::from(std::io::ErrorKind::NotFound);
+        assert_eq!(io_context(missing, "writing out.zip").to_string(), "entity not found");
+    }
+
+    #[test]
+    fn version_range_turns_a_patch_bump_into_a_full_match() {
+        let d = temp_dir("range");
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        let dir = d.to_str().unwrap();
+        let mut module = module_with(&[("sodium", "0.5.3", Side::Client, ModLoader::Fabric)]);
+
+        let (r, _) = scan_directory(dir, &module, &ScanOptions::default());
+        assert_eq!(r[0].match_quality, MatchQuality::Partial);
+
+        module.mods.get_mut("sodium").unwrap().version_range = Some(">=0.5.0,<0.6.0".into());
+        let (r, _) = scan_directory(dir, &module, &ScanOptions::default());
+        assert_eq!(r[0].match_quality, MatchQuality::Full);
+
+        module.mods.get_mut("sodium").unwrap().version_range = Some(">=0.6".into());
+        let (r, _) = scan_directory(dir, &module, &ScanOptions::default());
+        assert_eq!(r[0].match_quality, MatchQuality::Partial);
+    }
+
+    #[test]
+    fn saved_mapping_stands_in_for_the_jars() {
+        let d = temp_dir("mapping");
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        let dir = d.to_str().unwrap();
+        let (results, _) = scan_directory(dir, &Module::empty(), &ScanOptions::default());
+        let map = mapping::build(dir, &results, true, 2);
+        map.save(dir).unwrap();
+        assert_eq!(map.staleness(&list_jars(dir)), None);
+
+        let module = module_with(&[("sodium", "0.5.8", Side::Client, ModLoader::Fabric)]);
+        let opts = ScanOptions { mapping: true, ..Default::default() };
+        let (r, _) = scan_directory(dir, &module, &opts);
+        assert_eq!(r[0].match_quality, MatchQuality::Full);
+        assert_eq!(r[0].jar_info.as_ref().unwrap().metadata_file, ".lodestone/mapping.json");
+
+        // Replaced under the same name: the hash no longer matches, so the jar is opened
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", r#"{"id": "sodium", "version": "0.6.0"}"#)]);
+        write_jar(&d.join("extra.jar"), &[("readme.txt", "hi")]);
+        let (r, _) = scan_directory(dir, &module, &opts);
+        let sodium = r.iter().find(|r| r.jar_name == "sodium.jar").unwrap();
+        assert_eq!(sodium.jar_info.as_ref().unwrap().version.as_deref(), Some("0.6.0"));
+        assert!(mapping::load(dir).unwrap().staleness(&list_jars(dir)).is_some());
+    }
+
+    #[test]
+    fn unversioned_jars_are_an_id_match() {
+        let d = temp_dir("id-only");
+        write_jar(&d.join("create.jar"), &[("META-INF/mods.toml", "modLoader=\"javafml\"\n[[mods]]\nmodId=\"create\"\n")]);
+        let module = module_with(&[("create", "0.5.1", Side::Both, ModLoader::Forge)]);
+        let (r, summary) = scan_directory(d.to_str().unwrap(), &module, &ScanOptions::default());
+        assert_eq!(r[0].match_quality, MatchQuality::IdOnly);
+        assert!(r[0].mismatch.is_none());
+        assert_eq!(summary.id_only, 1);
+        assert!(summary.describe().contains("1 ID match (no version)"));
+        assert_eq!(summary.diagnosis(), None);
+        assert_eq!(r[0].effective_side(), Side::Both, "operations still select it by tag");
+    }
+
+    #[test]
+    fn failed_list_writes_leave_the_old_file_alone() {
+        let d = temp_dir("atomic");
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        let dir = d.to_str().unwrap();
+        let module = module_with(&[("sodium", "*", Side::Client, ModLoader::Fabric)]);
+        let (results, _) = scan_directory(dir, &module, &ScanOptions::default());
+        let out = d.join("names.txt");
+        fs::write(&out, "previous list\n").unwrap();
+
+        // A directory where the temporary file should go makes the write fail
+        fs::create_dir(d.join("names.txt.tmp")).unwrap();
+        assert!(run_operation(Operation::Export, dir, &results, OpFilter::side(Side::Client), out.to_str().unwrap(), false).is_err());
+        assert_eq!(fs::read_to_string(&out).unwrap(), "previous list\n");
+
+        fs::remove_dir(d.join("names.txt.tmp")).unwrap();
+        run_operation(Operation::Export, dir, &results, OpFilter::side(Side::Client), out.to_str().unwrap(), false).unwrap();
+        assert_eq!(fs::read_to_string(&out).unwrap(), "sodium.jar\n");
+        assert!(!d.join("names.txt.tmp").exists());
+    }
+
+    #[test]
+    fn several_sides_select_their_union() {
+        let d = temp_dir("multiside");
+        for id in ["zoom", "lib", "srv"] {
+            write_jar(&d.join(format!("{id}.jar")), &[("fabric.mod.json", &format!(r#"{{"id": "{id}", "version": "1.0"}}"#))]);
+        }
+        let module = module_with(&[
+            ("zoom", "1.0", Side::Client, ModLoader::Fabric),
+            ("lib",  "1.0", Side::Both,   ModLoader::Fabric),
+            ("srv",  "1.0", Side::Server, ModLoader::Fabric),
+        ]);
+        let (results, _) = scan_directory(d.to_str().unwrap(), &module, &ScanOptions::default());
+
+        let sides: Sides = "client, both".parse().unwrap();
+        assert_eq!(sides.to_string(), "Client + Both");
+        assert_eq!(sides.single(), None);
+        let filter = OpFilter { sides, ..OpFilter::side(Side::Client) };
+        assert_eq!(filter.target_word(), "client+both");
+        let mut picked: Vec<_> = op_targets(Operation::Move, &results, filter).iter().map(|r| r.jar_name.clone()).collect();
+        picked.sort();
+        assert_eq!(picked, vec!["lib.jar", "zoom.jar"]);
+        assert!("client,nether".parse::<Sides>().is_err());
+    }
+
+    #[test]
+    fn unknown_loaders_are_counted_apart_from_missing_metadata() {
+        let d = temp_dir("unknownloader");
+        // A language loader Lodestone doesn't know, and no loader dependency
+        write_jar(&d.join("odd.jar"), &[("META-INF/mods.toml", "modLoader = \"mysteryfml\"\n[[mods]]\nmodId = \"odd\"\n")]);
+        write_jar(&d.join("plain.jar"), &[("META-INF/mods.toml", "modLoader = \"javafml\"\n[[mods]]\nmodId = \"plain\"\n")]);
+        write_jar(&d.join("stray.jar"), &[("readme.txt", "hi")]);
+        let (_, summary) = scan_directory(d.to_str().unwrap(), &Module::empty(), &ScanOptions::default());
+        assert_eq!((summary.unknown_loader, summary.unknown), (1, 1));
+        assert!(summary.describe().ends_with("; 1 with metadata but no detected loader, 1 with no mod metadata."));
+    }
+
+    #[test]
+    fn declared_sides_fill_new_and_unknown_entries_only() {
+        let d = temp_dir("declared");
+        write_jar(&d.join("zoom.jar"), &[("fabric.mod.json", r#"{"id": "zoom", "version": "1.0", "environment": "client"}"#)]);
+        write_jar(&d.join("srv.jar"), &[("fabric.mod.json", r#"{"id": "srv", "version": "1.0", "environment": "server"}"#)]);
+        write_jar(&d.join("kept.jar"), &[("fabric.mod.json", r#"{"id": "kept", "version": "1.0", "environment": "client"}"#)]);
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        let mut m = module_with(&[
+            ("srv",  "1.0", Side::Unknown, ModLoader::Fabric),
+            ("kept", "1.0", Side::Both,    ModLoader::Fabric),
+        ]);
+        let (results, _) = scan_directory(d.to_str().unwrap(), &m, &ScanOptions::default());
+
+        let tagged = m.clone().tag_from_declarations(&results, false);
+        assert_eq!(tagged.added, vec![("zoom".to_string(), Side::Client)]);
+        assert_eq!(tagged.updated, vec![("srv".to_string(), Side::Unknown, Side::Server)]);
+        let mut review = tagged.review.clone();
+        review.sort();
+        assert_eq!(review, vec!["kept", "sodium"]);
+
+        let tagged = m.tag_from_declarations(&results, true);
+        assert_eq!(tagged.updated.len(), 2);
+        assert_eq!(m.mods["kept"].mod_tag, Side::Client);
+    }
+
+    #[test]
+    fn each_visible_subfolder_is_a_pack() {
+        let d = temp_dir("packs");
+        for sub in ["1.21", "1.20.1", ".lodestone"] {
+            fs::create_dir_all(d.join(sub)).unwrap();
+        }
+        write_jar(&d.join("1.20.1").join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        write_jar(&d.join("loose.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        let names: Vec<String> = pack_dirs(d.to_str().unwrap(), false).iter()
+            .map(|p| Path::new(p).file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, vec!["1.20.1", "1.21"]);
+    }
+
+    #[test]
+    fn disagreeing_tags_across_modules_are_conflicts() {
+        let mut a = module_with(&[
+            ("create", "*", Side::Both,   ModLoader::Forge),
+            ("JEI",    "*", Side::Client, ModLoader::Forge),
+            ("fresh",  "*", Side::Client, ModLoader::Forge),
+        ]);
+        a.path = "a.json".into();
+        let mut b = module_with(&[
+            ("create", "*", Side::Both,    ModLoader::Forge),
+            ("jei",    "*", Side::Both,    ModLoader::Forge),
+            ("fresh",  "*", Side::Unknown, ModLoader::Forge),
+        ]);
+        b.path = "b.json".into();
+        assert_eq!(tag_conflicts(&[a, b]), vec![TagConflict {
+            id:   "jei".into(),
+            tags: vec![("a.json".into(), Side::Client), ("b.json".into(), Side::Both)],
+        }]);
+    }
+
+    #[test]
+    fn bundled_jars_are_listed_with_their_parent() {
+        let zipped = |entries: &[(&str, &[u8])]| {
+            let mut w = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+            for (name, body) in entries {
+                w.start_file(*name, zip::write::FileOptions::default()).unwrap();
+                w.write_all(body).unwrap();
+            }
+            w.finish().unwrap().into_inner()
+        };
+        let base = zipped(&[("fabric.mod.json", br#"{"id": "fabric-api-base", "version": "0.4.1"}"#)]);
+        let api = zipped(&[
+            ("fabric.mod.json", br#"{"id": "fabric-api", "version": "0.92.0"}"#),
+            ("META-INF/jars/fabric-api-base.jar", &base),
+        ]);
+        let d = temp_dir("bundled");
+        let mut w = zip::ZipWriter::new(fs::File::create(d.join("sodium.jar")).unwrap());
+        for (name, body) in [("fabric.mod.json", FABRIC_SODIUM.as_bytes()), ("META-INF/jars/fabric-api.jar", &api),
+                             ("META-INF/jars/broken.jar", b"not a zip")] {
+            w.start_file(name, zip::write::FileOptions::default()).unwrap();
+            w.write_all(body).unwrap();
+        }
+        w.finish().unwrap();
+
+        let info = parse_jar(d.join("sodium.jar").to_str().unwrap()).unwrap().unwrap();
+        let ids: Vec<(&str, Option<&str>)> = info.bundled.iter().map(|b| (b.mod_id.as_str(), b.version.as_deref())).collect();
+        assert_eq!(ids, vec![("fabric-api", Some("0.92.0")), ("fabric-api-base", Some("0.4.1"))]);
+
+        let module = module_with(&[("fabric-api-base", "*", Side::Both, ModLoader::Fabric)]);
+        let (results, _) = scan_directory(d.to_str().unwrap(), &module, &ScanOptions::default());
+        assert!(module.missing_from(&results).is_empty(), "a bundled mod is installed");
+    }
+
+    #[test]
+    fn a_bom_is_added_once() {
+        let d = temp_dir("bom");
+        let out = d.join("names.txt");
+        fs::write(&out, "jökull.jar\n").unwrap();
+        add_bom(out.to_str().unwrap()).unwrap();
+        add_bom(out.to_str().unwrap()).unwrap();
+        assert_eq!(fs::read(&out).unwrap(), "\u{feff}jökull.jar\n".as_bytes());
+    }
+
+    #[test]
+    fn outputs_inside_the_scanned_folder_are_refused() {
+        let d = temp_dir("selfout");
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        let dir = d.to_str().unwrap();
+        let (results, _) = scan_directory(dir, &Module::empty(), &ScanOptions::default());
+        let unknown = OpFilter::side(Side::Unknown);
+        let inside = d.join("pack.jar");
+        assert!(run_operation(Operation::Zip, dir, &results, unknown, inside.to_str().unwrap(), false).is_err());
+        assert!(!inside.exists());
+        assert!(run_operation(Operation::Copy, &format!("{dir}/"), &results, unknown, &format!("{dir}/./"), false).is_err());
+        assert!(check_output(Operation::Move, dir, d.join("client").to_str().unwrap()).is_ok());
+        assert!(check_output(Operation::Export, dir, d.join("names.txt").to_str().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn explain_traces_a_partial_match_to_its_cause() {
+        let d = temp_dir("explain");
+        write_jar(&d.join("sodium-0.5.8.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        let module = module_with(&[("sodium", "0.5.3", Side::Client, ModLoader::Fabric)]);
+        let (results, _) = scan_directory(d.to_str().unwrap(), &module, &ScanOptions::default());
+
+        let lines = report::explain("Sodium", &results, &module);
+        assert!(lines.contains(&"  jar:       sodium-0.5.8.jar".to_string()));
+        assert!(lines.contains(&"  detected:  Fabric 0.5.8 (from fabric.mod.json)".to_string()));
+        assert!(lines.contains(&"  verdict:   Partial — version 0.5.8 is not the module's 0.5.3".to_string()));
+        assert!(report::explain("lithium", &results, &module).iter().any(|l| l.contains("none in the folder")));
+    }
+
+    #[test]
+    fn legacy_liteloader_and_rift_jars_are_identified() {
+        let d = temp_dir("legacy-loaders");
+        let lite = d.join("voxelmap.jar");
+        write_jar(&lite, &[("litemod.json", r#"{"name": "voxelmap", "version": "1.7.10", "mcversion": "1.12.2", "author": "MamiyaOtaru"}"#)]);
+        let info = parse_jar(lite.to_str().unwrap()).unwrap().unwrap();
+        assert_eq!((info.mod_id.as_str(), info.loader, info.version.as_deref()), ("voxelmap", ModLoader::LiteLoader, Some("1.7.10")));
+        assert_eq!(info.metadata_file, "litemod.json");
+
+        let rift = d.join("rift-mod.jar");
+        write_jar(&rift, &[
+            ("riftmod.json", r#"{"id": "examplemod", "name": "Example", "authors": ["someone"], "listeners": ["com.example.Main"]}"#),
+            ("META-INF/MANIFEST.MF", "Manifest-Version: 1.0\nImplementation-Version: 2.1\n"),
+        ]);
+        let info = parse_jar(rift.to_str().unwrap()).unwrap().unwrap();
+        assert_eq!((info.mod_id.as_str(), info.loader), ("examplemod", ModLoader::Rift));
+        assert_eq!((info.version.as_deref(), info.version_source), (Some("2.1"), VersionSource::Manifest));
+        assert_eq!("liteloader".parse::<ModLoader>(), Ok(ModLoader::LiteLoader));
+    }
+
+    #[test]
+    fn operations_find_jars_with_unusual_names() {
+        let d = temp_dir("odd-names");
+        let mods = d.join("mods");
+        fs::create_dir_all(&mods).unwrap();
+        let mut names = vec![OsString::from("Sodium — ソディウム ü.jar")];
+        // Not valid UTF-8, so its display name is lossy
+        #[cfg(unix)]
+        names.push(std::os::unix::ffi::OsStringExt::from_vec(b"sodium-\xff.jar".to_vec()));
+        for n in &names {
+            write_jar(&mods.join(n), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        }
+        let module = module_with(&[("sodium", "*", Side::Client, ModLoader::Fabric)]);
+        let dir = mods.to_str().unwrap();
+        let (results, _) = scan_directory(dir, &module, &ScanOptions::default());
+        assert!(results.iter().all(|r| r.jar_info.is_some()), "every jar is opened under its real name");
+
+        let out = d.join("client");
+        let targets: Vec<&ScanResult> = results.iter().collect();
+        let n = apply_operation(Operation::Move, dir, &targets, out.to_str().unwrap(), false).unwrap();
+        assert_eq!(n, names.len());
+        for n in &names {
+            assert!(out.join(n).is_file() && !mods.join(n).exists());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unusual_names_come_back_from_undo_and_quarantine() {
+        let d = temp_dir("odd-undo");
+        let dir = d.to_str().unwrap();
+        let name: OsString = std::os::unix::ffi::OsStringExt::from_vec(b"sodium-\xff.jar".to_vec());
+        write_jar(&d.join(&name), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        let module = module_with(&[("sodium", "*", Side::Client, ModLoader::Fabric)]);
+        let client = OpFilter::side(Side::Client);
+
+        let (results, _) = scan_directory(dir, &module, &ScanOptions::default());
+        let (n, id) = run_recorded(Operation::Delete, dir, &results, client, "module.json", OpOptions { backup: true, ..Default::default() }).unwrap();
+        assert_eq!(n, 1);
+        assert!(!d.join(&name).exists());
+        assert_eq!(history::undo(dir, &id.unwrap()), Ok((1, Vec::new())));
+        assert!(d.join(&name).is_file());
+
+        let (results, _) = scan_directory(dir, &module, &ScanOptions::default());
+        run_recorded(Operation::Quarantine, dir, &results, client, "module.json", OpOptions { backup: true, ..Default::default() }).unwrap();
+        assert!(quarantine::quarantine_dir(dir).join(&name).is_file());
+        let (restored, skipped) = quarantine::restore(dir, &[], false).unwrap();
+        assert_eq!((restored.len(), skipped.len()), (1, 0));
+        assert!(d.join(&name).is_file());
+
+        let (results, _) = scan_directory(dir, &module, &ScanOptions::default());
+        let zip = temp_dir("odd-zip").join("client.zip");
+        let zip = zip.to_str().unwrap();
+        run_recorded(Operation::Zip, dir, &results, client, "module.json", OpOptions { output: zip, ..Default::default() }).unwrap();
+        fs::remove_file(d.join(&name)).unwrap();
+        assert_eq!(history::restore_from_backup(zip, dir, false), Ok((1, Vec::new())));
+        assert!(d.join(&name).is_file(), "restored under its exact name");
+    }
+
+    #[test]
+    fn module_diff_lists_added_removed_and_changed() {
+        let old = module_with(&[
+            ("create", "0.5", Side::Both,   ModLoader::Forge),
+            ("jei",    "*",   Side::Both,   ModLoader::Forge),
+            ("oculus", "1.6", Side::Client, ModLoader::Forge),
+        ]);
+        let new = module_with(&[
+            ("create", "0.6", Side::Server, ModLoader::Forge),
+            ("oculus", "1.6", Side::Client, ModLoader::Forge),
+            ("sodium", "0.5.8", Side::Client, ModLoader::Fabric),
+        ]);
+        let diff = diff_modules(&old, &new);
+        assert_eq!(diff.removed, vec!["jei"]);
+        assert_eq!(diff.added.iter().map(|(id, _)| id.as_str()).collect::<Vec<_>>(), vec!["sodium"]);
+        assert_eq!(diff.changelog(false), vec![
+            "Added (1):", "  sodium 0.5.8 (Client, Fabric)",
+            "Removed (1):", "  jei",
+            "Changed (1):", "  create: tag Both → Server, version 0.5 → 0.6",
+        ]);
+        assert_eq!(diff.changelog(true)[..2], ["### Added (1)", "- `sodium` 0.5.8 (Client, Fabric)"]);
+    }
+
+    #[test]
+    fn homepage_and_issue_links_are_read_from_metadata() {
+        let d = temp_dir("links");
+        write_jar(&d.join("create.jar"), &[("META-INF/mods.toml", "issueTrackerURL = \"https://github.com/Creators-of-Create/Create/issues\"\n[[mods]]\nmodId = \"create\"\ndisplayURL = \"https://createmod.net\"\n")]);
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", r#"{"id": "sodium", "version": "0.5.8", "contact": {"homepage": "https://modrinth.com/mod/sodium", "issues": "https://github.com/CaffeineMC/sodium/issues"}}"#)]);
+        write_jar(&d.join("bare.jar"), &[("fabric.mod.json", r#"{"id": "bare", "version": "1"}"#)]);
+
+        let create = parse_jar(d.join("create.jar")).unwrap().unwrap();
+        assert_eq!(create.links.homepage.as_deref(), Some("https://createmod.net"));
+        assert_eq!(create.links.issues.as_deref(), Some("https://github.com/Creators-of-Create/Create/issues"));
+        let sodium = parse_jar(d.join("sodium.jar")).unwrap().unwrap();
+        assert_eq!(sodium.links.homepage.as_deref(), Some("https://modrinth.com/mod/sodium"));
+
+        let (results, _) = scan_directory(d.to_str().unwrap(), &module_with(&[]), &ScanOptions::default());
+        let lines = report::links(&results);
+        assert_eq!(lines.len(), 5);
+        assert!(lines[1].starts_with("bare") && lines[1].contains('—'));
+        assert_eq!(lines[4], "2 of 3 mod(s) link a homepage or issue tracker.");
+    }
+
+    #[test]
+    fn limit_caps_how_many_jars_an_operation_touches() {
+        let d = temp_dir("limit");
+        let mods = d.join("mods");
+        fs::create_dir_all(&mods).unwrap();
+        for id in ["a", "b", "c"] {
+            write_jar(&mods.join(format!("{id}.jar")), &[("fabric.mod.json", &format!(r#"{{"id": "{id}", "version": "1"}}"#))]);
+        }
+        let module = module_with(&[("a", "*", Side::Client, ModLoader::Fabric), ("b", "*", Side::Client, ModLoader::Fabric), ("c", "*", Side::Client, ModLoader::Fabric)]);
+        let dir = mods.to_str().unwrap();
+        let (results, _) = scan_directory(dir, &module, &ScanOptions::default());
+        let filter = OpFilter { limit: Some(2), ..OpFilter::side(Side::Client) };
+        let out = d.join("client");
+        let (n, _) = run_recorded(Operation::Move, dir, &results, filter, "", OpOptions { output: out.to_str().unwrap(), backup: true, ..Default::default() }).unwrap();
+        assert_eq!(n, 2);
+        assert!(out.join("a.jar").is_file() && out.join("b.jar").is_file());
+        assert!(mods.join("c.jar").is_file(), "the third jar is past the limit");
+    }
+
+    #[test]
+    fn quarantine_parks_jars_out_of_the_scan() {
+        let d = temp_dir("quarantine-op");
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        let module = module_with(&[("sodium", "*", Side::Client, ModLoader::Fabric)]);
+        let dir = d.to_str().unwrap();
+        let (results, _) = scan_directory(dir, &module, &ScanOptions::default());
+        let (n, run) = run_recorded(Operation::Quarantine, dir, &results, OpFilter::side(Side::Client), "", OpOptions { backup: true, ..Default::default() }).unwrap();
+        assert_eq!(n, 1);
+        assert!(run.is_some(), "quarantining is recorded so undo works too");
+        assert!(quarantine::quarantine_dir(dir).join("sodium.jar").is_file());
+        let held = quarantine::load(dir).jars;
+        assert_eq!((held[0].mod_id.as_deref(), held[0].tag), (Some("sodium"), Side::Client));
+        assert_eq!(scan_directory(dir, &module, &ScanOptions::default()).1.total, 0);
+    }
+
+    #[test]
+    fn quarantining_a_name_twice_keeps_both_copies() {
+        let d = temp_dir("quarantine-twice");
+        let dir = d.to_str().unwrap();
+        let module = module_with(&[("sodium", "*", Side::Client, ModLoader::Fabric)]);
+        let client = OpFilter::side(Side::Client);
+        let mut runs = Vec::new();
+        for version in ["0.5.7", "0.5.8"] {
+            write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", &format!(r#"{{"id": "sodium", "version": "{version}"}}"#))]);
+            let (results, _) = scan_directory(dir, &module, &ScanOptions::default());
+            runs.push(run_recorded(Operation::Quarantine, dir, &results, client, "", OpOptions::default()).unwrap().1.unwrap());
+        }
+        let q = quarantine::quarantine_dir(dir);
+        assert!(q.join("sodium.jar").is_file() && q.join("sodium-1.jar").is_file());
+        let held = quarantine::load(dir).jars;
+        assert_eq!(held.iter().map(|e| e.held_file()).collect::<Vec<_>>(), ["sodium.jar", "sodium-1.jar"]);
+
+        // Undo brings back the second copy under its own name
+        assert_eq!(history::undo(dir, &runs[1]), Ok((1, Vec::new())));
+        assert_eq!(parse_jar(d.join("sodium.jar")).unwrap().unwrap().version.as_deref(), Some("0.5.8"));
+        fs::remove_file(d.join("sodium.jar")).unwrap();
+        assert_eq!(quarantine::restore(dir, &[], false), Ok((vec!["sodium.jar".to_string()], Vec::new())));
+        assert_eq!(parse_jar(d.join("sodium.jar")).unwrap().unwrap().version.as_deref(), Some("0.5.7"));
+    }
+
+    #[test]
+    fn join_plan_installs_updates_and_drops_server_mods() {
+        let d = temp_dir("join");
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", r#"{"id": "sodium", "version": "0.5.7"}"#)]);
+        write_jar(&d.join("chunky.jar"), &[("fabric.mod.json", r#"{"id": "chunky", "version": "1.4"}"#)]);
+        write_jar(&d.join("jei.jar"), &[("fabric.mod.json", r#"{"id": "JEI", "version": "15.2"}"#)]);
+        let module = module_with(&[
+            ("sodium",  "0.5.8", Side::Client, ModLoader::Fabric),
+            ("lithium", "*",     Side::Both,   ModLoader::Fabric),
+            ("jei",     "15.2",  Side::Both,   ModLoader::Fabric),
+            ("chunky",  "*",     Side::Server, ModLoader::Fabric),
+        ]);
+        let (client, _) = scan_directory(d.to_str().unwrap(), &module, &ScanOptions::default());
+        let plan = compare::join_plan(&client, &compare::wanted_from_module(&module));
+
+        assert_eq!(plan.install.iter().map(|w| w.id.as_str()).collect::<Vec<_>>(), ["lithium"]);
+        assert_eq!(plan.update.len(), 1);
+        assert_eq!((plan.update[0].0.id.as_str(), plan.update[0].1.as_deref()), ("sodium", Some("0.5.7")));
+        assert_eq!(plan.remove, [("chunky.jar".to_string(), "chunky".to_string())]);
+        assert_eq!(plan.ok, 1);
+    }
+
+    #[test]
+    fn scan_warnings_are_collected_and_serialised() {
+        let d = temp_dir("warnings");
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", r#"{"id": "sodium", "version": "0.5.8", "environment": "client"}"#)]);
+        fs::write(d.join("broken.jar"), "not a zip").unwrap();
+        let module = module_with(&[("sodium", "0.5.8", Side::Server, ModLoader::Fabric)]);
+        let (results, summary) = scan_directory(d.to_str().unwrap(), &module, &ScanOptions::default());
+
+        let warnings = scan_warnings(&results, &module);
+        let kinds: Vec<WarningKind> = warnings.iter().map(|w| w.kind).collect();
+        assert!(kinds.contains(&WarningKind::SideDisagreement), "{warnings:?}");
+        assert!(scan_warnings(&results, &Module::empty()).iter().any(|w| w.kind == WarningKind::EmptyModule));
+
+        let json = serde_json::to_value(report::SummaryRecord::new(&summary, &warnings)).unwrap();
+        assert_eq!(json["warnings"][0]["kind"], "side-disagreement");
+        assert_eq!(json["warnings"][0]["jar"], "sodium.jar");
+        assert!(serde_json::to_value(report::SummaryRecord::new(&summary, &[])).unwrap().get("warnings").is_none());
+    }
+
+    #[test]
+    fn add_unknowns_tags_only_unlisted_mods() {
+        let d = temp_dir("add-unknowns");
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        write_jar(&d.join("lithium.jar"), &[("fabric.mod.json", r#"{"id": "lithium", "version": "0.12.1"}"#)]);
+        write_jar(&d.join("lithium-copy.jar"), &[("fabric.mod.json", r#"{"id": "lithium", "version": "0.12.1"}"#)]);
+        let mut module = module_with(&[("sodium", "0.5.8", Side::Client, ModLoader::Fabric)]);
+        let (results, _) = scan_directory(d.to_str().unwrap(), &module, &ScanOptions::default());
+
+        assert_eq!(module.add_unknowns(&results, Side::Both), ["lithium"]);
+        assert_eq!(module.mods["lithium"].mod_tag, Side::Both);
+        assert_eq!(module.mods["lithium"].mod_version, "0.12.1");
+        assert_eq!(module.mods["sodium"].mod_tag, Side::Client, "listed mods are left alone");
+        assert!(module.add_unknowns(&results, Side::Unknown).is_empty());
+    }
+
+    #[test]
+    fn misplaced_finds_packs_in_mods_and_mods_in_packs() {
+        let d = temp_dir("misplaced");
+        let game = d.join(".minecraft");
+        for f in ["mods", "resourcepacks", "shaderpacks"] {
+            fs::create_dir_all(game.join(f)).unwrap();
+        }
+        write_jar(&game.join("mods/faithful.zip"), &[("pack.mcmeta", "{}"), ("assets/minecraft/x.png", "")]);
+        write_jar(&game.join("mods/jei.jar"), &[("META-INF/mods.toml", "[[mods]]\nmodId=\"jei\"\nversion=\"1\""), ("pack.mcmeta", "{}")]);
+        write_jar(&game.join("resourcepacks/sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        write_jar(&game.join("resourcepacks/bsl.zip"), &[("shaders/composite.fsh", "")]);
+        write_jar(&game.join("shaderpacks/complementary.zip"), &[("shaders/final.fsh", "")]);
+
+        let found = placement::check(d.to_str().unwrap());
+        let summary: Vec<(String, String)> = found.iter()
+            .map(|m| (m.file.file_name().unwrap().to_string_lossy().into_owned(), m.move_to.file_name().unwrap().to_string_lossy().into_owned()))
+            .collect();
+        assert_eq!(summary, [
+            ("faithful.zip".to_string(), "resourcepacks".to_string()),
+            ("bsl.zip".to_string(), "shaderpacks".to_string()),
+            ("sodium.jar".to_string(), "mods".to_string()),
+        ]);
+        assert_eq!(found[2].content, placement::Content::Mod("sodium".into()));
+    }
+
+    #[test]
+    fn protected_mods_survive_a_matching_delete() {
+        let d = temp_dir("protected");
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        write_jar(&d.join("core.jar"), &[("fabric.mod.json", r#"{"id": "core", "version": "1.0"}"#)]);
+        let dir = d.to_str().unwrap();
+        let mut module = module_with(&[
+            ("sodium", "0.5.8", Side::Client, ModLoader::Fabric),
+            ("core",   "1.0",   Side::Client, ModLoader::Fabric),
+        ]);
+        module.mods.get_mut("core").unwrap().protected = true;
+        let (results, _) = scan_directory(dir, &module, &ScanOptions::default());
+
+        let filter = OpFilter::side(Side::Client);
+        assert_eq!(protected_targets(Operation::Delete, &results, filter).len(), 1);
+        assert!(protected_targets(Operation::Zip, &results, filter).is_empty(), "only removals skip protected mods");
+        assert_eq!(op_targets(Operation::Copy, &results, filter).len(), 2);
+
+        let n = run_operation(Operation::Delete, dir, &results, filter, "", false).unwrap();
+        assert_eq!(n, 1);
+        assert!(!d.join("sodium.jar").exists());
+        assert!(d.join("core.jar").is_file());
+        assert!(!serde_json::to_string(&module.mods["sodium"]).unwrap().contains("protected"));
+    }
+
+    #[test]
+    fn footprint_counts_what_a_delete_freed() {
+        let d = temp_dir("footprint");
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        write_jar(&d.join("lithium.jar"), &[("fabric.mod.json", r#"{"id": "lithium", "version": "0.12.1"}"#)]);
+        let dir = d.to_str().unwrap();
+        let module = module_with(&[("sodium", "0.5.8", Side::Client, ModLoader::Fabric)]);
+        let (results, _) = scan_directory(dir, &module, &ScanOptions::default());
+        let filter = OpFilter::side(Side::Client);
+
+        let footprint = stats::Footprint::of(dir, &op_targets(Operation::Delete, &results, filter));
+        let size = fs::metadata(d.join("sodium.jar")).unwrap().len();
+        assert_eq!((footprint.total(), footprint.gone()), (size, 0));
+        run_operation(Operation::Delete, dir, &results, filter, "", false).unwrap();
+        assert_eq!(footprint.gone(), size);
+    }
+
+    #[test]
+    fn resource_jars_are_checked_against_the_instance_version() {
+        let d = temp_dir("packformat");
+        let mods = d.join("mods");
+        fs::create_dir_all(&mods).unwrap();
+        fs::write(d.join("mmc-pack.json"), r#"{"components": [{"uid": "net.minecraft", "version": "1.20.1"}]}"#).unwrap();
+        let old = r#"{"pack": {"pack_format": 8, "description": "old textures"}}"#;
+        write_jar(&mods.join("textures.jar"), &[("pack.mcmeta", old)]);
+        write_jar(&mods.join("current.jar"), &[("pack.mcmeta", r#"{"pack": {"pack_format": 15}}"#)]);
+        write_jar(&mods.join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM), ("pack.mcmeta", old)]);
+        let dir = mods.to_str().unwrap();
+        let (results, _) = scan_directory(dir, &Module::empty(), &ScanOptions::default());
+
+        let mc = packformat::instance_minecraft(dir).unwrap();
+        assert_eq!(mc, "1.20.1");
+        let warnings = packformat::mismatches(dir, &results, &mc);
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert_eq!(warnings[0].jar.as_deref(), Some("textures.jar"));
+        assert!(warnings[0].message.contains("Minecraft 1.18–1.18.2"));
+    }
+
+    #[test]
+    fn rename_on_move_keeps_both_jars_and_undo_finds_the_new_name() {
+        let d = temp_dir("rename-on-move");
+        let (src, out) = (d.join("mods"), d.join("merged"));
+        fs::create_dir_all(&src).unwrap();
+        fs::create_dir_all(&out).unwrap();
+        write_jar(&src.join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        fs::write(out.join("sodium.jar"), "already here").unwrap();
+        fs::write(out.join("sodium-1.jar"), "and here").unwrap();
+        let dir = src.to_str().unwrap();
+        let module = module_with(&[("sodium", "0.5.8", Side::Client, ModLoader::Fabric)]);
+        let (results, _) = scan_directory(dir, &module, &ScanOptions::default());
+
+        let (n, id) = run_recorded(Operation::Move, dir, &results, OpFilter::side(Side::Client), "", OpOptions { output: out.to_str().unwrap(), backup: true, rename: true, ..Default::default() }).unwrap();
+        assert_eq!(n, 1);
+        assert_eq!(fs::read_to_string(out.join("sodium.jar")).unwrap(), "already here");
+        assert!(out.join("sodium-2.jar").is_file());
+        let run = history::list(dir).into_iter().find(|r| Some(&r.id) == id.as_ref()).unwrap();
+        assert_eq!(run.renamed(), [("sodium.jar", "sodium-2.jar".to_string())]);
+
+        history::undo(dir, &run.id).unwrap();
+        assert!(src.join("sodium.jar").is_file() && !out.join("sodium-2.jar").exists());
+    }
+
+    #[test]
+    fn report_to_a_md_file_is_a_markdown_table() {
+        let d = temp_dir("report-md");
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        write_jar(&d.join("odd|name.jar"), &[("fabric.mod.json", r#"{"id": "odd", "version": "1"}"#)]);
+        let dir = d.to_str().unwrap();
+        let module = module_with(&[("sodium", "0.5.8", Side::Client, ModLoader::Fabric)]);
+        let (results, _) = scan_directory(dir, &module, &ScanOptions::default());
+        let out = std::env::temp_dir().join(format!("lodestone-report-{}.md", std::process::id()));
+
+        run_operation(Operation::Report, dir, &results, OpFilter::side(Side::Client), out.to_str().unwrap(), false).unwrap();
+        let table = fs::read_to_string(&out).unwrap();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "| Jar | Mod id | Loader | Version | Module tag | Match |");
+        assert!(lines.contains(&"| sodium.jar | sodium | Fabric | 0.5.8 | Client | Full match |"), "{table}");
+        assert!(table.contains("| odd\\|name.jar | odd |"), "{table}");
+        assert_eq!("markdown".parse::<report::ReportFormat>(), Ok(report::ReportFormat::Markdown));
+        assert_eq!(report::ReportFormat::for_output("pack.txt"), report::ReportFormat::Text);
+    }
+
+    #[test]
+    fn changed_only_scans_what_moved_since_the_record() {
+        let d = temp_dir("changed-only");
+        let dir = d.to_str().unwrap();
+        write_jar(&d.join("a.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        write_jar(&d.join("b.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        let opts = ScanOptions { changed_only: true, ..Default::default() };
+        let (all, _) = scan_directory(dir, &Module::empty(), &opts);
+        assert_eq!(all.len(), 2, "no record yet, so every jar");
+
+        let mut record = lastscan::LastScan::default();
+        record.update(dir, &all);
+        record.save(dir).unwrap();
+        assert!(scan_directory(dir, &Module::empty(), &opts).0.is_empty());
+        std::fs::write(d.join("b.jar"), b"replaced").unwrap();
+        write_jar(&d.join("c.jar"), &[("fabric.mod.json", FABRIC_SODIUM)]);
+        let (changed, _) = scan_directory(dir, &Module::empty(), &opts);
+        assert_eq!(changed.iter().map(|r| r.jar_name.as_str()).collect::<Vec<_>>(), ["b.jar", "c.jar"]);
+    }
+
+    #[test]
+    fn declared_breaks_pair_up_installed_mods() {
+        let d = temp_dir("breaks");
+        let dir = d.to_str().unwrap();
+        write_jar(&d.join("sodium.jar"), &[("fabric.mod.json",
+            r#"{"id": "sodium", "version": "0.5.3", "breaks": {"optifabric": "*"}, "conflicts": {"iris": "<1.6"}}"#)]);
+        write_jar(&d.join("optifabric.jar"), &[("fabric.mod.json", r#"{"id": "optifabric", "version": "1.13"}"#)]);
+        write_jar(&d.join("iris.jar"), &[("fabric.mod.json", r#"{"id": "iris", "version": "1.7.0"}"#)]);
+        write_jar(&d.join("old.jar"), &[("META-INF/mods.toml",
+            "[[mods]]\nmodId = \"old\"\nversion = \"1\"\n[[dependencies.old]]\nmodId = \"iris\"\ntype = \"incompatible\"\nversionRange = \"[1.0,1.2),[1.5,)\"\n")]);
+        let (results, _) = scan_directory(dir, &Module::empty(), &ScanOptions::default());
+        let old = results.iter().find(|r| r.jar_name == "old.jar").and_then(|r| r.jar_info.as_ref()).unwrap();
+        assert!(old.dependencies.is_empty(), "incompatible entries aren't dependencies");
+
+        let clashes = incompat::clashes(&results);
+        let pairs: Vec<(&str, &str, bool)> = clashes.iter()
+            .map(|c| (c.by.mod_id.as_str(), c.with.mod_id.as_str(), c.checked))
+            .collect();
+        assert_eq!(pairs, [("old", "iris", false), ("sodium", "optifabric", true)]);
+    }
+
+    #[test]
+    fn modpack_marks_entries_by_slug_then_id() {
+        let mut module = module_with(&[
+            ("sodium", "0.5", Side::Client, ModLoader::Fabric),
+            ("create", "0.5", Side::Both, ModLoader::Fabric),
+            ("jei", "15", Side::Client, ModLoader::Fabric),
+        ]);
+        module.mods.get_mut("create").unwrap().modrinth = Some("create-fabric".into());
+        let slugs = ["Sodium".to_string(), "create-fabric".into(), "lithium".into()];
+        let (marked, unmatched) = module.mark_pack("fo", &slugs);
+        assert_eq!(marked, ["create", "sodium"]);
+        assert_eq!(unmatched, ["lithium"]);
+        assert!(module.mark_pack("fo", &slugs).0.is_empty(), "already marked");
+        assert!(module.mods["jei"].packs.is_empty());
+    }
+}
@@ -0,0 +1,122 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// mapping.rs — A saved jar → mod id mapping, so a stable folder needn't be reopened
+//
+// `lodestone mapping <dir>` writes `<dir>/.lodestone/mapping.json` with what
+// each identified jar turned out to be.  `scan --mapping` then takes jars
+// from that file instead of opening them, the way `--curseforge` does with
+// an instance manifest.  Only the id, loader, version and declared side are
+// kept; bytecode and signature checks are skipped for mapped jars.
+//
+// The file records how many jars the folder had, and with `--hash` each
+// jar's SHA-256.  A jar whose hash no longer matches is opened as usual.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{hash, pool, JarInfo, Links, ModLoader, ScanResult, Side, VersionSource};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Mapping {
+    /// Jars in the folder when the mapping was written, identified or not
+    pub jar_count: usize,
+    pub jars:      BTreeMap<String, MappedJar>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MappedJar {
+    pub mod_id:  String,
+    #[serde(rename = "type")]
+    pub loader:  ModLoader,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub declared_side: Option<Side>,
+    /// Only written with `--hash`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256:  Option<String>,
+}
+
+pub fn mapping_path(dir: &str) -> PathBuf {
+    Path::new(dir).join(".lodestone").join("mapping.json")
+}
+
+/// The mapping for a finished scan of `dir`.  `hash` records a SHA-256 per
+/// jar so later scans can tell when one was replaced under the same name;
+/// the jars are hashed `jobs` at a time (see `pool.rs`).
+pub fn build(dir: &str, results: &[ScanResult], hash: bool, jobs: usize) -> Mapping {
+    let hashes = if hash {
+        pool::map(results, jobs, |_| false, |r| hash::sha256(&Path::new(dir).join(&r.file_name)).ok())
+    } else {
+        Vec::new()
+    };
+    let jars = results.iter().enumerate()
+        .filter_map(|(i, r)| {
+            let info = r.jar_info.as_ref()?;
+            let sha256 = hashes.get(i).cloned().flatten();
+            Some((r.jar_name.clone(), MappedJar {
+                mod_id:  info.mod_id.clone(),
+                loader:  info.loader,
+                version: info.version.clone(),
+                declared_side: info.declared_side,
+                sha256,
+            }))
+        })
+        .collect();
+    Mapping { jar_count: results.len(), jars }
+}
+
+impl Mapping {
+    pub fn save(&self, dir: &str) -> std::io::Result<PathBuf> {
+        let path = mapping_path(dir);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+
+    /// Why the mapping no longer describes a folder holding `jars`, if it doesn't.
+    pub fn staleness(&self, jars: &[String]) -> Option<String> {
+        let gone = self.jars.keys().filter(|name| !jars.contains(name)).count();
+        if gone > 0 {
+            Some(format!("{gone} mapped jar(s) are no longer in the folder"))
+        } else if jars.len() != self.jar_count {
+            Some(format!("the folder had {} jar(s) when mapped and has {} now", self.jar_count, jars.len()))
+        } else {
+            None
+        }
+    }
+
+    /// The recorded identification of `jar` in `dir`, unless it was never
+    /// mapped or its hash has changed since.
+    pub fn lookup(&self, dir: &str, jar: &str) -> Option<JarInfo> {
+        let m = self.jars.get(jar)?;
+        if let Some(want) = &m.sha256 {
+            if hash::sha256(&Path::new(dir).join(jar)).ok().as_ref() != Some(want) {
+                return None;
+            }
+        }
+        Some(JarInfo {
+            mod_id:        m.mod_id.clone(),
+            loader:        m.loader,
+            version:       m.version.clone(),
+            declared_side: m.declared_side,
+            dependencies:  Vec::new(),
+            breaks:        Vec::new(),
+            version_source: VersionSource::Metadata,
+            icon:          None,
+            metadata_file: ".lodestone/mapping.json".into(),
+            mixin_only:    false,
+            bundled:       Vec::new(),
+            links:         Links::default(),
+        })
+    }
+}
+
+/// The mapping saved in `dir`, if there is one that parses.
+pub fn load(dir: &str) -> Option<Mapping> {
+    serde_json::from_str(&std::fs::read_to_string(mapping_path(dir)).ok()?).ok()
+}
@@ -0,0 +1,143 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// metadata.rs — Which files inside a jar hold its loader metadata
+//
+// Detection tries the patterns in order against all of the jar's entry
+// names and hands the first entry matching the earliest pattern to that
+// pattern's parser, so the order here decides, not the order in the zip.  The built-in formats are the
+// defaults; a `lodestone-metadata.json` next to the executable or in the
+// working directory adds more without recompiling:
+//
+//   [ { "pattern": "META-INF/neoforge.mods.toml", "parser": "mods_toml", "loader": "NeoForge" } ]
+//
+// User patterns are tried before the defaults.  `loader` is optional and
+// overrides whatever the parser would have reported.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use crate::ModLoader;
+
+const CONFIG_FILE: &str = "lodestone-metadata.json";
+
+/// The metadata formats Lodestone can read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Parser {
+    /// Forge / NeoForge `mods.toml`
+    ModsToml,
+    FabricModJson,
+    QuiltModJson,
+    /// Pre-1.13 Forge `mcmod.info`
+    McmodInfo,
+    /// LiteLoader `litemod.json`
+    LitemodJson,
+    /// Rift `riftmod.json`
+    RiftmodJson,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Pattern {
+    /// Glob over the full entry name; `*` matches any run of characters
+    /// (including `/`), `?` exactly one
+    pub pattern: String,
+    pub parser:  Parser,
+    #[serde(default)]
+    pub loader:  Option<ModLoader>,
+}
+
+impl Pattern {
+    fn new(pattern: &str, parser: Parser) -> Self {
+        Self { pattern: pattern.to_string(), parser, loader: None }
+    }
+}
+
+pub fn defaults() -> Vec<Pattern> {
+    vec![
+        Pattern::new("*mods.toml", Parser::ModsToml),
+        Pattern::new("*fabric.mod.json", Parser::FabricModJson),
+        Pattern::new("*quilt.mod.json", Parser::QuiltModJson),
+        Pattern::new("*mcmod.info", Parser::McmodInfo),
+        Pattern::new("litemod.json", Parser::LitemodJson),
+        Pattern::new("riftmod.json", Parser::RiftmodJson),
+    ]
+}
+
+/// User patterns followed by the defaults, loaded once per process.
+pub fn patterns() -> &'static [Pattern] {
+    static PATTERNS: OnceLock<Vec<Pattern>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        let mut all = load_config().unwrap_or_default();
+        all.extend(defaults());
+        all
+    })
+}
+
+fn load_config() -> Option<Vec<Pattern>> {
+    let exe_dir = std::env::current_exe().ok().and_then(|p| p.parent().map(PathBuf::from));
+    let raw = exe_dir.into_iter().chain([PathBuf::from(".")])
+        .find_map(|d| std::fs::read_to_string(d.join(CONFIG_FILE)).ok())?;
+    match serde_json::from_str(&raw) {
+        Ok(p) => Some(p),
+        Err(e) => {
+            eprintln!("warning: ignoring {CONFIG_FILE}: {e}");
+            None
+        }
+    }
+}
+
+/// The entry `patterns` rank first among `names`: the first name matching
+/// the earliest pattern that matches any, with that pattern.
+pub fn best<'a, 'n>(patterns: &'a [Pattern], names: &[&'n str]) -> Option<(&'a Pattern, &'n str)> {
+    patterns.iter().find_map(|p| names.iter().find(|n| glob_match(&p.pattern, n)).map(|n| (p, *n)))
+}
+
+/// `*` / `?` glob match over the whole of `name`.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let (p, n): (Vec<char>, Vec<char>) = (pattern.chars().collect(), name.chars().collect());
+    let (mut pi, mut ni) = (0, 0);
+    // Position of the last `*` and the name index it was tried at, for backtracking
+    let mut star: Option<(usize, usize)> = None;
+    while ni < n.len() {
+        match p.get(pi) {
+            Some('*') => { star = Some((pi, ni)); pi += 1; }
+            Some(&c) if c == '?' || c == n[ni] => { pi += 1; ni += 1; }
+            _ => match star {
+                Some((sp, sn)) => { pi = sp + 1; ni = sn + 1; star = Some((sp, sn + 1)); }
+                None => return false,
+            },
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Unit tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn globs_match_whole_names() {
+        assert!(glob_match("*mods.toml", "META-INF/mods.toml"));
+        assert!(glob_match("*mods.toml", "META-INF/neoforge.mods.toml"));
+        assert!(glob_match("META-INF/?.toml", "META-INF/a.toml"));
+        assert!(!glob_match("*mods.toml", "META-INF/mods.toml.bak"));
+        assert!(!glob_match("mods.toml", "META-INF/mods.toml"));
+    }
+
+    #[test]
+    fn user_patterns_take_priority() {
+        let user: Vec<Pattern> = serde_json::from_str(
+            r#"[{ "pattern": "META-INF/neoforge.mods.toml", "parser": "mods_toml", "loader": "NeoForge" }]"#,
+        ).unwrap();
+        let all: Vec<Pattern> = user.into_iter().chain(defaults()).collect();
+        assert_eq!(best(&all, &["META-INF/neoforge.mods.toml"]).unwrap().0.loader, Some(ModLoader::NeoForge));
+        assert_eq!(best(&all, &["META-INF/mods.toml"]).unwrap().0.loader, None);
+        assert!(best(&all, &["pack.mcmeta"]).is_none());
+    }
+}
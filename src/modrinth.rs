@@ -0,0 +1,289 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// modrinth.rs — Modrinth modpack (`.mrpack`) interop
+//
+// An `.mrpack` is a zip whose `modrinth.index.json` lists every file with its
+// hashes, size, download URLs and per-side `env` requirements.  We can't know
+// download URLs, but everything else follows from a scan: hashes and sizes
+// from the jars, `env` from each jar's side.
+//
+// `lodestone slugs` also looks module ids up on the Modrinth API to find each
+// mod's project slug.  Lodestone has no HTTP client of its own, so requests go
+// through the system `curl`, spaced out to stay within Modrinth's rate limit,
+// and answers (including "not found") are cached next to the module.
+//
+// `lodestone pack-tag` goes the other way: it reads a modpack's index (a
+// local `.mrpack`, or the latest version of a Modrinth modpack project),
+// turns the project ids in its download URLs into slugs, and marks the
+// module entries with those slugs as part of the pack.  CurseForge packs
+// need an API key to resolve, so only Modrinth's are read.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::{hash, ModLoader, ScanResult, Side};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Index {
+    pub format_version: u32,
+    pub game:           &'static str,
+    pub version_id:     String,
+    pub name:           String,
+    pub files:          Vec<IndexFile>,
+    /// "minecraft" and the loader, mapped to versions — only filled in from
+    /// what the caller tells us, since jars don't record them reliably
+    pub dependencies:   BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexFile {
+    pub path:      String,
+    pub hashes:    BTreeMap<&'static str, String>,
+    pub env:       Env,
+    pub downloads: Vec<String>,
+    pub file_size: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Env {
+    pub client: &'static str,
+    pub server: &'static str,
+}
+
+impl Env {
+    fn for_side(side: Side) -> Self {
+        let (client, server) = match side {
+            Side::Client  => ("required",    "unsupported"),
+            Side::Server  => ("unsupported", "required"),
+            Side::Both    => ("required",    "required"),
+            Side::Unknown => ("optional",    "optional"),
+        };
+        Self { client, server }
+    }
+}
+
+/// The dependency key Modrinth uses for a loader.
+pub fn loader_key(loader: ModLoader) -> Option<&'static str> {
+    match loader {
+        ModLoader::Forge    => Some("forge"),
+        ModLoader::NeoForge => Some("neoforge"),
+        ModLoader::Fabric   => Some("fabric-loader"),
+        ModLoader::Quilt    => Some("quilt-loader"),
+        // .mrpack has no dependency key for the legacy loaders
+        ModLoader::LiteLoader | ModLoader::Rift | ModLoader::Unknown => None,
+    }
+}
+
+/// Build an index for the jars in `dir`.  Paths are relative to the pack root,
+/// so every file lives under `mods/`.
+pub fn build_index(
+    dir: &str, results: &[ScanResult], name: String, version_id: String,
+    dependencies: BTreeMap<String, String>,
+) -> std::io::Result<Index> {
+    let mut files = Vec::with_capacity(results.len());
+    for r in results {
+        let p = Path::new(dir).join(&r.file_name);
+        let hashes = BTreeMap::from([("sha1", hash::sha1(&p)?), ("sha512", hash::sha512(&p)?)]);
+        files.push(IndexFile {
+            path: format!("mods/{}", r.jar_name),
+            hashes,
+            env: Env::for_side(r.effective_side()),
+            downloads: Vec::new(),
+            file_size: std::fs::metadata(&p)?.len(),
+        });
+    }
+    Ok(Index { format_version: 1, game: "minecraft", version_id, name, files, dependencies })
+}
+
+// ── Slug lookup ───────────────────────────────────────────────────────────────
+
+const API: &str = "https://api.modrinth.com/v2";
+/// Modrinth asks for an identifying user agent
+const USER_AGENT: &str = concat!("lodestone/", env!("CARGO_PKG_VERSION"));
+/// Modrinth allows 300 requests a minute; this stays well under it
+pub const REQUEST_GAP: Duration = Duration::from_millis(250);
+
+/// Mod id → slug answers already fetched; `None` records "no project".
+pub type SlugCache = BTreeMap<String, Option<String>>;
+
+/// The cache file for the module at `module_path`: `.lodestone-slugs.json`
+/// in the same folder.
+pub fn cache_path(module_path: &str) -> PathBuf {
+    Path::new(module_path).parent().unwrap_or(Path::new(".")).join(".lodestone-slugs.json")
+}
+
+pub fn load_cache(module_path: &str) -> SlugCache {
+    std::fs::read_to_string(cache_path(module_path)).ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_cache(module_path: &str, cache: &SlugCache) -> std::io::Result<()> {
+    std::fs::write(cache_path(module_path), serde_json::to_string_pretty(cache)?)
+}
+
+/// The Modrinth slug for mod id `id`: the project of that id or slug if there
+/// is one, otherwise a search hit whose slug or title spells the id.  Makes
+/// up to two requests, [`REQUEST_GAP`] apart.
+pub fn lookup_slug(id: &str) -> Result<Option<String>, String> {
+    if let Some(project) = get_json(&format!("{API}/project/{}", encode(id)))? {
+        return Ok(project.get("slug").and_then(|s| s.as_str()).map(String::from));
+    }
+    std::thread::sleep(REQUEST_GAP);
+    let found = get_json(&format!("{API}/search?limit=5&query={}", encode(id)))?;
+    Ok(found.and_then(|f| best_hit(id, &f)))
+}
+
+/// The first search hit that is plausibly `id` itself rather than a mod
+/// that merely mentions it.
+fn best_hit(id: &str, search: &serde_json::Value) -> Option<String> {
+    let key = |s: &str| s.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_ascii_lowercase();
+    let want = key(id);
+    search.get("hits")?.as_array()?.iter()
+        .find(|h| ["slug", "title"].iter().any(|f| h.get(f).and_then(|v| v.as_str()).is_some_and(|v| key(v) == want)))
+        .and_then(|h| h.get("slug")?.as_str().map(String::from))
+}
+
+/// GET `url` as JSON.  A 404 is `None`; any other failure is an error.
+fn get_json(url: &str) -> Result<Option<serde_json::Value>, String> {
+    let out = Command::new("curl")
+        .args(["--silent", "--show-error", "--user-agent", USER_AGENT, "--write-out", "\n%{http_code}", url])
+        .output()
+        .map_err(|e| format!("running curl: {e}"))?;
+    if !out.status.success() {
+        return Err(format!("curl: {}", String::from_utf8_lossy(&out.stderr).trim()));
+    }
+    let body = String::from_utf8_lossy(&out.stdout);
+    let (body, status) = body.rsplit_once('\n').unwrap_or(("", &body));
+    match status.trim() {
+        "200" => serde_json::from_str(body).map(Some).map_err(|e| format!("{url}: {e}")),
+        "404" => Ok(None),
+        "429" => Err("Modrinth is rate limiting requests; try again in a minute".into()),
+        code  => Err(format!("{url}: HTTP {code}")),
+    }
+}
+
+/// Percent-encode everything but unreserved URL characters.
+fn encode(s: &str) -> String {
+    s.bytes().map(|b| match b {
+        b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+        _ => format!("%{b:02X}"),
+    }).collect()
+}
+
+// ── Packs ────────────────────────────────────────────────────────────────────
+
+/// The Modrinth project ids of the files an `.mrpack` index lists, read from
+/// their `cdn.modrinth.com/data/<project>/versions/…` download URLs.  Files
+/// hosted anywhere else are skipped.
+pub fn index_projects(index: &serde_json::Value) -> Vec<String> {
+    let mut ids: Vec<String> = index.get("files").and_then(|f| f.as_array()).into_iter().flatten()
+        .filter_map(|f| f.get("downloads")?.as_array()?.iter().find_map(|u| {
+            let rest = u.as_str()?.strip_prefix("https://cdn.modrinth.com/data/")?;
+            rest.split('/').next().filter(|id| !id.is_empty()).map(String::from)
+        }))
+        .collect();
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+/// The name and project ids of the `.mrpack` at `path`.
+pub fn read_pack(path: &Path) -> Result<(String, Vec<String>), String> {
+    let what = |e: &dyn std::fmt::Display| format!("{}: {e}", path.display());
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(path).map_err(|e| what(&e))?).map_err(|e| what(&e))?;
+    let mut raw = String::new();
+    std::io::Read::read_to_string(&mut archive.by_name("modrinth.index.json").map_err(|e| what(&e))?, &mut raw)
+        .map_err(|e| what(&e))?;
+    let index: serde_json::Value = serde_json::from_str(&raw).map_err(|e| what(&e))?;
+    let name = index.get("name").and_then(|n| n.as_str()).unwrap_or_default().to_string();
+    Ok((name, index_projects(&index)))
+}
+
+/// The latest version of the Modrinth modpack `project` (id or slug):
+/// its primary file is downloaded to a temporary file and read with
+/// [`read_pack`].  The name is the project's title.
+pub fn fetch_pack(project: &str) -> Result<(String, Vec<String>), String> {
+    let info = get_json(&format!("{API}/project/{}", encode(project)))?
+        .ok_or_else(|| format!("no Modrinth project '{project}'"))?;
+    if info.get("project_type").and_then(|t| t.as_str()) != Some("modpack") {
+        return Err(format!("{project} is not a modpack on Modrinth"));
+    }
+    std::thread::sleep(REQUEST_GAP);
+    let versions = get_json(&format!("{API}/project/{}/version", encode(project)))?.unwrap_or_default();
+    let files = versions.get(0).and_then(|v| v.get("files")).and_then(|f| f.as_array())
+        .ok_or_else(|| format!("{project} has no published versions"))?;
+    let file = files.iter().find(|f| f.get("primary").and_then(|p| p.as_bool()) == Some(true)).or(files.first());
+    let url = file.and_then(|f| f.get("url")).and_then(|u| u.as_str())
+        .ok_or_else(|| format!("{project}'s latest version has no file"))?;
+
+    let tmp = std::env::temp_dir().join(format!("lodestone-pack-{}.mrpack", std::process::id()));
+    let out = Command::new("curl")
+        .args(["--silent", "--show-error", "--location", "--fail", "--user-agent", USER_AGENT])
+        .arg("--output").arg(&tmp).arg(url)
+        .output()
+        .map_err(|e| format!("running curl: {e}"))?;
+    let read = if out.status.success() {
+        read_pack(&tmp)
+    } else {
+        Err(format!("downloading {url}: {}", String::from_utf8_lossy(&out.stderr).trim()))
+    };
+    let _ = std::fs::remove_file(&tmp);
+    let (_, ids) = read?;
+    let title = info.get("title").and_then(|t| t.as_str()).unwrap_or(project).to_string();
+    Ok((title, ids))
+}
+
+/// The slugs of Modrinth projects `ids`, in batches of 100, [`REQUEST_GAP`]
+/// apart.  Ids Modrinth doesn't know are left out.
+pub fn project_slugs(ids: &[String]) -> Result<Vec<String>, String> {
+    let mut slugs = Vec::new();
+    for (i, batch) in ids.chunks(100).enumerate() {
+        if i > 0 { std::thread::sleep(REQUEST_GAP); }
+        let list = serde_json::to_string(batch).map_err(|e| e.to_string())?;
+        let found = get_json(&format!("{API}/projects?ids={}", encode(&list)))?.unwrap_or_default();
+        slugs.extend(found.as_array().into_iter().flatten()
+            .filter_map(|p| p.get("slug")?.as_str().map(String::from)));
+    }
+    Ok(slugs)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Unit tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_hits_must_spell_the_id() {
+        let search = serde_json::json!({"hits": [
+            {"slug": "create-addition", "title": "Create Crafts & Additions"},
+            {"slug": "create", "title": "Create"},
+        ]});
+        assert_eq!(best_hit("create", &search).as_deref(), Some("create"));
+        let search = serde_json::json!({"hits": [{"slug": "jei-addon", "title": "JEI Addon"}]});
+        assert_eq!(best_hit("jei", &search), None);
+        let search = serde_json::json!({"hits": [{"slug": "cloth-config", "title": "Cloth Config API"}]});
+        assert_eq!(best_hit("cloth_config", &search).as_deref(), Some("cloth-config"));
+        assert_eq!(encode("a b/c"), "a%20b%2Fc");
+    }
+
+    #[test]
+    fn pack_index_yields_modrinth_projects_only() {
+        let index = serde_json::json!({"name": "Pack", "files": [
+            {"path": "mods/sodium.jar", "downloads": ["https://cdn.modrinth.com/data/AANobbMI/versions/abc/sodium.jar"]},
+            {"path": "mods/iris.jar", "downloads": ["https://cdn.modrinth.com/data/YL57xq9U/versions/def/iris.jar"]},
+            {"path": "mods/again.jar", "downloads": ["https://cdn.modrinth.com/data/AANobbMI/versions/old/sodium.jar"]},
+            {"path": "mods/cf.jar", "downloads": ["https://edge.forgecdn.net/files/1/2/cf.jar"]},
+        ]});
+        assert_eq!(index_projects(&index), ["AANobbMI", "YL57xq9U"]);
+    }
+}
@@ -0,0 +1,158 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// packformat.rs — Resource packs in the mods folder, checked against the game
+//
+// A jar with no mod metadata but a `pack.mcmeta` is resource content, and
+// its `pack_format` says which Minecraft versions it was made for:
+//
+//   { "pack": { "pack_format": 15, "supported_formats": [15, 18] } }
+//
+// When the instance's Minecraft version is known — `scan --minecraft`, or
+// read from the launcher's `minecraftinstance.json` (CurseForge) or
+// `mmc-pack.json` (MultiMC, Prism) — each such jar whose format the game
+// doesn't use gets a warning.  Mod jars carry a `pack.mcmeta` too, often
+// stale, and the loader ignores it, so they are not checked.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::io::Read;
+use std::path::Path;
+
+use crate::version::{compare_parts, numeric_parts};
+use crate::{ScanResult, Warning, WarningKind};
+
+/// Resource pack formats and the Minecraft releases that use them, first
+/// to last.
+const RESOURCE_FORMATS: &[(u32, &str, &str)] = &[
+    (1,  "1.6.1",  "1.8.9"),
+    (2,  "1.9",    "1.10.2"),
+    (3,  "1.11",   "1.12.2"),
+    (4,  "1.13",   "1.14.4"),
+    (5,  "1.15",   "1.16.1"),
+    (6,  "1.16.2", "1.16.5"),
+    (7,  "1.17",   "1.17.1"),
+    (8,  "1.18",   "1.18.2"),
+    (9,  "1.19",   "1.19.2"),
+    (12, "1.19.3", "1.19.3"),
+    (13, "1.19.4", "1.19.4"),
+    (15, "1.20",   "1.20.1"),
+    (18, "1.20.2", "1.20.2"),
+    (22, "1.20.3", "1.20.4"),
+    (32, "1.20.5", "1.20.6"),
+    (34, "1.21",   "1.21.1"),
+    (42, "1.21.2", "1.21.3"),
+    (46, "1.21.4", "1.21.4"),
+    (55, "1.21.5", "1.21.5"),
+];
+
+/// The resource pack format Minecraft `mc` reads, if it is in the table.
+pub fn format_for(mc: &str) -> Option<u32> {
+    let v = numeric_parts(mc);
+    RESOURCE_FORMATS.iter()
+        .find(|(_, first, last)| compare_parts(&v, &numeric_parts(first)).is_ge() && compare_parts(&v, &numeric_parts(last)).is_le())
+        .map(|(f, _, _)| *f)
+}
+
+/// "1.20–1.20.1" for a known format.
+fn releases(format: u32) -> Option<String> {
+    let (_, first, last) = RESOURCE_FORMATS.iter().find(|(f, _, _)| *f == format)?;
+    Some(if first == last { first.to_string() } else { format!("{first}–{last}") })
+}
+
+/// What a `pack.mcmeta` declares.
+#[derive(Debug, PartialEq)]
+pub struct PackMeta {
+    pub pack_format: u32,
+    /// `supported_formats`, as an inclusive range
+    pub supported:   Option<(u32, u32)>,
+}
+
+impl PackMeta {
+    pub fn parse(raw: &str) -> Option<Self> {
+        let v: serde_json::Value = serde_json::from_str(raw).ok()?;
+        let pack = v.get("pack")?;
+        let pack_format = u32::try_from(pack.get("pack_format")?.as_u64()?).ok()?;
+        let n = |v: &serde_json::Value| v.as_u64().and_then(|n| u32::try_from(n).ok());
+        // A number, [min, max], or { "min_inclusive": …, "max_inclusive": … }
+        let supported = pack.get("supported_formats").and_then(|s| match s {
+            serde_json::Value::Array(a) if a.len() == 2 => Some((n(&a[0])?, n(&a[1])?)),
+            serde_json::Value::Object(_) => Some((n(s.get("min_inclusive")?)?, n(s.get("max_inclusive")?)?)),
+            other => n(other).map(|f| (f, f)),
+        });
+        Some(Self { pack_format, supported })
+    }
+
+    pub fn supports(&self, format: u32) -> bool {
+        self.pack_format == format || self.supported.is_some_and(|(lo, hi)| (lo..=hi).contains(&format))
+    }
+}
+
+/// The `pack.mcmeta` at the root of the jar at `path`, if it has one.
+pub fn read(path: &Path) -> Option<PackMeta> {
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(path).ok()?).ok()?;
+    let mut raw = String::new();
+    archive.by_name("pack.mcmeta").ok()?.read_to_string(&mut raw).ok()?;
+    PackMeta::parse(&raw)
+}
+
+/// The Minecraft version the launcher recorded for the instance `mods_dir`
+/// belongs to: CurseForge's `minecraftinstance.json` beside `mods/`, or a
+/// MultiMC/Prism `mmc-pack.json` there or one folder further up (beside
+/// `.minecraft/`).
+pub fn instance_minecraft(mods_dir: &str) -> Option<String> {
+    let game = Path::new(mods_dir).parent()?;
+    let json = |p: &Path| -> Option<serde_json::Value> { serde_json::from_str(&std::fs::read_to_string(p).ok()?).ok() };
+    if let Some(v) = json(&game.join("minecraftinstance.json")) {
+        if let Some(mc) = v.get("gameVersion").and_then(|g| g.as_str()) {
+            return Some(mc.to_string());
+        }
+    }
+    [Some(game), game.parent()].into_iter().flatten()
+        .find_map(|d| json(&d.join("mmc-pack.json")))?
+        .get("components")?.as_array()?.iter()
+        .find(|c| c.get("uid").and_then(|u| u.as_str()) == Some("net.minecraft"))?
+        .get("version")?.as_str().map(String::from)
+}
+
+/// A warning for every resource jar in `dir` whose pack format Minecraft
+/// `mc` doesn't read.  Nothing when `mc` isn't a release the table knows.
+pub fn mismatches(dir: &str, results: &[ScanResult], mc: &str) -> Vec<Warning> {
+    let Some(want) = format_for(mc) else { return Vec::new() };
+    results.iter()
+        .filter(|r| r.jar_info.is_none())
+        .filter_map(|r| {
+            let meta = read(&Path::new(dir).join(&r.file_name))?;
+            if meta.supports(want) { return None; }
+            let made_for = releases(meta.pack_format).map(|v| format!(" (Minecraft {v})")).unwrap_or_default();
+            Some(Warning::for_jar(WarningKind::PackFormat, r, format!(
+                "resource pack format {}{made_for}, but Minecraft {mc} reads format {want}", meta.pack_format,
+            )))
+        })
+        .collect()
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Unit tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_follow_the_release_table() {
+        assert_eq!(format_for("1.20.1"), Some(15));
+        assert_eq!(format_for("1.16.5"), Some(6));
+        assert_eq!(format_for("1.21"), Some(34));
+        assert_eq!(format_for("25w01a"), None);
+        assert_eq!(releases(22).as_deref(), Some("1.20.3–1.20.4"));
+    }
+
+    #[test]
+    fn supported_formats_widen_a_pack() {
+        let ranged = PackMeta::parse(r#"{"pack": {"pack_format": 15, "supported_formats": [15, 22]}}"#).unwrap();
+        assert!(ranged.supports(18) && !ranged.supports(32));
+        let object = PackMeta::parse(r#"{"pack": {"pack_format": 34, "supported_formats": {"min_inclusive": 32, "max_inclusive": 34}}}"#).unwrap();
+        assert_eq!(object.supported, Some((32, 34)));
+        let plain = PackMeta::parse(r#"{"pack": {"pack_format": 8, "description": "old"}}"#).unwrap();
+        assert!(plain.supports(8) && !plain.supports(9));
+    }
+}
@@ -0,0 +1,240 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// pattern.rs — A small regular-expression matcher for mod ids
+//
+// Enough of the usual syntax to classify families of mods by name:
+//
+//   .  [a-z0-9_]  [^…]  \d \w \s  \.     atoms
+//   *  +  ?                              greedy repetition
+//   ( … | … )  and top-level  |          grouping and alternatives
+//
+// A pattern must match the whole id, as in `.*map.*` or `(xaero|journey)map`;
+// a leading `^` and trailing `$` are accepted and mean the same thing.
+// Matching backtracks, which is fine for inputs the length of a mod id; a
+// pathological pattern such as `(a*)*b` gives up as "no match" after
+// `MAX_STEPS` steps rather than running for ever.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::cell::Cell;
+
+/// Steps one match may take before it gives up.  Real patterns over real ids
+/// need a few hundred.
+const MAX_STEPS: usize = 100_000;
+
+#[derive(Debug, Clone)]
+enum Node {
+    Char(char),
+    Any,
+    Class { ranges: Vec<(char, char)>, negated: bool },
+    Group(Vec<Vec<Node>>),
+    Repeat { node: Box<Node>, min: usize, max: Option<usize> },
+}
+
+#[derive(Debug, Clone)]
+pub struct IdPattern {
+    source: String,
+    alts:   Vec<Vec<Node>>,
+}
+
+impl std::fmt::Display for IdPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl std::str::FromStr for IdPattern {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let body = s.strip_prefix('^').unwrap_or(s);
+        let body = body.strip_suffix('$').filter(|b| !b.ends_with('\\')).unwrap_or(body);
+        let chars: Vec<char> = body.chars().collect();
+        let mut pos = 0;
+        let alts = parse_alts(&chars, &mut pos).map_err(|e| format!("bad pattern '{s}': {e}"))?;
+        if pos < chars.len() {
+            return Err(format!("bad pattern '{s}': unmatched ')'"));
+        }
+        Ok(Self { source: s.to_string(), alts })
+    }
+}
+
+impl IdPattern {
+    /// Whether the whole of `id` matches; `false` too when matching would
+    /// take more than [`MAX_STEPS`].
+    pub fn is_match(&self, id: &str) -> bool {
+        let s: Vec<char> = id.chars().collect();
+        let m = Matcher { s: &s, steps: Cell::new(0) };
+        self.alts.iter().any(|alt| m.seq(alt, 0, &mut |j| j == s.len()))
+    }
+}
+
+// ── Parsing ───────────────────────────────────────────────────────────────────
+
+fn parse_alts(c: &[char], pos: &mut usize) -> Result<Vec<Vec<Node>>, String> {
+    let mut alts = vec![parse_seq(c, pos)?];
+    while c.get(*pos) == Some(&'|') {
+        *pos += 1;
+        alts.push(parse_seq(c, pos)?);
+    }
+    Ok(alts)
+}
+
+fn parse_seq(c: &[char], pos: &mut usize) -> Result<Vec<Node>, String> {
+    let mut seq = Vec::new();
+    while let Some(&ch) = c.get(*pos) {
+        if ch == '|' || ch == ')' { break; }
+        *pos += 1;
+        let atom = match ch {
+            '.' => Node::Any,
+            '(' => {
+                let alts = parse_alts(c, pos)?;
+                if c.get(*pos) != Some(&')') { return Err("unclosed '('".into()); }
+                *pos += 1;
+                Node::Group(alts)
+            }
+            '[' => parse_class(c, pos)?,
+            '\\' => {
+                let e = *c.get(*pos).ok_or("trailing '\\'")?;
+                *pos += 1;
+                escape(e)
+            }
+            '*' | '+' | '?' => return Err(format!("'{ch}' has nothing to repeat")),
+            _ => Node::Char(ch),
+        };
+        let (min, max) = match c.get(*pos) {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            _ => { seq.push(atom); continue; }
+        };
+        *pos += 1;
+        seq.push(Node::Repeat { node: Box::new(atom), min, max });
+    }
+    Ok(seq)
+}
+
+fn escape(e: char) -> Node {
+    let class = |ranges: &[(char, char)]| Node::Class { ranges: ranges.to_vec(), negated: false };
+    match e {
+        'd' => class(&[('0', '9')]),
+        'w' => class(&[('a', 'z'), ('A', 'Z'), ('0', '9'), ('_', '_')]),
+        's' => class(&[(' ', ' '), ('\t', '\t')]),
+        other => Node::Char(other),
+    }
+}
+
+fn parse_class(c: &[char], pos: &mut usize) -> Result<Node, String> {
+    let negated = c.get(*pos) == Some(&'^');
+    if negated { *pos += 1; }
+    let mut ranges = Vec::new();
+    loop {
+        let ch = *c.get(*pos).ok_or("unclosed '['")?;
+        *pos += 1;
+        // A ']' straight after '[' is a literal
+        if ch == ']' && !ranges.is_empty() { break; }
+        let lo = if ch == '\\' { let e = *c.get(*pos).ok_or("unclosed '['")?; *pos += 1; e } else { ch };
+        if c.get(*pos) == Some(&'-') && c.get(*pos + 1).is_some_and(|&n| n != ']') {
+            let hi = c[*pos + 1];
+            *pos += 2;
+            if hi < lo { return Err(format!("range {lo}-{hi} is reversed")); }
+            ranges.push((lo, hi));
+        } else {
+            ranges.push((lo, lo));
+        }
+    }
+    Ok(Node::Class { ranges, negated })
+}
+
+// ── Matching ──────────────────────────────────────────────────────────────────
+
+/// One id being matched, and how many steps that has taken.
+struct Matcher<'a> {
+    s:     &'a [char],
+    steps: Cell<usize>,
+}
+
+impl Matcher<'_> {
+    /// Match `node` then `rest` at `i`; `k` accepts the final position.
+    fn node(&self, node: &Node, rest: &[Node], i: usize, k: &mut dyn FnMut(usize) -> bool) -> bool {
+        self.steps.set(self.steps.get() + 1);
+        if self.steps.get() > MAX_STEPS {
+            return false;
+        }
+        match node {
+            Node::Group(alts) => alts.iter().any(|alt| self.seq(alt, i, &mut |j| self.seq(rest, j, k))),
+            Node::Repeat { node, min, max } => self.repeat(node, (*min, *max), 0, rest, i, k),
+            atom => i < self.s.len() && atom_matches(atom, self.s[i]) && self.seq(rest, i + 1, k),
+        }
+    }
+
+    fn seq(&self, seq: &[Node], i: usize, k: &mut dyn FnMut(usize) -> bool) -> bool {
+        match seq.split_first() {
+            Some((first, rest)) => self.node(first, rest, i, k),
+            None => k(i),
+        }
+    }
+
+    /// Greedy: try one more repetition before giving up to `rest`.  A
+    /// repetition that consumed nothing is not retried, so `(a*)*` terminates.
+    fn repeat(
+        &self, node: &Node, (min, max): (usize, Option<usize>), count: usize,
+        rest: &[Node], i: usize, k: &mut dyn FnMut(usize) -> bool,
+    ) -> bool {
+        if max.is_none_or(|m| count < m)
+            && self.node(node, &[], i, &mut |j| j != i && self.repeat(node, (min, max), count + 1, rest, j, k))
+        {
+            return true;
+        }
+        count >= min && self.seq(rest, i, k)
+    }
+}
+
+fn atom_matches(atom: &Node, ch: char) -> bool {
+    match atom {
+        Node::Char(c) => *c == ch,
+        Node::Any => true,
+        Node::Class { ranges, negated } => ranges.iter().any(|&(lo, hi)| (lo..=hi).contains(&ch)) != *negated,
+        Node::Group(_) | Node::Repeat { .. } => unreachable!("handled by match_node"),
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Unit tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(p: &str, id: &str) -> bool {
+        p.parse::<IdPattern>().unwrap().is_match(id)
+    }
+
+    #[test]
+    fn matches_whole_ids() {
+        assert!(matches(".*map.*", "xaerominimap"));
+        assert!(matches(".*map.*", "journeymap"));
+        assert!(!matches("map", "journeymap"));
+        assert!(matches("^(xaero|journey)map$", "journeymap"));
+        assert!(matches("jei_?[a-z]+", "jei_addons"));
+        assert!(matches("jei_?[a-z]+", "jeiaddons"));
+        assert!(!matches("jei_?[a-z]+", "jei_"));
+        assert!(matches("mod\\d+", "mod42"));
+        assert!(matches("[^_]+", "sodium"));
+        assert!(!matches("[^_]+", "fabric_api"));
+        assert!(matches("(a*)*b", "aaab"));
+    }
+
+    #[test]
+    fn pathological_patterns_give_up() {
+        // Exponentially many ways to split the a's, none ending in b
+        assert!(!matches("(a*)*b", &format!("{}c", "a".repeat(40))));
+        assert!(!matches("(a|a)*b", &format!("{}c", "a".repeat(40))));
+    }
+
+    #[test]
+    fn rejects_malformed_patterns() {
+        assert!("(abc".parse::<IdPattern>().is_err());
+        assert!("abc)".parse::<IdPattern>().is_err());
+        assert!("*abc".parse::<IdPattern>().is_err());
+        assert!("[z-a]".parse::<IdPattern>().is_err());
+    }
+}
@@ -0,0 +1,104 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// placement.rs — Mods and packs dropped into each other's folders
+//
+// `lodestone misplaced <instance>` looks through the instance's `mods/`,
+// `resourcepacks/` and `shaderpacks/` (beside `mods/`, so `.minecraft/` is
+// found too) and says what each archive there really is:
+//
+//   - anything with mod metadata is a mod, however it is named;
+//   - otherwise a `shaders/` folder makes it a shader pack,
+//   - and a `pack.mcmeta` at the root a resource pack.
+//
+// Many mods ship a `pack.mcmeta` of their own, which is why metadata is
+// checked first.  Files that are none of these are left alone.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{instance_mods_dir, is_hidden, parse_jar};
+
+/// What an archive turned out to be.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Content {
+    Mod(String),
+    ResourcePack,
+    ShaderPack,
+}
+
+impl Content {
+    /// The instance folder this kind of file belongs in.
+    pub fn folder(&self) -> &'static str {
+        match self {
+            Content::Mod(_)       => "mods",
+            Content::ResourcePack => "resourcepacks",
+            Content::ShaderPack   => "shaderpacks",
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Content::Mod(id)      => format!("the mod {id}"),
+            Content::ResourcePack => "a resource pack".into(),
+            Content::ShaderPack   => "a shader pack".into(),
+        }
+    }
+}
+
+/// A file sitting in the wrong instance folder.
+#[derive(Debug, PartialEq)]
+pub struct Misplaced {
+    pub file:    PathBuf,
+    pub content: Content,
+    /// Where it should go: the matching folder beside the one it is in
+    pub move_to: PathBuf,
+}
+
+/// Look inside the archive at `path`.  `None` when it isn't a readable zip
+/// or holds nothing recognisable.
+pub fn classify(path: &Path) -> Option<Content> {
+    if let Ok(Some(info)) = parse_jar(path) {
+        return Some(Content::Mod(info.mod_id));
+    }
+    let archive = zip::ZipArchive::new(fs::File::open(path).ok()?).ok()?;
+    let names: Vec<&str> = archive.file_names().collect();
+    if names.iter().any(|n| n.starts_with("shaders/")) {
+        Some(Content::ShaderPack)
+    } else if names.contains(&"pack.mcmeta") {
+        Some(Content::ResourcePack)
+    } else {
+        None
+    }
+}
+
+/// The folder holding `mods/`, `resourcepacks/` and `shaderpacks/`.
+fn game_dir(instance: &str) -> PathBuf {
+    instance_mods_dir(instance)
+        .and_then(|m| Path::new(&m).parent().map(Path::to_path_buf))
+        .unwrap_or_else(|| PathBuf::from(instance))
+}
+
+/// Every `.jar` or `.zip` in the instance's mod and pack folders that
+/// belongs in one of the others, in folder then name order.
+pub fn check(instance: &str) -> Vec<Misplaced> {
+    let root = game_dir(instance);
+    let mut out = Vec::new();
+    for folder in ["mods", "resourcepacks", "shaderpacks"] {
+        let Ok(rd) = fs::read_dir(root.join(folder)) else { continue };
+        let mut files: Vec<PathBuf> = rd.filter_map(Result::ok)
+            .map(|e| e.path())
+            .filter(|p| p.is_file() && !is_hidden(p))
+            .filter(|p| p.extension().and_then(|e| e.to_str())
+                .is_some_and(|e| e.eq_ignore_ascii_case("jar") || e.eq_ignore_ascii_case("zip")))
+            .collect();
+        files.sort();
+        for file in files {
+            let Some(content) = classify(&file) else { continue };
+            if content.folder() != folder {
+                let move_to = root.join(content.folder());
+                out.push(Misplaced { file, content, move_to });
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,240 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// plan.rs — Batch plans: a fixed sequence of steps run with `lodestone run`
+//
+// A plan is a TOML (or JSON) file that provisions a folder reproducibly:
+//
+//   on_error = "stop"            # or "continue"
+//
+//   [[steps]]
+//   action = "module"
+//   path   = "modules/module.json"
+//
+//   [[steps]]
+//   action = "scan"
+//   dir    = "server/mods"
+//
+//   [[steps]]
+//   action = "op"
+//   op     = "delete"
+//   tag    = "client"          # or "client,both"
+//
+// Relative paths are resolved against the plan file's folder.  Every `op`
+// rescans first, so each step sees what the previous ones left behind.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::{op_targets, run_recorded, scan_directory, template, Module, OpFilter, OpOptions, Operation, ScanOptions, ScanResult, Sides};
+
+#[derive(Debug, Deserialize)]
+pub struct Plan {
+    #[serde(default)]
+    pub on_error: OnError,
+    pub steps:    Vec<Step>,
+}
+
+/// What to do when a step fails.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnError {
+    #[default]
+    Stop,
+    Continue,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Step {
+    /// Load the module later steps match against
+    Module { path: String },
+    /// Set the folder later steps act on, and print its summary
+    Scan {
+        dir: String,
+        #[serde(default)]
+        strict: bool,
+    },
+    /// Run one operation, as `lodestone op` would
+    Op {
+        op:           String,
+        tag:          Option<String>,
+        loader:       Option<String>,
+        #[serde(default)]
+        only_matched: bool,
+        /// Act only on files that are not valid jars
+        #[serde(default)]
+        invalid:      bool,
+        output:       Option<String>,
+        /// Delete into the run history rather than removing outright
+        #[serde(default = "default_backup")]
+        backup:       bool,
+        /// Act on at most this many jars
+        limit:        Option<usize>,
+        /// Move: give a jar whose name is taken at the output a numbered name
+        #[serde(default)]
+        rename_on_move: bool,
+    },
+}
+
+fn default_backup() -> bool { true }
+
+/// Read a plan; `.json` files are JSON, anything else TOML.
+pub fn load(path: &str) -> Result<Plan, String> {
+    let raw = std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+    let parsed = if path.to_ascii_lowercase().ends_with(".json") {
+        serde_json::from_str(&raw).map_err(|e| e.to_string())
+    } else {
+        toml::from_str(&raw).map_err(|e| e.to_string())
+    };
+    parsed.map_err(|e| format!("{path}: {e}"))
+}
+
+/// State carried from one step to the next.
+#[derive(Default)]
+struct Session {
+    module: Option<Module>,
+    dir:    Option<String>,
+    opts:   ScanOptions,
+    fail_fast: bool,
+}
+
+/// Run `plan` with paths relative to `base`.  Progress lines go to `log`.
+/// `fail_fast` stops the plan at the first failure whatever its `on_error`,
+/// and makes every scan and operation stop at their first failed jar too.
+/// Returns the failed steps as (1-based step number, message).
+pub fn execute(plan: &Plan, base: &Path, fail_fast: bool, log: &mut dyn FnMut(String)) -> Vec<(usize, String)> {
+    let mut session = Session { fail_fast, ..Default::default() };
+    let mut failures = Vec::new();
+    for (i, step) in plan.steps.iter().enumerate() {
+        match run_step(step, base, &mut session) {
+            Ok(msg) => log(format!("step {}: {msg}", i + 1)),
+            Err(e) => {
+                log(format!("step {}: failed: {e}", i + 1));
+                failures.push((i + 1, e));
+                if fail_fast || plan.on_error == OnError::Stop { break; }
+            }
+        }
+    }
+    failures
+}
+
+impl Session {
+    /// Under `fail_fast` the scan stopped at its first unreadable jar; that
+    /// fails the step rather than acting on the jars read so far.
+    fn check_scan(&self, results: &[ScanResult]) -> Result<(), String> {
+        match results.iter().find_map(|r| Some((&r.jar_name, r.parse_error.as_ref()?))) {
+            Some((jar, e)) if self.fail_fast => Err(format!("{jar}: {e}")),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn resolve(base: &Path, p: &str) -> String {
+    let path = PathBuf::from(p);
+    if path.is_absolute() { p.to_string() } else { base.join(path).display().to_string() }
+}
+
+fn run_step(step: &Step, base: &Path, session: &mut Session) -> Result<String, String> {
+    match step {
+        Step::Module { path } => {
+            let path = resolve(base, path);
+            let module = Module::load(&path).map_err(|e| format!("{path}: {e}"))?;
+            let mut msg = format!("loaded '{}' ({} mods)", module.name, module.mods.len());
+            if let Some(why) = module.too_new() {
+                msg = format!("{msg}; warning: {why}");
+            }
+            session.module = Some(module);
+            Ok(msg)
+        }
+        Step::Scan { dir, strict } => {
+            let module = session.module.as_ref().ok_or("scan before any module step")?;
+            let dir = resolve(base, dir);
+            if !Path::new(&dir).is_dir() {
+                return Err(format!("{dir} is not a directory"));
+            }
+            session.opts = ScanOptions { strict: *strict, fail_fast: session.fail_fast, ..Default::default() };
+            let (results, summary) = scan_directory(&dir, module, &session.opts);
+            session.check_scan(&results)?;
+            session.dir = Some(dir);
+            Ok(summary.describe())
+        }
+        Step::Op { op, tag, loader, only_matched, invalid, output, backup, limit, rename_on_move } => {
+            let module = session.module.as_ref().ok_or("op before any module step")?;
+            let dir = session.dir.as_deref().ok_or("op before any scan step")?;
+            let op: Operation = op.parse()?;
+            let sides: Sides = match tag {
+                Some(t) => t.parse()?,
+                None if op == Operation::Report || *only_matched || *invalid => Sides::default(),
+                None => return Err(format!("{op} needs a tag, only_matched or invalid")),
+            };
+            let filter = OpFilter {
+                sides,
+                loader: loader.as_deref().map(str::parse).transpose()?,
+                matched_only: *only_matched,
+                invalid:      *invalid,
+                limit:        *limit,
+            };
+            let output = output.as_deref().map(|o| resolve(base, o)).unwrap_or_default();
+            if op.needs_output() && output.is_empty() {
+                return Err(format!("{op} needs an output"));
+            }
+
+            let (results, _) = scan_directory(dir, module, &session.opts);
+            session.check_scan(&results)?;
+            let count = op_targets(op, &results, filter).len();
+            let output = template::expand(&output, &template::Fields::today(&filter.target_word(), &module.name, count))?;
+            let (n, run_id) = run_recorded(op, dir, &results, filter, &module.path, OpOptions { output: &output, backup: *backup, rename: *rename_on_move, fail_fast: session.fail_fast })
+                .map_err(|e| e.to_string())?;
+            Ok(match run_id {
+                Some(id) => format!("{op}: {n} file(s) affected, recorded as run {id}"),
+                None     => format!("{op}: {n} file(s) affected"),
+            })
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Unit tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn continue_policy_runs_past_a_failed_step() {
+        let d = std::env::temp_dir().join(format!("lodestone-plan-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&d);
+        std::fs::create_dir_all(d.join("mods")).unwrap();
+        std::fs::write(d.join("mods").join("broken.jar"), b"not a zip").unwrap();
+        std::fs::write(d.join("module.json"),
+            r#"{"header": {"module_name": "t", "module_version": 1.0, "module_author": "t"}, "mods": {}}"#).unwrap();
+
+        let plan: Plan = toml::from_str(r#"
+            on_error = "continue"
+            [[steps]]
+            action = "module"
+            path = "module.json"
+            [[steps]]
+            action = "scan"
+            dir = "mods"
+            [[steps]]
+            action = "op"
+            op = "zip"
+            tag = "unknown"
+            [[steps]]
+            action = "op"
+            op = "move"
+            invalid = true
+            output = "unknown"
+        "#).unwrap();
+
+        let mut lines = Vec::new();
+        let failures = execute(&plan, &d, false, &mut |l| lines.push(l));
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, 3, "the zip step has no output");
+        assert_eq!(lines.len(), 4);
+        assert!(d.join("unknown").join("broken.jar").is_file());
+    }
+}
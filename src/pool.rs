@@ -0,0 +1,98 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// pool.rs — A fixed number of worker threads for per-jar work
+//
+// Scanning and hashing a large instance one jar at a time leaves most cores
+// idle, but a thread per jar would open thousands of files at once.  `map`
+// runs a closure over a slice on `jobs` scoped threads that each take the
+// next unclaimed item, so at most `jobs` jars are open at any moment, and
+// hands the results back in slice order.  `--jobs N` sets the count; 0 (the
+// default) means one per CPU.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// How many workers `requested` means: itself, or one per CPU for 0.
+pub fn workers(requested: usize) -> usize {
+    match requested {
+        0 => std::thread::available_parallelism().map(usize::from).unwrap_or(1),
+        n => n,
+    }
+}
+
+/// `f` applied to every item, in order, on up to `jobs` threads (see
+/// [`workers`]).  Once a result satisfies `stop` no further items are
+/// started, and everything after the first such result is dropped, so the
+/// output is what a serial loop that breaks there would give.
+pub fn map<T, R>(
+    items: &[T], jobs: usize, stop: impl Fn(&R) -> bool + Sync, f: impl Fn(&T) -> R + Sync,
+) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+{
+    let threads = workers(jobs).min(items.len());
+    if threads <= 1 {
+        let mut out = Vec::new();
+        for item in items {
+            let r = f(item);
+            let done = stop(&r);
+            out.push(r);
+            if done { break; }
+        }
+        return out;
+    }
+
+    let (next, stopped) = (AtomicUsize::new(0), AtomicBool::new(false));
+    let done = Mutex::new(Vec::with_capacity(items.len()));
+    std::thread::scope(|s| {
+        for _ in 0..threads {
+            s.spawn(|| loop {
+                if stopped.load(Ordering::Relaxed) { break; }
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(i) else { break };
+                let r = f(item);
+                if stop(&r) { stopped.store(true, Ordering::Relaxed); }
+                done.lock().unwrap_or_else(|e| e.into_inner()).push((i, r));
+            });
+        }
+    });
+
+    // Items are claimed in order, so everything before a stopping result
+    // finished; anything after it is cut to match the serial loop.
+    let mut done = done.into_inner().unwrap_or_else(|e| e.into_inner());
+    done.sort_by_key(|(i, _)| *i);
+    let cut = done.iter().position(|(_, r)| stop(r)).map_or(done.len(), |p| p + 1);
+    done.into_iter().take(cut).map(|(_, r)| r).collect()
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Unit tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn results_keep_order_and_stop_where_a_serial_loop_would() {
+        let items: Vec<u32> = (0..200).collect();
+        let open = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let doubled = map(&items, 4, |_| false, |n| {
+            let now = open.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            std::thread::yield_now();
+            open.fetch_sub(1, Ordering::SeqCst);
+            n * 2
+        });
+        assert_eq!(doubled, items.iter().map(|n| n * 2).collect::<Vec<_>>());
+        assert!(peak.load(Ordering::SeqCst) <= 4);
+
+        for jobs in [1, 3] {
+            assert_eq!(map(&items, jobs, |n| *n == 7, |n| *n), (0..=7).collect::<Vec<_>>());
+        }
+        assert_eq!(workers(3), 3);
+        assert!(workers(0) >= 1);
+    }
+}
@@ -0,0 +1,182 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// quarantine.rs — A holding folder for jars you aren't sure about
+//
+// `op quarantine` moves the selected jars into `<dir>/.lodestone_quarantine/`
+// instead of deleting them.  The folder is dot-prefixed, so Lodestone skips
+// it and the game doesn't load from it, and `manifest.json` there says what
+// each jar was and when it went in:
+//
+//   { "jars": [ { "name": "oculus.jar", "mod_id": "oculus", "tag": "Client",
+//                 "quarantined": 1718029353 } ] }
+//
+// A jar quarantined while one of its name is already held is stored as the
+// next free `oculus-N.jar`, recorded as `held`, and goes back under its own
+// name.
+//
+// `restore-quarantine` puts jars back; `empty-quarantine` deletes them for good.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::history::{on_disk, raw_name};
+use crate::{move_file, ScanResult, Side};
+
+pub const DIR: &str = ".lodestone_quarantine";
+const MANIFEST: &str = "manifest.json";
+
+pub fn quarantine_dir(dir: &str) -> PathBuf {
+    Path::new(dir).join(DIR)
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    pub jars: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub name:        String,
+    /// The name on disk, when `name` is only a lossy rendering of it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_name:    Option<String>,
+    /// The name it is stored under in the quarantine, when not `name`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub held:        Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub held_raw:    Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mod_id:      Option<String>,
+    pub tag:         Side,
+    /// Seconds since the Unix epoch
+    pub quarantined: u64,
+}
+
+impl Entry {
+    /// The jar's file name back in the folder.
+    pub fn file(&self) -> OsString {
+        on_disk(&self.name, self.raw_name.as_deref())
+    }
+
+    /// The jar's file name in the quarantine.
+    pub fn held_file(&self) -> OsString {
+        match &self.held {
+            Some(held) => on_disk(held, self.held_raw.as_deref()),
+            None       => self.file(),
+        }
+    }
+}
+
+/// The quarantine manifest for `dir`; empty when there is none yet.
+pub fn load(dir: &str) -> Manifest {
+    std::fs::read_to_string(quarantine_dir(dir).join(MANIFEST)).ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+impl Manifest {
+    /// Write the manifest, dropping entries whose jar is no longer there
+    /// (restored by `undo`, or removed by hand).
+    fn save(mut self, dir: &str) -> std::io::Result<()> {
+        let q = quarantine_dir(dir);
+        self.jars.retain(|e| q.join(e.held_file()).is_file());
+        std::fs::create_dir_all(&q)?;
+        std::fs::write(q.join(MANIFEST), serde_json::to_string_pretty(&self)?)
+    }
+}
+
+/// Record `jars`, just moved into the quarantine, in its manifest, each with
+/// the name it is held under there.
+pub fn record(dir: &str, jars: &[(&ScanResult, &OsStr)]) -> std::io::Result<()> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let mut manifest = load(dir);
+    for (r, held) in jars {
+        // An entry for a held file replaced by hand is stale
+        manifest.jars.retain(|e| e.held_file() != *held);
+        let renamed = *held != r.file_name;
+        manifest.jars.push(Entry {
+            name:        r.jar_name.clone(),
+            raw_name:    raw_name(&r.file_name),
+            held:        renamed.then(|| held.to_string_lossy().into_owned()),
+            held_raw:    renamed.then(|| raw_name(held)).flatten(),
+            mod_id:      r.jar_info.as_ref().map(|i| i.mod_id.clone()),
+            tag:         r.effective_side(),
+            quarantined: now,
+        });
+    }
+    manifest.save(dir)
+}
+
+/// Move quarantined jars back into `dir`: the ones named in `only`, or all
+/// of them.  A jar whose name is already taken in `dir` stays put unless
+/// `overwrite`.  Returns (restored, skipped).
+pub fn restore(dir: &str, only: &[String], overwrite: bool) -> Result<(Vec<String>, Vec<String>), String> {
+    let q = quarantine_dir(dir);
+    let mut manifest = load(dir);
+    if let Some(absent) = only.iter().find(|n| !manifest.jars.iter().any(|e| e.name == **n)) {
+        return Err(format!("{absent} is not in the quarantine"));
+    }
+    let (mut restored, mut skipped) = (Vec::new(), Vec::new());
+    for e in manifest.jars.iter().filter(|e| only.is_empty() || only.contains(&e.name)) {
+        let (src, dst) = (q.join(e.held_file()), Path::new(dir).join(e.file()));
+        // Taken back out by `undo` since
+        if !src.is_file() {
+            continue;
+        }
+        if dst.exists() && !overwrite {
+            skipped.push(e.name.clone());
+            continue;
+        }
+        move_file(&src, &dst).map_err(|err| format!("moving {} back: {err}", e.name))?;
+        restored.push(e.name.clone());
+    }
+    manifest.jars.retain(|e| !restored.contains(&e.name));
+    manifest.save(dir).map_err(|e| format!("{}: {e}", q.display()))?;
+    Ok((restored, skipped))
+}
+
+/// Delete every quarantined jar and the quarantine folder.  Returns how
+/// many jars were removed.
+pub fn empty(dir: &str) -> Result<usize, String> {
+    let q = quarantine_dir(dir);
+    let n = load(dir).jars.iter().filter(|e| q.join(e.held_file()).is_file()).count();
+    if q.exists() {
+        std::fs::remove_dir_all(&q).map_err(|e| format!("{}: {e}", q.display()))?;
+    }
+    Ok(n)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Unit tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restore_skips_names_taken_since() {
+        let d = std::env::temp_dir().join(format!("lodestone-quarantine-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&d);
+        let dir = d.to_str().unwrap();
+        std::fs::create_dir_all(quarantine_dir(dir)).unwrap();
+        let manifest = Manifest { jars: ["a.jar", "b.jar"].iter().map(|n| {
+            std::fs::write(quarantine_dir(dir).join(n), "jar").unwrap();
+            Entry { name: n.to_string(), raw_name: None, held: None, held_raw: None, mod_id: None, tag: Side::Client, quarantined: 0 }
+        }).collect() };
+        manifest.save(dir).unwrap();
+        std::fs::write(d.join("b.jar"), "newer").unwrap();
+
+        assert!(restore(dir, &["c.jar".into()], false).is_err());
+        let (restored, skipped) = restore(dir, &[], false).unwrap();
+        assert_eq!((restored, skipped), (vec!["a.jar".to_string()], vec!["b.jar".to_string()]));
+        assert!(d.join("a.jar").is_file());
+        assert_eq!(load(dir).jars.len(), 1);
+        assert_eq!(empty(dir), Ok(1));
+        assert!(!quarantine_dir(dir).exists());
+        assert_eq!(std::fs::read_to_string(d.join("b.jar")).unwrap(), "newer");
+    }
+}
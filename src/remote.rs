@@ -0,0 +1,215 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// remote.rs — Modules loaded straight from a URL
+//
+// Anywhere a module path is taken, an `https://` (or `http://`) URL works
+// too: the file is fetched with curl, as the Modrinth lookups are, and kept
+// under the user's cache folder (`$XDG_CACHE_HOME/lodestone/modules`, else
+// `~/.cache/…`).  The next load sends the saved ETag / Last-Modified, so an
+// unchanged module costs a 304 and nothing more; when the server can't be
+// reached, or sends back something that isn't a module, the cached copy is
+// used with a warning.  With `--verify-module` the `.sha256` published next
+// to the URL is downloaded beside the cached copy and checked as for a
+// local module.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+/// Give up on a download after this many seconds.
+const TIMEOUT_SECS: &str = "30";
+/// Modules are a few hundred KiB at most; anything bigger isn't one.
+pub const MAX_BYTES: u64 = 16 * 1024 * 1024;
+const USER_AGENT: &str = concat!("lodestone/", env!("CARGO_PKG_VERSION"));
+
+pub fn is_url(path: &str) -> bool {
+    let lower = path.to_ascii_lowercase();
+    lower.starts_with("https://") || lower.starts_with("http://")
+}
+
+/// What the last download of a URL said about itself.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CacheMeta {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+fn cache_dir() -> PathBuf {
+    std::env::var_os("XDG_CACHE_HOME").map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache")))
+        .unwrap_or_else(std::env::temp_dir)
+        .join("lodestone")
+        .join("modules")
+}
+
+/// The cached copy of `url`, named after its hash.  Keeps the `.gz` of a
+/// gzipped module so it is read back the same way.
+pub fn cache_path(url: &str) -> PathBuf {
+    let key = crate::checksum::sha256(url.as_bytes());
+    let gz = url.split(['?', '#']).next().unwrap_or(url).to_ascii_lowercase().ends_with(".gz");
+    cache_dir().join(format!("{}.json{}", &key[..16], if gz { ".gz" } else { "" }))
+}
+
+fn meta_path(url: &str) -> PathBuf {
+    let mut p = cache_path(url).into_os_string();
+    p.push(".meta");
+    PathBuf::from(p)
+}
+
+/// `ETag` and `Last-Modified` from a curl `--dump-header` file.  With
+/// redirects it holds several responses; the last one counts.
+pub fn parse_headers(raw: &str, url: &str) -> CacheMeta {
+    let last = raw.rsplit("\r\n\r\n").find(|block| !block.trim().is_empty()).unwrap_or(raw);
+    let header = |name: &str| last.lines()
+        .filter_map(|l| l.split_once(':'))
+        .find(|(k, _)| k.trim().eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+    CacheMeta { url: url.to_string(), etag: header("etag"), last_modified: header("last-modified") }
+}
+
+/// GET `url` into `part` with curl, sending `cached`'s validators, and
+/// return the HTTP status and the response headers.  `Err` when curl itself
+/// failed.
+fn download(url: &str, part: &Path, cached: Option<&CacheMeta>) -> Result<(String, String), String> {
+    let mut headers = part.as_os_str().to_owned();
+    headers.push(".headers");
+    let headers = PathBuf::from(headers);
+    let mut cmd = Command::new("curl");
+    cmd.args(["--silent", "--show-error", "--location", "--max-time", TIMEOUT_SECS])
+        .args(["--max-filesize", &MAX_BYTES.to_string(), "--user-agent", USER_AGENT])
+        .arg("--dump-header").arg(&headers)
+        .arg("--output").arg(part)
+        .args(["--write-out", "%{http_code}"]);
+    if let Some(m) = cached {
+        if let Some(etag) = &m.etag { cmd.args(["--header", &format!("If-None-Match: {etag}")]); }
+        if let Some(lm) = &m.last_modified { cmd.args(["--header", &format!("If-Modified-Since: {lm}")]); }
+    }
+    let out = cmd.arg(url).output().map_err(|e| format!("running curl: {e}"))?;
+    let header_text = std::fs::read_to_string(&headers).unwrap_or_default();
+    let _ = std::fs::remove_file(&headers);
+    if !out.status.success() {
+        // 63: --max-filesize exceeded
+        return Err(if out.status.code() == Some(63) {
+            format!("over {} MiB, which no module is", MAX_BYTES / (1024 * 1024))
+        } else {
+            format!("curl: {}", String::from_utf8_lossy(&out.stderr).trim())
+        });
+    }
+    Ok((String::from_utf8_lossy(&out.stdout).trim().to_string(), header_text))
+}
+
+/// Whether the downloaded `part` is JSON with an object at the top, as
+/// every module is; a login or error page served with a 200 is not.
+fn looks_like_module(part: &Path, gz: bool) -> bool {
+    let Ok(bytes) = std::fs::read(part) else { return false };
+    let text = if gz {
+        let mut text = String::new();
+        match flate2::read::GzDecoder::new(bytes.as_slice()).read_to_string(&mut text) {
+            Ok(_) => text,
+            Err(_) => return false,
+        }
+    } else {
+        match String::from_utf8(bytes) {
+            Ok(text) => text,
+            Err(_) => return false,
+        }
+    };
+    serde_json::from_str::<serde_json::Value>(&text).is_ok_and(|v| v.is_object())
+}
+
+/// Make sure the module at `url` is in the cache, downloading it unless the
+/// cached copy is still current, and return the cached file.  A download
+/// that isn't a module never replaces the cached copy.
+pub fn fetch(url: &str) -> Result<PathBuf, String> {
+    let (body, meta_file) = (cache_path(url), meta_path(url));
+    std::fs::create_dir_all(cache_dir()).map_err(|e| format!("{}: {e}", cache_dir().display()))?;
+    let cached: Option<CacheMeta> = body.is_file()
+        .then(|| std::fs::read_to_string(&meta_file).ok().and_then(|raw| serde_json::from_str(&raw).ok()))
+        .flatten();
+
+    let part = body.with_extension("part");
+    let failure = match download(url, &part, cached.as_ref()) {
+        Err(why) => Some(why),
+        Ok((status, _)) if status == "304" && cached.is_some() => None,
+        Ok((status, _)) if status == "200" && !looks_like_module(&part, crate::is_gzip(&body.to_string_lossy())) => {
+            Some("the download is not a module (a login or error page?)".into())
+        }
+        Ok((status, header_text)) if status == "200" => {
+            std::fs::rename(&part, &body).map_err(|e| format!("{}: {e}", body.display()))?;
+            let meta = parse_headers(&header_text, url);
+            let _ = std::fs::write(&meta_file, serde_json::to_string_pretty(&meta).unwrap_or_default());
+            None
+        }
+        Ok((code, _)) => Some(format!("HTTP {code}")),
+    };
+    let _ = std::fs::remove_file(&part);
+    match failure {
+        None => Ok(body),
+        Some(why) if body.is_file() => {
+            eprintln!("warning: {url}: {why}; using the copy cached at {}", body.display());
+            Ok(body)
+        }
+        Some(why) => Err(why),
+    }
+}
+
+/// Where a module at `url` publishes its checksum file: the same URL with
+/// `.sha256` added to the path.
+pub fn sidecar_url(url: &str) -> String {
+    let (path, rest) = url.find(['?', '#']).map_or((url, ""), |i| url.split_at(i));
+    format!("{path}.sha256{rest}")
+}
+
+/// Download the checksum file published next to the module at `url` to sit
+/// beside its cached copy, where `--verify-module` looks.  It is fetched
+/// anew each time; when that fails no older copy is left to be trusted.
+pub fn fetch_sidecar(url: &str) -> Result<PathBuf, String> {
+    let side = crate::checksum::sidecar_path(&cache_path(url).to_string_lossy());
+    let part = side.with_extension("part");
+    let _ = std::fs::remove_file(&side);
+    let outcome = match download(&sidecar_url(url), &part, None) {
+        Ok((status, _)) if status == "200" => std::fs::rename(&part, &side).map(|()| side).map_err(|e| e.to_string()),
+        Ok((code, _)) => Err(format!("HTTP {code} from {}", sidecar_url(url))),
+        Err(why) => Err(why),
+    };
+    let _ = std::fs::remove_file(&part);
+    outcome
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Unit tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_final_response_headers_are_kept() {
+        let raw = "HTTP/2 302\r\nlocation: /m.json\r\netag: \"old\"\r\n\r\n\
+                   HTTP/2 200\r\nETag: \"abc\"\r\nLast-Modified: Tue, 01 Oct 2024 10:00:00 GMT\r\n\r\n";
+        let meta = parse_headers(raw, "https://example.org/m.json");
+        assert_eq!(meta.etag.as_deref(), Some("\"abc\""));
+        assert_eq!(meta.last_modified.as_deref(), Some("Tue, 01 Oct 2024 10:00:00 GMT"));
+        assert!(is_url("HTTPS://example.org/m.json") && !is_url("modules/m.json"));
+        assert!(cache_path("https://example.org/m.json.gz?v=2").to_string_lossy().ends_with(".json.gz"));
+        assert_eq!(sidecar_url("https://example.org/m.json?v=2"), "https://example.org/m.json.sha256?v=2");
+    }
+
+    #[test]
+    fn only_json_objects_pass_for_modules() {
+        let p = std::env::temp_dir().join(format!("lodestone-remote-{}.part", std::process::id()));
+        std::fs::write(&p, "<html>Sign in to the hotel wifi</html>").unwrap();
+        assert!(!looks_like_module(&p, false));
+        std::fs::write(&p, r#"{"header": {}, "mods": {}}"#).unwrap();
+        assert!(looks_like_module(&p, false));
+        assert!(!looks_like_module(&p, true), "not gzipped");
+        let _ = std::fs::remove_file(&p);
+    }
+}
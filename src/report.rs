@@ -0,0 +1,427 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// report.rs — Machine- and human-readable renderings of scan results
+//
+// The GUI draws `ScanResult`s itself; everything that leaves the process as
+// text (CLI output, files for other tools) is formatted here so the field
+// names and wording stay consistent between formats.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::io::Write;
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+use crate::signing::SignatureStatus;
+use crate::{BundledMod, Links, MatchQuality, Mismatch, ModLoader, Module, ModuleEntry, ScanResult, ScanSummary, Side, VersionSource, Warning};
+
+/// The flat, per-jar view of a `ScanResult` that reports serialise.
+#[derive(Debug, Serialize)]
+pub struct JarRecord<'a> {
+    pub jar:     &'a str,
+    pub id:      Option<&'a str>,
+    #[serde(rename = "type")]
+    pub loader:  ModLoader,
+    pub version: Option<&'a str>,
+    pub side:    Side,
+    /// The side the jar's own metadata declares, when it declares one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub declared: Option<Side>,
+    #[serde(rename = "match")]
+    pub status:  MatchQuality,
+    /// Why a partial match was partial
+    #[serde(skip_serializing_if = "Mismatch::is_none")]
+    pub mismatch: Mismatch,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<&'a SignatureStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_source: Option<VersionSource>,
+    /// The metadata file inside the jar the id and loader came from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata_file: Option<&'a str>,
+    /// Mods shipped inside the jar as jar-in-jar
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    pub bundled: &'a [BundledMod],
+    /// Homepage and issue tracker from the metadata
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub links: Option<&'a Links>,
+}
+
+impl<'a> JarRecord<'a> {
+    pub fn new(r: &'a ScanResult) -> Self {
+        let info = r.jar_info.as_ref();
+        Self {
+            jar:     &r.jar_name,
+            id:      info.map(|i| i.mod_id.as_str()),
+            loader:  info.map(|i| i.loader).unwrap_or(ModLoader::Unknown),
+            version: info.and_then(|i| i.version.as_deref()),
+            side:    r.effective_side(),
+            declared: info.and_then(|i| i.declared_side),
+            status:  r.match_quality,
+            mismatch: r.mismatch,
+            signature: r.signature.as_ref(),
+            // Only called out when it isn't the obvious place
+            version_source: info.map(|i| i.version_source).filter(|s| *s != VersionSource::Metadata),
+            metadata_file: info.map(|i| i.metadata_file.as_str()),
+            bundled: info.map(|i| i.bundled.as_slice()).unwrap_or_default(),
+            links:   info.map(|i| &i.links).filter(|l| !l.is_empty()),
+        }
+    }
+}
+
+/// A scan's counts plus, when nothing matched, the reason in words, and
+/// whatever the scan warned about.
+#[derive(Debug, Serialize)]
+pub struct SummaryRecord<'a> {
+    #[serde(flatten)]
+    pub counts:    &'a ScanSummary,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diagnosis: Option<String>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    pub warnings:  &'a [Warning],
+}
+
+impl<'a> SummaryRecord<'a> {
+    pub fn new(summary: &'a ScanSummary, warnings: &'a [Warning]) -> Self {
+        Self { counts: summary, diagnosis: summary.diagnosis(), warnings }
+    }
+}
+
+/// Writes one JSON object per line.  The writer sits behind a lock so results
+/// can be emitted from whichever thread finished the jar without interleaving.
+pub struct NdjsonWriter<W: Write> {
+    out: Mutex<W>,
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    pub fn new(out: W) -> Self {
+        Self { out: Mutex::new(out) }
+    }
+
+    pub fn emit(&self, r: &ScanResult) -> std::io::Result<()> {
+        let line = serde_json::to_string(&JarRecord::new(r))?;
+        let mut out = self.out.lock().unwrap_or_else(|e| e.into_inner());
+        writeln!(out, "{line}")?;
+        out.flush()
+    }
+}
+
+/// A fixed-width text line for terminal output.
+pub fn text_line(r: &ScanResult) -> String {
+    let rec = JarRecord::new(r);
+    format!(
+        "{:<40} {:<24} {:<9} {:<14} {:<8} {}",
+        rec.jar,
+        rec.id.unwrap_or("—"),
+        rec.loader.to_string(),
+        rec.version.unwrap_or("—"),
+        rec.side.to_string(),
+        r.status_label(),
+    )
+}
+
+/// One skimmable line per jar for `scan --compact`: a status mark, then the
+/// mod's id and version with its loader and side, or the file name and why
+/// it couldn't be identified.
+pub fn compact_line(r: &ScanResult) -> String {
+    let mark = match r.match_quality {
+        MatchQuality::Full                                 => '✓',
+        MatchQuality::IdOnly                               => '≈',
+        MatchQuality::Unclassified | MatchQuality::Partial => '~',
+        MatchQuality::Unidentified | MatchQuality::Unknown => '?',
+        MatchQuality::Invalid                              => '✗',
+    };
+    let rec = JarRecord::new(r);
+    match rec.id {
+        _ if r.match_quality == MatchQuality::Invalid => format!("{mark} {} [not a valid jar]", rec.jar),
+        None => format!("{mark} {} [no metadata]", rec.jar),
+        Some(id) => {
+            let version = rec.version.map(|v| format!(" {v}")).unwrap_or_default();
+            format!("{mark} {id}{version} [{}/{}]", rec.loader, rec.side)
+        }
+    }
+}
+
+/// The indented line `scan --verbose` prints under a jar: where each part of
+/// its identification came from.  `None` for jars with no metadata.
+pub fn source_line(r: &ScanResult) -> Option<String> {
+    let info = r.jar_info.as_ref()?;
+    let version = match info.version_source {
+        VersionSource::Metadata         => String::new(),
+        VersionSource::Manifest         => ", version from META-INF/MANIFEST.MF".into(),
+        VersionSource::GradleProperties => ", version from gradle.properties".into(),
+    };
+    let bundles = if info.bundled.is_empty() {
+        String::new()
+    } else {
+        let ids: Vec<&str> = info.bundled.iter().map(|b| b.mod_id.as_str()).collect();
+        format!("; bundles {}", ids.join(", "))
+    };
+    Some(format!("    from {}{version}; side by {}{bundles}", info.metadata_file, r.side_source()))
+}
+
+// ── Explain ───────────────────────────────────────────────────────────────────
+
+/// Everything the scan knows about mod `id`, for `scan --explain`: each jar
+/// that provides it (or bundles it), what was detected and from which file,
+/// the module's entry, and the verdict with its reason.
+pub fn explain(id: &str, results: &[ScanResult], module: &Module) -> Vec<String> {
+    let is_id = |other: &str| other.eq_ignore_ascii_case(id);
+    let entry = module.mods.iter().find(|(k, _)| is_id(k));
+    let mut out = vec![id.to_string()];
+
+    let mut providers = 0;
+    for r in results {
+        let Some(info) = r.jar_info.as_ref().filter(|i| is_id(&i.mod_id)) else { continue };
+        providers += 1;
+        let version = info.version.as_deref().unwrap_or("no version");
+        let from = match info.version_source {
+            VersionSource::Metadata         => String::new(),
+            VersionSource::Manifest         => ", version from META-INF/MANIFEST.MF".into(),
+            VersionSource::GradleProperties => ", version from gradle.properties".into(),
+        };
+        out.push(format!("  jar:       {}", r.jar_name));
+        out.push(format!("  detected:  {} {version} (from {}{from})", info.loader, info.metadata_file));
+        out.push(format!("  declared:  {}", info.declared_side.map(|s| s.to_string()).unwrap_or_else(|| "—".into())));
+        if let Some(bc) = &r.bytecode_side {
+            out.push(format!("  bytecode:  {bc:?} ({:?})", r.bytecode_confidence));
+        }
+        out.push(format!("  side:      {} (by {})", r.effective_side(), r.side_source()));
+        out.push(format!("  verdict:   {} — {}", r.status_label(), verdict_reason(r, module)));
+    }
+    for r in results {
+        let Some(info) = &r.jar_info else { continue };
+        for b in info.bundled.iter().filter(|b| is_id(&b.mod_id)) {
+            providers += 1;
+            out.push(format!("  bundled:   {} {} inside {} ({})",
+                b.mod_id, b.version.as_deref().unwrap_or("—"), r.jar_name, b.path));
+        }
+    }
+    if providers == 0 {
+        out.push("  jar:       none in the folder declares this id".into());
+    }
+
+    match entry {
+        Some((key, e)) => {
+            let range = e.version_range.as_deref().map(|r| format!(", accepts {r}")).unwrap_or_default();
+            out.push(format!("  module:    '{key}' in '{}': {} {} {}{range}", module.name, e.mod_tag, e.mod_version, e.mod_type));
+        }
+        None => out.push(format!("  module:    not in '{}'", module.name)),
+    }
+    out
+}
+
+/// Why `r` got its match quality, in a phrase.
+fn verdict_reason(r: &ScanResult, module: &Module) -> String {
+    let (Some(info), Some(e)) = (&r.jar_info, &r.module_entry) else {
+        return format!("the id is not in '{}'", module.name);
+    };
+    let version = info.version.as_deref().unwrap_or("");
+    match r.match_quality {
+        MatchQuality::Full if e.mod_version == "*" => "the module accepts any version and the loader agrees".into(),
+        MatchQuality::Full if version == e.mod_version => "id, version and loader agree".into(),
+        MatchQuality::Full => match &e.version_range {
+            Some(range) if crate::version::in_range(version, range).unwrap_or(false) =>
+                format!("version {version} is inside {range}"),
+            _ => format!("version {version} is close enough to {} under the version tolerance", e.mod_version),
+        },
+        MatchQuality::Unclassified => "it matches, but the module's tag is Unknown".into(),
+        MatchQuality::IdOnly => "the jar declares no version to compare".into(),
+        MatchQuality::Partial => {
+            let mut why = Vec::new();
+            if r.mismatch.version {
+                why.push(format!("version {version} is not the module's {}", e.mod_version));
+            }
+            if r.mismatch.loader {
+                why.push(format!("built for {}, the module lists {}", info.loader, e.mod_type));
+            }
+            why.join("; ")
+        }
+        _ => String::new(),
+    }
+}
+
+// ── Links ─────────────────────────────────────────────────────────────────────
+
+/// Every identified mod with the homepage and issue tracker its metadata
+/// gives, sorted by id, for `scan --emit-links` — the raw material of a
+/// credits page.  Mods that give neither are listed too, so none is missed.
+pub fn links(results: &[ScanResult]) -> Vec<String> {
+    let mut mods: Vec<(&str, &Links)> = results.iter()
+        .filter_map(|r| r.jar_info.as_ref())
+        .map(|i| (i.mod_id.as_str(), &i.links))
+        .collect();
+    mods.sort_by_key(|(id, _)| id.to_lowercase());
+    mods.dedup_by_key(|(id, _)| id.to_lowercase());
+    let or_dash = |v: &Option<String>| v.clone().unwrap_or_else(|| "—".into());
+    let mut out = vec![format!("{:<32} {:<48} issues", "id", "homepage")];
+    out.extend(mods.iter().map(|(id, l)| format!("{id:<32} {:<48} {}", or_dash(&l.homepage), or_dash(&l.issues))));
+    let linked = mods.iter().filter(|(_, l)| !l.is_empty()).count();
+    out.push(format!("{linked} of {} mod(s) link a homepage or issue tracker.", mods.len()));
+    out
+}
+
+// ── Missing mods ──────────────────────────────────────────────────────────────
+
+/// One module entry with no jar in the folder, as written to a manifest.
+#[derive(Debug, Serialize)]
+pub struct MissingRecord<'a> {
+    pub id:      &'a str,
+    /// `None` when the module accepts any version
+    pub version: Option<&'a str>,
+    #[serde(rename = "type")]
+    pub loader:  ModLoader,
+    pub side:    Side,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modrinth:   Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub curseforge: Option<&'a str>,
+}
+
+impl<'a> MissingRecord<'a> {
+    pub fn new(id: &'a str, e: &'a ModuleEntry) -> Self {
+        Self {
+            id,
+            version: Some(e.mod_version.as_str()).filter(|v| *v != "*"),
+            loader:  e.mod_type,
+            side:    e.mod_tag,
+            modrinth:   e.modrinth.as_deref(),
+            curseforge: e.curseforge.as_deref(),
+        }
+    }
+}
+
+// ── Report files ──────────────────────────────────────────────────────────────
+
+/// How `op report` lays out its file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Jar names under a `# Side` heading each
+    #[default]
+    Text,
+    /// One table row per jar, for forum posts and wikis
+    Markdown,
+}
+
+impl std::str::FromStr for ReportFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "txt" | "text"     => Ok(ReportFormat::Text),
+            "md" | "markdown"  => Ok(ReportFormat::Markdown),
+            _ => Err(format!("unknown report format '{s}' (expected txt or markdown)")),
+        }
+    }
+}
+
+impl ReportFormat {
+    /// Markdown for a `.md` output, text for anything else.
+    pub fn for_output(path: &str) -> Self {
+        let lower = path.to_ascii_lowercase();
+        if lower.ends_with(".md") || lower.ends_with(".markdown") { ReportFormat::Markdown } else { ReportFormat::Text }
+    }
+}
+
+pub fn render(targets: &[&ScanResult], format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Text     => side_sections(targets),
+        ReportFormat::Markdown => markdown_table(targets),
+    }
+}
+
+fn side_sections(targets: &[&ScanResult]) -> String {
+    let mut report = String::new();
+    for (i, side) in [Side::Client, Side::Server, Side::Both, Side::Unknown].into_iter().enumerate() {
+        if i > 0 { report.push('\n'); }
+        report += &format!("# {side}\n");
+        for r in targets.iter().filter(|r| r.effective_side() == side) {
+            report += &format!("{}\n", r.jar_name);
+        }
+    }
+    report
+}
+
+/// The scan as a Markdown table, in folder order:
+///
+///   | Jar | Mod id | Loader | Version | Module tag | Match |
+///   |-----|--------|--------|---------|------------|-------|
+///   | sodium.jar | sodium | Fabric | 0.5.8 | Client | Full match |
+pub fn markdown_table(targets: &[&ScanResult]) -> String {
+    // A pipe would end the cell early
+    let cell = |s: &str| s.replace('|', "\\|");
+    let mut out = String::from("| Jar | Mod id | Loader | Version | Module tag | Match |\n|-----|--------|--------|---------|------------|-------|\n");
+    for r in targets {
+        let rec = JarRecord::new(r);
+        let tag = r.module_entry.as_ref().map(|e| e.mod_tag.to_string()).unwrap_or_else(|| "—".into());
+        out += &format!(
+            "| {} | {} | {} | {} | {tag} | {} |\n",
+            cell(rec.jar), cell(rec.id.unwrap_or("—")), rec.loader, cell(rec.version.unwrap_or("—")), r.status_label(),
+        );
+    }
+    out
+}
+
+/// A plain-text list of missing mods, one per line, for pasting into a chat:
+///
+///   lithium 0.11.2 (Fabric, Both) — modrinth: lithium
+pub fn missing_manifest(module_name: &str, missing: &[(&str, &ModuleEntry)]) -> String {
+    let mut out = format!("# {} mod(s) from '{module_name}' not in this folder\n", missing.len());
+    for (id, e) in missing {
+        let rec = MissingRecord::new(id, e);
+        let mut line = format!("{}{} ({}, {})", rec.id,
+            rec.version.map(|v| format!(" {v}")).unwrap_or_default(), rec.loader, rec.side);
+        let links: Vec<String> = [("modrinth", rec.modrinth), ("curseforge", rec.curseforge)].into_iter()
+            .filter_map(|(site, slug)| slug.map(|s| format!("{site}: {s}")))
+            .collect();
+        if !links.is_empty() {
+            line += &format!(" — {}", links.join(", "));
+        }
+        out += &line;
+        out.push('\n');
+    }
+    out
+}
+
+// ── Dependency graph ──────────────────────────────────────────────────────────
+
+/// Node fill per side — the GUI palette, so the graph reads like the app.
+fn side_fill(side: Side) -> &'static str {
+    match side {
+        Side::Client  => "#c76a2f",
+        Side::Server  => "#328051",
+        Side::Both    => "#7758a1",
+        Side::Unknown => "#99a0a4",
+    }
+}
+
+fn dot_id(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Graphviz DOT of installed mods and their declared dependencies.  Installed
+/// mods are filled by side; dependencies with no jar in the folder are dashed.
+pub fn dependency_dot(results: &[ScanResult]) -> String {
+    let installed: std::collections::BTreeMap<&str, Side> = results.iter()
+        .filter_map(|r| r.jar_info.as_ref().map(|i| (i.mod_id.as_str(), r.effective_side())))
+        .collect();
+
+    let mut out = String::from("digraph mods {\n    rankdir=LR;\n    node [shape=box, style=\"rounded,filled\", fontcolor=white, fontname=\"Helvetica\"];\n");
+    for (id, side) in &installed {
+        out += &format!("    {} [fillcolor=\"{}\", tooltip=\"{side}\"];\n", dot_id(id), side_fill(*side));
+    }
+
+    let mut missing = std::collections::BTreeSet::new();
+    for info in results.iter().filter_map(|r| r.jar_info.as_ref()) {
+        for dep in &info.dependencies {
+            let id = dep.mod_id.as_str();
+            if !installed.contains_key(id) { missing.insert(id); }
+            // Optional dependencies are drawn dashed so the hard requirements stand out
+            let style = if dep.mandatory { "" } else { " [style=dashed]" };
+            out += &format!("    {} -> {}{style};\n", dot_id(&info.mod_id), dot_id(id));
+        }
+    }
+    for id in missing {
+        out += &format!("    {} [style=\"rounded,dashed\", fontcolor=\"#5a6165\", tooltip=\"not installed\"];\n", dot_id(id));
+    }
+    out.push_str("}\n");
+    out
+}
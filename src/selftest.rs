@@ -0,0 +1,168 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// selftest.rs — Detection run against jars built on the spot
+//
+// `lodestone self-test` builds a small jar in memory for each metadata
+// format Lodestone reads, runs them through the same detection a scan uses,
+// and prints a pass or FAIL line per fixture with what was expected and
+// found.  It needs no mods folder, so its output can go straight into a bug
+// report ("Fabric detection is broken"), and CI can run it as a smoke test.
+// User patterns from `lodestone-metadata.json` apply, as they do to scans,
+// and the header counts them because they can change the outcome.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::io::{Cursor, Write};
+
+use crate::{metadata, parse_archive, ModLoader, Side};
+
+/// One jar to build and what detection should make of it.
+struct Fixture {
+    name:    &'static str,
+    entries: Vec<(&'static str, Vec<u8>)>,
+    id:      &'static str,
+    loader:  ModLoader,
+    version: Option<&'static str>,
+    side:    Option<Side>,
+    /// Mod ids expected among its jar-in-jar bundles
+    bundled: &'static [&'static str],
+}
+
+/// The outcome for one fixture.
+#[derive(Debug)]
+pub struct Outcome {
+    pub name:    &'static str,
+    pub loader:  ModLoader,
+    /// Empty when everything matched
+    pub problems: Vec<String>,
+}
+
+impl Outcome {
+    pub fn passed(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+fn zip(entries: &[(&str, Vec<u8>)]) -> Vec<u8> {
+    let mut w = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, body) in entries {
+        w.start_file(*name, zip::write::FileOptions::default()).expect("in-memory zip");
+        w.write_all(body).expect("in-memory zip");
+    }
+    w.finish().expect("in-memory zip").into_inner()
+}
+
+fn text(s: &str) -> Vec<u8> {
+    s.as_bytes().to_vec()
+}
+
+fn fixtures() -> Vec<Fixture> {
+    let inner = zip(&[("fabric.mod.json", text(r#"{"id": "inner-lib", "version": "2.0"}"#))]);
+    vec![
+        Fixture {
+            name: "Forge mods.toml",
+            entries: vec![("META-INF/mods.toml", text(
+                "modLoader = \"javafml\"\n[[mods]]\nmodId = \"forgemod\"\nversion = \"1.2.0\"\n\
+                 [[dependencies.forgemod]]\nmodId = \"forge\"\nmandatory = true\n"))],
+            id: "forgemod", loader: ModLoader::Forge, version: Some("1.2.0"), side: None, bundled: &[],
+        },
+        Fixture {
+            name: "NeoForge neoforge.mods.toml",
+            entries: vec![("META-INF/neoforge.mods.toml", text(
+                "[[mods]]\nmodId = \"neomod\"\nversion = \"3.1\"\n\
+                 [[dependencies.neomod]]\nmodId = \"neoforge\"\ntype = \"required\"\n\
+                 [[dependencies.neomod]]\nmodId = \"minecraft\"\nside = \"CLIENT\"\n"))],
+            id: "neomod", loader: ModLoader::NeoForge, version: Some("3.1"), side: Some(Side::Client), bundled: &[],
+        },
+        Fixture {
+            name: "Forge version from MANIFEST.MF",
+            entries: vec![
+                ("META-INF/mods.toml", text("[[mods]]\nmodId = \"placeholder\"\nversion = \"${file.jarVersion}\"\n")),
+                ("META-INF/MANIFEST.MF", text("Manifest-Version: 1.0\r\nImplementation-Version: 4.5.6\r\n")),
+            ],
+            id: "placeholder", loader: ModLoader::Forge, version: Some("4.5.6"), side: None, bundled: &[],
+        },
+        Fixture {
+            name: "legacy Forge mcmod.info",
+            entries: vec![("mcmod.info", text(r#"[{"modid": "oldmod", "version": "1.7.10-2"}]"#))],
+            id: "oldmod", loader: ModLoader::Forge, version: Some("1.7.10-2"), side: None, bundled: &[],
+        },
+        Fixture {
+            name: "Fabric fabric.mod.json",
+            entries: vec![("fabric.mod.json", text(r#"{"id": "fabricmod", "version": "0.5.8", "environment": "client"}"#))],
+            id: "fabricmod", loader: ModLoader::Fabric, version: Some("0.5.8"), side: Some(Side::Client), bundled: &[],
+        },
+        Fixture {
+            name: "Fabric jar-in-jar",
+            entries: vec![
+                ("fabric.mod.json", text(r#"{"id": "bundler", "version": "1.0", "jars": [{"file": "META-INF/jars/inner.jar"}]}"#)),
+                ("META-INF/jars/inner.jar", inner),
+            ],
+            id: "bundler", loader: ModLoader::Fabric, version: Some("1.0"), side: None, bundled: &["inner-lib"],
+        },
+        Fixture {
+            name: "Quilt quilt.mod.json",
+            entries: vec![("quilt.mod.json", text(r#"{"quilt_loader": {"id": "quiltmod", "version": "2.2.0"}}"#))],
+            id: "quiltmod", loader: ModLoader::Quilt, version: Some("2.2.0"), side: None, bundled: &[],
+        },
+        Fixture {
+            name: "LiteLoader litemod.json",
+            entries: vec![("litemod.json", text(r#"{"name": "litemod", "version": "1.0"}"#))],
+            id: "litemod", loader: ModLoader::LiteLoader, version: Some("1.0"), side: None, bundled: &[],
+        },
+        Fixture {
+            name: "Rift riftmod.json",
+            entries: vec![("riftmod.json", text(r#"{"id": "riftmod"}"#))],
+            id: "riftmod", loader: ModLoader::Rift, version: None, side: None, bundled: &[],
+        },
+    ]
+}
+
+fn check(f: &Fixture) -> Vec<String> {
+    let mut archive = match zip::ZipArchive::new(Cursor::new(zip(&f.entries))) {
+        Ok(a) => a,
+        Err(e) => return vec![format!("fixture jar unreadable: {e}")],
+    };
+    let info = match parse_archive(&mut archive, 0) {
+        Ok(Some(info)) => info,
+        Ok(None) => return vec!["no mod metadata found".into()],
+        Err(e) => return vec![format!("parse error: {e}")],
+    };
+    let mut problems = Vec::new();
+    let mut expect = |what: &str, want: String, got: String| {
+        if want != got { problems.push(format!("{what}: expected {want}, got {got}")); }
+    };
+    let opt = |v: Option<&str>| v.unwrap_or("none").to_string();
+    expect("id", f.id.into(), info.mod_id.clone());
+    expect("loader", f.loader.to_string(), info.loader.to_string());
+    expect("version", opt(f.version), opt(info.version.as_deref()));
+    expect("side", opt(f.side.map(|s| s.to_string()).as_deref()), opt(info.declared_side.map(|s| s.to_string()).as_deref()));
+    let bundled: Vec<&str> = info.bundled.iter().map(|b| b.mod_id.as_str()).collect();
+    expect("bundled", f.bundled.join(", "), bundled.join(", "));
+    problems
+}
+
+/// Run every fixture through detection.
+pub fn run() -> Vec<Outcome> {
+    fixtures().iter().map(|f| Outcome { name: f.name, loader: f.loader, problems: check(f) }).collect()
+}
+
+/// How many user patterns are in effect on top of the defaults.
+pub fn user_patterns() -> usize {
+    metadata::patterns().len().saturating_sub(metadata::defaults().len())
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Unit tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_fixture_passes() {
+        let failed: Vec<String> = run().into_iter().filter(|o| !o.passed())
+            .map(|o| format!("{}: {}", o.name, o.problems.join("; ")))
+            .collect();
+        assert!(failed.is_empty(), "{failed:#?}");
+    }
+}
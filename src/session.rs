@@ -0,0 +1,171 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// session.rs — Editing a module one typed command at a time
+//
+// `lodestone shell <module.json>` reads commands such as `add sodium client
+// fabric 0.5.8`, `tag sodium both` or `remove sodium` and saves the module
+// after each one.  Every change first pushes the module as it was onto an
+// in-memory stack, so `u` (or `undo`) puts back the previous state and saves
+// that instead; a slip needs no trip to a text editor.  The stack holds the
+// last `MAX_UNDO` changes and is gone when the session ends.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::collections::VecDeque;
+
+use crate::{Module, ModuleEntry, ModLoader, Side};
+
+/// How many changes back `undo` can go.
+pub const MAX_UNDO: usize = 50;
+
+pub const HELP: &str = "\
+  add <mod id> <tag> [<loader>] [<version>]   add or replace an entry
+  tag <mod id> <tag>                          retag an entry
+  note <mod id> [<text>]                      set, or with no text clear, its note
+  remove <mod id>                             drop an entry
+  list                                        every entry
+  u | undo                                    put back the state before the last change
+  q | quit                                    end the session";
+
+/// What a command asks of the caller.
+#[derive(Debug, PartialEq)]
+pub enum Step {
+    /// The module changed and should be saved; says what was done
+    Save(String),
+    /// Nothing changed; just print this
+    Show(String),
+    Quit,
+}
+
+pub struct Session {
+    pub module: Module,
+    /// Earlier states, newest last, each with the change that left it
+    past:  VecDeque<(Module, String)>,
+    limit: usize,
+}
+
+impl Session {
+    pub fn new(module: Module, limit: usize) -> Self {
+        Self { module, past: VecDeque::new(), limit }
+    }
+
+    /// How many changes can be undone.
+    pub fn depth(&self) -> usize {
+        self.past.len()
+    }
+
+    /// Run one command line.  A command that fails leaves the module, and the
+    /// undo stack, as they were.
+    pub fn apply(&mut self, line: &str) -> Result<Step, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [] => Ok(Step::Show(String::new())),
+            ["q" | "quit" | "exit"] => Ok(Step::Quit),
+            ["help" | "?"] => Ok(Step::Show(HELP.into())),
+            ["u" | "undo"] => self.undo(),
+            ["list"] => Ok(Step::Show(self.listing())),
+            ["add", id, tag, rest @ ..] if rest.len() <= 2 => {
+                let tag: Side = tag.parse()?;
+                let loader: ModLoader = rest.first().map_or(Ok(ModLoader::Unknown), |l| l.parse())?;
+                let version = rest.get(1).unwrap_or(&"*").to_string();
+                let id = id.to_string();
+                self.change(move |m| {
+                    let old = m.mods.remove(&id);
+                    let what = if old.is_some() { "replaced" } else { "added" };
+                    m.mods.insert(id.clone(), ModuleEntry {
+                        mod_version: version.clone(),
+                        version_range: None,
+                        mod_tag:  tag,
+                        mod_type: loader,
+                        protected: false,
+                        note:       old.as_ref().and_then(|e| e.note.clone()),
+                        modrinth:   old.as_ref().and_then(|e| e.modrinth.clone()),
+                        curseforge: old.as_ref().and_then(|e| e.curseforge.clone()),
+                        packs:      old.map(|e| e.packs).unwrap_or_default(),
+                    });
+                    Ok(format!("{what} {id}: {tag} · {loader} · v{version}"))
+                })
+            }
+            ["tag", id, tag] => {
+                let tag: Side = tag.parse()?;
+                self.change(|m| {
+                    entry(m, id)?.mod_tag = tag;
+                    Ok(format!("{id} tagged {tag}"))
+                })
+            }
+            ["note", id, text @ ..] => {
+                let text = text.join(" ");
+                self.change(|m| {
+                    let e = entry(m, id)?;
+                    if text.is_empty() {
+                        e.note = None;
+                        Ok(format!("cleared the note on {id}"))
+                    } else {
+                        e.note = Some(text.clone());
+                        Ok(format!("noted {id}: {text}"))
+                    }
+                })
+            }
+            ["remove", id] => self.change(|m| {
+                m.mods.remove(*id).ok_or_else(|| format!("'{id}' is not in the module"))?;
+                Ok(format!("removed {id}"))
+            }),
+            [cmd, ..] => Err(format!("not a command: {cmd} (type help for the list)")),
+        }
+    }
+
+    /// Apply `edit` to the module, keeping the state before it for undo.
+    fn change(&mut self, edit: impl FnOnce(&mut Module) -> Result<String, String>) -> Result<Step, String> {
+        let before = self.module.clone();
+        let what = edit(&mut self.module).inspect_err(|_| self.module = before.clone())?;
+        self.past.push_back((before, what.clone()));
+        if self.past.len() > self.limit {
+            self.past.pop_front();
+        }
+        Ok(Step::Save(what))
+    }
+
+    fn undo(&mut self) -> Result<Step, String> {
+        let (before, what) = self.past.pop_back().ok_or("nothing to undo")?;
+        self.module = before;
+        Ok(Step::Save(format!("undid: {what}")))
+    }
+
+    fn listing(&self) -> String {
+        if self.module.mods.is_empty() {
+            return "(no entries)".into();
+        }
+        self.module.mods.iter()
+            .map(|(id, e)| format!("{id}: {} · {} · v{}", e.mod_tag, e.mod_type, e.mod_version))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn entry<'m>(m: &'m mut Module, id: &str) -> Result<&'m mut ModuleEntry, String> {
+    m.mods.get_mut(id).ok_or_else(|| format!("'{id}' is not in the module"))
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Unit tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_walks_back_through_changes_up_to_the_limit() {
+        let mut s = Session::new(Module::new("m.json", "m", ""), 2);
+        assert!(matches!(s.apply("add sodium client fabric 0.5.8"), Ok(Step::Save(_))));
+        s.apply("tag sodium both").unwrap();
+        s.apply("note sodium drop on servers").unwrap();
+        assert!(s.apply("remove missing").is_err());
+        assert_eq!(s.depth(), 2, "a failed command isn't recorded and the oldest change falls off");
+
+        assert_eq!(s.apply("u").unwrap(), Step::Save("undid: noted sodium: drop on servers".into()));
+        assert_eq!(s.module.mods["sodium"].note, None);
+        s.apply("undo").unwrap();
+        assert_eq!(s.module.mods["sodium"].mod_tag, Side::Client);
+        assert!(s.apply("undo").is_err(), "the add is past the limit");
+        assert!(s.module.mods.contains_key("sodium"));
+    }
+}
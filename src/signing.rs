@@ -0,0 +1,117 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// signing.rs — Best-effort jar signature consistency check
+//
+// A signed jar carries, under META-INF/:
+//   MANIFEST.MF     per-entry digests ("Name: …" + "SHA-256-Digest: …")
+//   <NAME>.SF       signature file, again listing entries by name
+//   <NAME>.RSA/.DSA/.EC   the signature block over the .SF
+//
+// We don't verify any cryptography.  We only check that the pieces a signed
+// jar must have are present and agree with each other — enough to flag a jar
+// that was signed and then had files added, removed, or stripped, which is
+// what a tampered mod tends to look like.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::collections::BTreeSet;
+use std::io::Read;
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "status", content = "reason")]
+pub enum SignatureStatus {
+    /// No signature files and no digests in the manifest
+    Unsigned,
+    /// Signature files and blocks are present and reference existing entries
+    Signed,
+    /// The jar claims to be signed but something is missing or doesn't line up
+    Inconsistent(String),
+}
+
+const BLOCK_EXTS: &[&str] = &[".RSA", ".DSA", ".EC"];
+
+/// Check the signature files of the jar at `path`.  `None` if it is not a zip.
+pub fn check_jar(path: impl AsRef<std::path::Path>) -> Option<SignatureStatus> {
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(path).ok()?).ok()?;
+    let names: BTreeSet<String> = archive.file_names().map(String::from).collect();
+
+    let meta = |n: &String| n.to_ascii_uppercase().starts_with("META-INF/") && !n[9..].contains('/');
+    let stem = |n: &str, ext: &str| n[..n.len() - ext.len()].to_ascii_uppercase();
+
+    let sf: BTreeSet<String> = names.iter().filter(|n| meta(n))
+        .filter(|n| n.to_ascii_uppercase().ends_with(".SF"))
+        .map(|n| stem(n, ".SF")).collect();
+    let blocks: BTreeSet<String> = names.iter().filter(|n| meta(n))
+        .filter_map(|n| BLOCK_EXTS.iter()
+            .find(|e| n.to_ascii_uppercase().ends_with(*e))
+            .map(|e| stem(n, e)))
+        .collect();
+
+    let manifest = read_entry(&mut archive, "META-INF/MANIFEST.MF").unwrap_or_default();
+    let manifest_digests = manifest.lines().any(|l| l.contains("-Digest:"));
+
+    if sf.is_empty() && blocks.is_empty() {
+        return Some(if manifest_digests {
+            SignatureStatus::Inconsistent("manifest has entry digests but no signature files".into())
+        } else {
+            SignatureStatus::Unsigned
+        });
+    }
+    if let Some(s) = sf.difference(&blocks).next() {
+        return Some(SignatureStatus::Inconsistent(format!("{s}.SF has no signature block")));
+    }
+    if let Some(b) = blocks.difference(&sf).next() {
+        return Some(SignatureStatus::Inconsistent(format!("signature block {b} has no .SF file")));
+    }
+
+    // Every entry a signature file vouches for must still be in the jar
+    let sf_files: Vec<String> = names.iter().filter(|n| meta(n))
+        .filter(|n| n.to_ascii_uppercase().ends_with(".SF")).cloned().collect();
+    for sf_name in sf_files {
+        let body = read_entry(&mut archive, &sf_name).unwrap_or_default();
+        if let Some(missing) = signed_names(&body).into_iter().find(|n| !names.contains(n)) {
+            return Some(SignatureStatus::Inconsistent(format!("{sf_name} lists missing entry {missing}")));
+        }
+    }
+    Some(SignatureStatus::Signed)
+}
+
+fn read_entry(archive: &mut zip::ZipArchive<std::fs::File>, name: &str) -> Option<String> {
+    let mut e = archive.by_name(name).ok()?;
+    let mut s = String::new();
+    e.read_to_string(&mut s).ok()?;
+    Some(s)
+}
+
+/// Entry names in a manifest-style file.  Long values wrap onto continuation
+/// lines that start with a single space.
+fn signed_names(body: &str) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    let mut in_name = false;
+    for line in body.lines() {
+        if let Some(rest) = line.strip_prefix("Name: ") {
+            out.push(rest.to_string());
+            in_name = true;
+        } else if let (true, Some(cont)) = (in_name, line.strip_prefix(' ')) {
+            if let Some(last) = out.last_mut() { last.push_str(cont); }
+        } else {
+            in_name = false;
+        }
+    }
+    out
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Unit tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn joins_wrapped_names() {
+        let sf = "Signature-Version: 1.0\nName: com/example/a/very/long/\n path/Thing.class\nSHA-256-Digest: x\n";
+        assert_eq!(signed_names(sf), vec!["com/example/a/very/long/path/Thing.class"]);
+    }
+}
@@ -0,0 +1,113 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// stats.rs — What's in a mods folder, without a module
+//
+// `lodestone stats` runs the ordinary detection pipeline and skips matching
+// altogether: how many jars, which loaders, how many carry metadata Lodestone
+// can read, and where the disk space goes.  [`Footprint`] does the same
+// sum for the jars a delete is about to take.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::{MatchQuality, ModLoader, ScanResult};
+
+/// How many of the largest jars are listed.
+pub const LARGEST: usize = 5;
+
+#[derive(Debug, Default)]
+pub struct FolderStats {
+    pub total:         usize,
+    /// Jars per detected loader, most common first; unreadable jars count as Unknown
+    pub by_loader:     Vec<(ModLoader, usize)>,
+    /// Jars whose metadata parsed to a mod id
+    pub with_metadata: usize,
+    /// Files that are not valid zips
+    pub invalid:       usize,
+    pub total_bytes:   u64,
+    /// The [`LARGEST`] biggest jars, biggest first
+    pub largest:       Vec<(String, u64)>,
+}
+
+pub fn folder_stats(dir: &str, results: &[ScanResult]) -> FolderStats {
+    let mut loaders: HashMap<ModLoader, usize> = HashMap::new();
+    let mut sizes = Vec::with_capacity(results.len());
+    for r in results {
+        *loaders.entry(r.loader()).or_default() += 1;
+        let size = std::fs::metadata(Path::new(dir).join(&r.file_name)).map(|m| m.len()).unwrap_or(0);
+        sizes.push((r.jar_name.clone(), size));
+    }
+    let mut by_loader: Vec<_> = loaders.into_iter().collect();
+    by_loader.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.to_string().cmp(&b.0.to_string())));
+
+    let total_bytes = sizes.iter().map(|(_, n)| n).sum();
+    sizes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    sizes.truncate(LARGEST);
+
+    FolderStats {
+        total:         results.len(),
+        by_loader,
+        with_metadata: results.iter().filter(|r| r.jar_info.is_some()).count(),
+        invalid:       results.iter().filter(|r| r.match_quality == MatchQuality::Invalid).count(),
+        total_bytes,
+        largest:       sizes,
+    }
+}
+
+/// The size on disk of a set of jars, measured before an operation so what
+/// it freed can be told afterwards from which of them are gone.
+#[derive(Debug, Default)]
+pub struct Footprint {
+    files: Vec<(PathBuf, u64)>,
+}
+
+impl Footprint {
+    /// The targets still in `dir`, with their sizes.
+    pub fn of(dir: &str, targets: &[&ScanResult]) -> Self {
+        let files = targets.iter()
+            .map(|r| Path::new(dir).join(&r.file_name))
+            .filter_map(|p| std::fs::metadata(&p).ok().filter(|m| m.is_file()).map(|m| (p, m.len())))
+            .collect();
+        Self { files }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.files.iter().map(|(_, n)| n).sum()
+    }
+
+    /// Bytes of the measured jars that are no longer there.
+    pub fn gone(&self) -> u64 {
+        self.files.iter().filter(|(p, _)| !p.exists()).map(|(_, n)| n).sum()
+    }
+}
+
+/// `1536` → `"1.5 KiB"`.
+pub fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut n = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while n >= 1024.0 && unit + 1 < UNITS.len() {
+        n /= 1024.0;
+        unit += 1;
+    }
+    format!("{n:.1} {}", UNITS[unit])
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Unit tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_read_like_a_file_manager() {
+        assert_eq!(human_size(512), "512 B");
+        assert_eq!(human_size(1536), "1.5 KiB");
+        assert_eq!(human_size(5 * 1024 * 1024), "5.0 MiB");
+    }
+}
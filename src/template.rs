@@ -0,0 +1,85 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// template.rs — Output names built from what an operation selected
+//
+// An operation's output may name its own file: `{module}-{tag}-{date}.zip`
+// becomes `My-Pack-client+both-2024-06-10.zip`.  The placeholders are
+// `{tag}` (the selection, as `OpFilter::target_word` spells it), `{module}`
+// (the module's name), `{date}` (today, UTC, `YYYY-MM-DD`) and `{count}`
+// (how many jars it acts on).  Values are made safe for a file name; the
+// rest of the template, folders included, is kept as written.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The values a template can use.
+pub struct Fields<'a> {
+    pub tag:    &'a str,
+    pub module: &'a str,
+    pub count:  usize,
+    /// `YYYY-MM-DD`
+    pub date:   String,
+}
+
+impl<'a> Fields<'a> {
+    /// Fields dated today.
+    pub fn today(tag: &'a str, module: &'a str, count: usize) -> Self {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        Self { tag, module, count, date: crate::history::utc_date(now) }
+    }
+}
+
+/// Whether `s` has anything to expand.
+pub fn is_template(s: &str) -> bool {
+    s.contains('{')
+}
+
+/// Characters that can't be in a file name on some system become `-`.
+fn file_safe(s: &str) -> String {
+    let safe: String = s.trim().chars()
+        .map(|c| if c.is_control() || c.is_whitespace() || "<>:\"/\\|?*".contains(c) { '-' } else { c })
+        .collect();
+    if safe.is_empty() { "unnamed".into() } else { safe }
+}
+
+/// `template` with every placeholder replaced.  An unknown or unclosed
+/// placeholder is an error rather than ending up in a file name.
+pub fn expand(template: &str, fields: &Fields) -> Result<String, String> {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let close = after.find('}').ok_or_else(|| format!("unclosed '{{' in output name '{template}'"))?;
+        out.push_str(&match &after[..close] {
+            "tag"    => file_safe(fields.tag),
+            "module" => file_safe(fields.module),
+            "date"   => fields.date.clone(),
+            "count"  => fields.count.to_string(),
+            other    => return Err(format!(
+                "unknown placeholder {{{other}}} in output name '{template}' (use {{tag}}, {{module}}, {{date}} or {{count}})"
+            )),
+        });
+        rest = &after[close + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Unit tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholders_expand_to_file_safe_values() {
+        let fields = Fields { tag: "client+both", module: "My Pack: 1.20", count: 12, date: "2024-06-10".into() };
+        assert_eq!(expand("out/{module}-{tag}-{date}.zip", &fields).unwrap(), "out/My-Pack--1.20-client+both-2024-06-10.zip");
+        assert_eq!(expand("{count}-jars.zip", &fields).unwrap(), "12-jars.zip");
+        assert_eq!(expand("plain.zip", &fields).unwrap(), "plain.zip");
+        assert!(expand("{size}.zip", &fields).is_err());
+        assert!(expand("{tag.zip", &fields).is_err());
+    }
+}
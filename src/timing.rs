@@ -0,0 +1,109 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// timing.rs — Where a scan spends its time
+//
+// `scan --timing` reports how long the scan took from start to finish, how
+// long listing the folder took, and how long reading metadata, the bytecode
+// and signature passes, and matching against the module took summed over
+// every jar, plus the slowest jars.  With `--jobs` above 1 those sums are CPU
+// time across the workers and can well exceed the total.  The clock is always running
+// (an `Instant` per phase costs nothing next to opening a zip); the flag
+// only decides whether it is shown.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::time::Duration;
+
+/// How many of the slowest jars are listed.
+pub const SLOWEST: usize = 5;
+
+/// One jar's share, by phase.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JarTiming {
+    /// Opening the zip and parsing its loader metadata
+    pub parse:    Duration,
+    /// Bytecode side analysis and the signature check
+    pub analyse:  Duration,
+    /// Looking the mod up in the module and grading the match
+    pub matching: Duration,
+}
+
+impl JarTiming {
+    pub fn total(&self) -> Duration {
+        self.parse + self.analyse + self.matching
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ScanTiming {
+    /// The whole scan, start to finish
+    pub wall:      Duration,
+    /// Listing the folder and loading any instance manifest or mapping
+    pub enumerate: Duration,
+    /// Every jar's phases added up
+    pub jars:      JarTiming,
+    /// Time per jar, for picking out the slowest
+    pub per_jar:   Vec<(String, Duration)>,
+}
+
+impl ScanTiming {
+    pub fn add(&mut self, jar: &str, t: JarTiming) {
+        self.jars.parse += t.parse;
+        self.jars.analyse += t.analyse;
+        self.jars.matching += t.matching;
+        self.per_jar.push((jar.to_string(), t.total()));
+    }
+
+    /// The [`SLOWEST`] jars, slowest first.
+    pub fn slowest(&self) -> Vec<(&str, Duration)> {
+        let mut all: Vec<_> = self.per_jar.iter().map(|(n, d)| (n.as_str(), *d)).collect();
+        all.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        all.truncate(SLOWEST);
+        all
+    }
+
+    /// The report `scan --timing` prints, one line per phase, then the slowest jars.
+    pub fn lines(&self) -> Vec<String> {
+        let mut out = vec![
+            format!("timing: {} in total over {} jar(s)", millis(self.wall), self.per_jar.len()),
+            format!("  {:<10} {:>10}", "enumerate", millis(self.enumerate)),
+            "  CPU time across workers:".into(),
+        ];
+        for (phase, d) in [("parse", self.jars.parse), ("analyse", self.jars.analyse), ("match", self.jars.matching)] {
+            out.push(format!("    {phase:<8} {:>10}", millis(d)));
+        }
+        let slowest = self.slowest();
+        if !slowest.is_empty() {
+            out.push("  slowest:".into());
+            out.extend(slowest.into_iter().map(|(jar, d)| format!("    {:>10}  {jar}", millis(d))));
+        }
+        out
+    }
+}
+
+/// `1.5ms` style, to a tenth of a millisecond.
+fn millis(d: Duration) -> String {
+    format!("{:.1}ms", d.as_secs_f64() * 1000.0)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Unit tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slowest_jars_come_first() {
+        let mut t = ScanTiming::default();
+        for (jar, ms) in [("a.jar", 3), ("b.jar", 9), ("c.jar", 1)] {
+            t.add(jar, JarTiming { parse: Duration::from_millis(ms), ..Default::default() });
+        }
+        assert_eq!(t.jars.parse, Duration::from_millis(13));
+        // Jars scanned in parallel take less wall time than their sum
+        t.wall = Duration::from_millis(5);
+        let names: Vec<&str> = t.slowest().into_iter().map(|(n, _)| n).collect();
+        assert_eq!(names, vec!["b.jar", "a.jar", "c.jar"]);
+        assert_eq!(t.lines()[0], "timing: 5.0ms in total over 3 jar(s)");
+        assert_eq!(t.lines()[3], "    parse        13.0ms");
+    }
+}
@@ -0,0 +1,308 @@
+// ─────────────────────────────────────────────────────────────────────────────
+// version.rs — Lenient version parsing and comparison
+//
+// Mod versions are only loosely semver: "0.5.1.f", "15.2.0.27", "v2.1+mc1.20".
+// We read the leading run of dot-separated numbers and compare those, falling
+// back to an exact string comparison when a version has no numeric prefix.
+// A module's own version is the exception: `module bump` wants strict semver.
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// How closely a detected version must agree with the module's to count as a match.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VersionTolerance {
+    /// The strings must be identical
+    #[default]
+    Exact,
+    /// Major and minor components must agree
+    Minor,
+    /// Only the major component must agree
+    Major,
+}
+
+impl std::fmt::Display for VersionTolerance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            VersionTolerance::Exact => "Exact",
+            VersionTolerance::Minor => "Major.minor",
+            VersionTolerance::Major => "Major only",
+        })
+    }
+}
+
+impl std::str::FromStr for VersionTolerance {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "exact" => Ok(VersionTolerance::Exact),
+            "minor" => Ok(VersionTolerance::Minor),
+            "major" => Ok(VersionTolerance::Major),
+            _ => Err(format!("unknown version tolerance '{s}' (expected exact, minor or major)")),
+        }
+    }
+}
+
+/// Leading numeric components: "v0.5.1.f" → [0, 5, 1], "1.20-beta" → [1, 20].
+pub fn numeric_parts(v: &str) -> Vec<u64> {
+    v.trim().trim_start_matches(['v', 'V'])
+        .split(['.', '-', '+'])
+        .map_while(|p| p.parse().ok())
+        .collect()
+}
+
+/// Whether `detected` satisfies the module's `wanted` version under `tol`.
+pub fn versions_match(detected: &str, wanted: &str, tol: VersionTolerance) -> bool {
+    if detected == wanted { return true; }
+    let keep = match tol {
+        VersionTolerance::Exact => return false,
+        VersionTolerance::Minor => 2,
+        VersionTolerance::Major => 1,
+    };
+    let (d, w) = (numeric_parts(detected), numeric_parts(wanted));
+    if d.is_empty() || w.is_empty() { return false; }
+    // Missing components count as 0, so "1.2" and "1.2.0" agree at any level
+    (0..keep).all(|i| d.get(i).unwrap_or(&0) == w.get(i).unwrap_or(&0))
+}
+
+/// Compare numeric components, missing ones counting as 0, so "1.2" and
+/// "1.2.0" are equal.
+pub fn compare_parts(a: &[u64], b: &[u64]) -> std::cmp::Ordering {
+    (0..a.len().max(b.len()))
+        .map(|i| a.get(i).unwrap_or(&0).cmp(b.get(i).unwrap_or(&0)))
+        .find(|o| o.is_ne())
+        .unwrap_or(std::cmp::Ordering::Equal)
+}
+
+/// Whether `v` satisfies every comparator in `range`, e.g. `>=0.5.0,<0.6.0`.
+/// Comparators are `>=`, `>`, `<=`, `<` and `=`; a bare version means `=`.
+/// A version with no numeric prefix is never in range.
+pub fn in_range(v: &str, range: &str) -> Result<bool, String> {
+    let parts = numeric_parts(v);
+    let mut ok = !parts.is_empty();
+    for c in range.split(',').map(str::trim) {
+        let (op, bound) = ["<=", ">=", "==", "<", ">", "="].iter()
+            .find_map(|op| c.strip_prefix(op).map(|b| (*op, b.trim())))
+            .unwrap_or(("=", c));
+        let bound = numeric_parts(bound);
+        if bound.is_empty() {
+            return Err(format!("bad version range '{range}': '{c}' has no version"));
+        }
+        let ord = compare_parts(&parts, &bound);
+        ok &= match op {
+            "<=" => ord.is_le(),
+            ">=" => ord.is_ge(),
+            "<"  => ord.is_lt(),
+            ">"  => ord.is_gt(),
+            _    => ord.is_eq(),
+        };
+    }
+    Ok(ok)
+}
+
+/// Whether `v` is inside a range as a mod's own metadata writes it: Forge's
+/// Maven intervals (`[1.2,2.0)`, `[1.2]`), or Fabric/Quilt predicates
+/// (`>=1.2 <2`, `^1.2`, `~1.2.3`, `*`, alternatives joined by `||`).
+/// `None` when the range is in a form this doesn't follow.
+pub fn declared_range_holds(v: &str, range: &str) -> Option<bool> {
+    let range = range.trim();
+    if range.contains("||") {
+        let each: Vec<Option<bool>> = range.split("||").map(|r| declared_range_holds(v, r)).collect();
+        return if each.contains(&Some(true)) { Some(true) } else if each.contains(&None) { None } else { Some(false) };
+    }
+    if range.is_empty() || range == "*" {
+        return Some(true);
+    }
+    let comparators = if let Some(inner) = range.strip_prefix(['[', '(']).and_then(|r| r.strip_suffix([']', ')'])) {
+        let (lo_incl, hi_incl) = (range.starts_with('['), range.ends_with(']'));
+        match inner.split(',').map(str::trim).collect::<Vec<_>>()[..] {
+            [exact] if lo_incl && hi_incl => format!("={exact}"),
+            [lo, hi] => [
+                (!lo.is_empty()).then(|| format!("{}{lo}", if lo_incl { ">=" } else { ">" })),
+                (!hi.is_empty()).then(|| format!("{}{hi}", if hi_incl { "<=" } else { "<" })),
+            ].into_iter().flatten().collect::<Vec<_>>().join(","),
+            _ => return None,
+        }
+    } else {
+        let mut out = Vec::new();
+        for c in range.split_whitespace() {
+            let next = |bound: &str, width: usize| {
+                let mut parts = numeric_parts(bound);
+                parts.truncate(width);
+                if let Some(last) = parts.last_mut() { *last += 1; }
+                parts.iter().map(u64::to_string).collect::<Vec<_>>().join(".")
+            };
+            if let Some(b) = c.strip_prefix('^') {
+                out.push(format!(">={b},<{}", next(b, 1)));
+            } else if let Some(b) = c.strip_prefix('~') {
+                out.push(format!(">={b},<{}", next(b, 2)));
+            } else if c.contains(['x', 'X', '*']) {
+                return None;
+            } else {
+                out.push(c.to_string());
+            }
+        }
+        out.join(",")
+    };
+    if comparators.is_empty() {
+        return Some(true);
+    }
+    in_range(v, &comparators).ok()
+}
+
+/// Reject a malformed range up front rather than letting it never match.
+pub fn check_range(range: &str) -> Result<(), String> {
+    in_range("0", range).map(drop)
+}
+
+// ── Semver ───────────────────────────────────────────────────────────────────
+
+/// Which part of a version `module bump` raises.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bump {
+    Major,
+    Minor,
+    Patch,
+}
+
+impl std::str::FromStr for Bump {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "major" => Ok(Bump::Major),
+            "minor" => Ok(Bump::Minor),
+            "patch" => Ok(Bump::Patch),
+            other   => Err(format!("unknown version part '{other}' (expected major, minor or patch)")),
+        }
+    }
+}
+
+/// A strict `MAJOR.MINOR.PATCH[-pre][+build]` version; unlike mod versions,
+/// a module's own version is ours to keep tidy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Semver {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub pre:   Option<String>,
+    pub build: Option<String>,
+}
+
+impl std::str::FromStr for Semver {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || format!("'{s}' is not a semver version (MAJOR.MINOR.PATCH, e.g. 1.4.0)");
+        let (rest, build) = match s.split_once('+') {
+            Some((r, b)) => (r, Some(b)),
+            None         => (s, None),
+        };
+        let (core, pre) = match rest.split_once('-') {
+            Some((c, p)) => (c, Some(p)),
+            None         => (rest, None),
+        };
+        let label = |l: Option<&str>| match l {
+            Some(l) if l.is_empty() || !l.split('.').all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')) => Err(bad()),
+            l => Ok(l.map(String::from)),
+        };
+        let number = |p: &str| match p {
+            "0" => Some(0),
+            p if !p.starts_with('0') && p.chars().all(|c| c.is_ascii_digit()) => p.parse().ok(),
+            _ => None,
+        };
+        let parts: Vec<Option<u64>> = core.split('.').map(number).collect();
+        let [Some(major), Some(minor), Some(patch)] = parts[..] else { return Err(bad()) };
+        Ok(Self { major, minor, patch, pre: label(pre)?, build: label(build)? })
+    }
+}
+
+impl std::fmt::Display for Semver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(p) = &self.pre { write!(f, "-{p}")?; }
+        if let Some(b) = &self.build { write!(f, "+{b}")?; }
+        Ok(())
+    }
+}
+
+impl Semver {
+    /// The next version after this one.  A pre-release is released rather
+    /// than skipped: `2.0.0-beta` bumped by major is `2.0.0`.  Build
+    /// metadata is dropped.
+    pub fn bump(&self, part: Bump) -> Self {
+        let pre = self.pre.is_some();
+        let (major, minor, patch) = match part {
+            Bump::Major if pre && self.minor == 0 && self.patch == 0 => (self.major, 0, 0),
+            Bump::Major => (self.major + 1, 0, 0),
+            Bump::Minor if pre && self.patch == 0 => (self.major, self.minor, 0),
+            Bump::Minor => (self.major, self.minor + 1, 0),
+            Bump::Patch if pre => (self.major, self.minor, self.patch),
+            Bump::Patch => (self.major, self.minor, self.patch + 1),
+        };
+        Self { major, minor, patch, pre: None, build: None }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Unit tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_leading_numbers() {
+        assert_eq!(numeric_parts("0.5.1.f"), vec![0, 5, 1]);
+        assert_eq!(numeric_parts("v2.1+mc1.20"), vec![2, 1]);
+        assert!(numeric_parts("mc1.20-3.0").is_empty());
+    }
+
+    #[test]
+    fn tolerance_levels() {
+        use VersionTolerance::*;
+        assert!(!versions_match("0.5.1", "0.5.2", Exact));
+        assert!(versions_match("0.5.1", "0.5.2", Minor));
+        assert!(!versions_match("0.5.1", "0.6.0", Minor));
+        assert!(versions_match("0.5.1", "0.6.0", Major));
+        assert!(!versions_match("1.0", "2.0", Major));
+        assert!(versions_match("1.2", "1.2.0", Minor));
+        assert!(!versions_match("beta", "gamma", Major));
+    }
+
+    #[test]
+    fn ranges_bound_both_ends() {
+        assert_eq!(in_range("0.5.3", ">=0.5.0,<0.6.0"), Ok(true));
+        assert_eq!(in_range("0.5", ">=0.5.0, <0.6.0"), Ok(true));
+        assert_eq!(in_range("0.6.0", ">=0.5.0,<0.6.0"), Ok(false));
+        assert_eq!(in_range("0.4.9", ">=0.5.0,<0.6.0"), Ok(false));
+        assert_eq!(in_range("1.2.0", "1.2"), Ok(true));
+        assert_eq!(in_range("beta", ">=0.1"), Ok(false));
+        assert!(in_range("1.0", ">=x").is_err());
+    }
+
+    #[test]
+    fn semver_bumps_reset_the_lower_parts() {
+        let v: Semver = "1.4.2+build.7".parse().unwrap();
+        assert_eq!(v.bump(Bump::Patch).to_string(), "1.4.3");
+        assert_eq!(v.bump(Bump::Minor).to_string(), "1.5.0");
+        assert_eq!(v.bump(Bump::Major).to_string(), "2.0.0");
+        let pre: Semver = "2.0.0-rc.1".parse().unwrap();
+        assert_eq!(pre.bump(Bump::Major).to_string(), "2.0.0");
+        assert_eq!(pre.bump(Bump::Patch).to_string(), "2.0.0");
+        for bad in ["1.0", "1", "1.02.0", "1.2.3-", "v1.2.3", "1.2.3.4"] {
+            assert!(bad.parse::<Semver>().is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn declared_ranges_in_both_loader_syntaxes() {
+        assert_eq!(declared_range_holds("1.5", "[1.2,2.0)"), Some(true));
+        assert_eq!(declared_range_holds("2.0", "[1.2,2.0)"), Some(false));
+        assert_eq!(declared_range_holds("0.9", "(,1.0]"), Some(true));
+        assert_eq!(declared_range_holds("1.2.0", "[1.2]"), Some(true));
+        assert_eq!(declared_range_holds("0.5.3", ">=0.5 <0.6"), Some(true));
+        assert_eq!(declared_range_holds("1.9", "^1.2"), Some(true));
+        assert_eq!(declared_range_holds("1.3.0", "~1.2.3"), Some(false));
+        assert_eq!(declared_range_holds("3.0", "<1.0 || >=3"), Some(true));
+        assert_eq!(declared_range_holds("anything", "*"), Some(true));
+        assert_eq!(declared_range_holds("1.20.1", "1.20.x"), None);
+    }
+}